@@ -1,12 +1,14 @@
 // SPDX-FileCopyrightText: 2025 HalfSweet
 // SPDX-License-Identifier: Apache-2.0
 
-use clap::{Arg, ArgMatches, ColorChoice, Command, value_parser};
+use clap::{Arg, ArgAction, ArgMatches, ColorChoice, Command, value_parser};
 use clap::builder::styling;
+use clap::parser::ValueSource;
 use rust_i18n::t;
 
 pub fn cli_command() -> Command {
     let language = Arg::new("language")
+        .global(true)
         .short('l')
         .long("language")
         .help(t!("root_language_help").to_string())
@@ -14,6 +16,7 @@ pub fn cli_command() -> Command {
         .default_value("auto");
 
     let EDA = Arg::new("EDA")
+        .global(true)
         .short('e')
         .long("eda")
         .help(t!("root_EDA_help").to_string())
@@ -21,6 +24,7 @@ pub fn cli_command() -> Command {
         .default_value("auto");
 
     let path = Arg::new("path")
+        .global(true)
         .short('p')
         .long("path")
         .help(t!("root_path_help").to_string())
@@ -28,6 +32,7 @@ pub fn cli_command() -> Command {
         .default_value(".");
 
     let output_path = Arg::new("output_path")
+        .global(true)
         .short('o')
         .long("output_path")
         .help(t!("root_output_path_help").to_string())
@@ -35,6 +40,7 @@ pub fn cli_command() -> Command {
         .default_value("./output");
 
     let zip = Arg::new("zip")
+        .global(true)
         .short('z')
         .long("zip")
         .help(t!("root_zip_help").to_string())
@@ -42,12 +48,193 @@ pub fn cli_command() -> Command {
         .default_value("false");
 
     let zip_name = Arg::new("zip_name")
+        .global(true)
         .short('n')
         .long("zip_name")
         .help(t!("root_zip_name_help").to_string())
         .value_parser(value_parser! { String })
         .default_value("Gerber");
 
+    let zip_compression = Arg::new("zip_compression")
+        .global(true)
+        .long("zip_compression")
+        .help(t!("root_zip_compression_help").to_string())
+        .value_parser(["stored", "deflate", "zstd"])
+        .default_value("stored");
+
+    let zip_timestamp = Arg::new("zip_timestamp")
+        .global(true)
+        .long("zip_timestamp")
+        .help(t!("root_zip_timestamp_help").to_string())
+        .value_parser(value_parser! { String });
+
+    let zip_folder = Arg::new("zip_folder")
+        .global(true)
+        .long("zip_folder")
+        .help(t!("root_zip_folder_help").to_string())
+        .value_parser(value_parser! { String });
+
+    let clean = Arg::new("clean")
+        .global(true)
+        .long("clean")
+        .help(t!("root_clean_help").to_string())
+        .action(ArgAction::SetTrue)
+        .conflicts_with_all(["no_overwrite", "force"]);
+
+    let no_overwrite = Arg::new("no_overwrite")
+        .global(true)
+        .long("no-overwrite")
+        .help(t!("root_no_overwrite_help").to_string())
+        .action(ArgAction::SetTrue)
+        .conflicts_with("force");
+
+    let force = Arg::new("force")
+        .global(true)
+        .long("force")
+        .help(t!("root_force_help").to_string())
+        .action(ArgAction::SetTrue);
+
+    let verbose = Arg::new("verbose")
+        .global(true)
+        .short('v')
+        .long("verbose")
+        .help(t!("root_verbose_help").to_string())
+        .action(ArgAction::Count)
+        .conflicts_with("quiet");
+
+    let quiet = Arg::new("quiet")
+        .global(true)
+        .short('q')
+        .long("quiet")
+        .help(t!("root_quiet_help").to_string())
+        .action(ArgAction::Count);
+
+    let log_file = Arg::new("log_file")
+        .global(true)
+        .long("log-file")
+        .help(t!("root_log_file_help").to_string())
+        .value_parser(value_parser! { String });
+
+    let log_format = Arg::new("log_format")
+        .global(true)
+        .long("log-format")
+        .help(t!("root_log_format_help").to_string())
+        .value_parser(["text", "json"])
+        .default_value("text");
+
+    let map = Arg::new("map")
+        .global(true)
+        .long("map")
+        .help(t!("root_map_help").to_string())
+        .value_name("PATTERN=LAYER")
+        .action(ArgAction::Append)
+        .value_parser(value_parser! { String });
+
+    let interactive = Arg::new("interactive")
+        .global(true)
+        .short('i')
+        .long("interactive")
+        .help(t!("root_interactive_help").to_string())
+        .action(ArgAction::SetTrue);
+
+    let positive_planes = Arg::new("positive_planes")
+        .global(true)
+        .long("positive-planes")
+        .help(t!("root_positive_planes_help").to_string())
+        .action(ArgAction::SetTrue);
+
+    let report = Arg::new("report")
+        .global(true)
+        .long("report")
+        .help(t!("root_report_help").to_string())
+        .action(ArgAction::SetTrue);
+
+    let notes_summary = Arg::new("notes_summary")
+        .global(true)
+        .long("notes-summary")
+        .help(t!("root_notes_summary_help").to_string())
+        .action(ArgAction::SetTrue);
+
+    let outline_tolerance = Arg::new("outline_tolerance")
+        .global(true)
+        .long("outline-tolerance")
+        .value_name("MM")
+        .help(t!("root_outline_tolerance_help").to_string())
+        .value_parser(positive_number)
+        .default_value("0.01");
+
+    let merge_outline = Arg::new("merge_outline")
+        .global(true)
+        .long("merge-outline")
+        .help(t!("root_merge_outline_help").to_string())
+        .value_name("PATTERN")
+        .action(ArgAction::Append)
+        .value_parser(value_parser! { String });
+
+    let generate_outline = Arg::new("generate_outline")
+        .global(true)
+        .long("generate-outline")
+        .value_name("SHAPE")
+        .help(t!("root_generate_outline_help").to_string())
+        .value_parser(value_parser! { String });
+
+    let outline_margin = Arg::new("outline_margin")
+        .global(true)
+        .long("outline-margin")
+        .value_name("MM")
+        .help(t!("root_outline_margin_help").to_string())
+        .value_parser(non_negative_number)
+        .default_value("1");
+
+    let outline_radius = Arg::new("outline_radius")
+        .global(true)
+        .long("outline-radius")
+        .value_name("MM")
+        .help(t!("root_outline_radius_help").to_string())
+        .value_parser(non_negative_number)
+        .default_value("0");
+
+    let preflight = Arg::new("preflight")
+        .global(true)
+        .long("preflight")
+        .value_name("PROFILE")
+        .help(t!("root_preflight_help").to_string())
+        .value_parser(value_parser! { String });
+
+    let import_outline = Arg::new("import_outline")
+        .global(true)
+        .long("import-outline")
+        .value_name("FILE")
+        .help(t!("root_import_outline_help").to_string())
+        .value_parser(value_parser! { String });
+
+    let import_outline_mode = Arg::new("import_outline_mode")
+        .global(true)
+        .long("import-outline-mode")
+        .help(t!("root_import_outline_mode_help").to_string())
+        .value_parser(["replace", "add"])
+        .default_value("replace");
+
+    let config = Arg::new("config")
+        .global(true)
+        .long("config")
+        .help(t!("root_config_help").to_string())
+        .value_parser(value_parser! { String });
+
+    let max_extract_size = Arg::new("max_extract_size")
+        .global(true)
+        .long("max_extract_size")
+        .help(t!("root_max_extract_size_help").to_string())
+        .value_parser(value_parser! { u64 })
+        .default_value("1024");
+
+    let max_extract_files = Arg::new("max_extract_files")
+        .global(true)
+        .long("max_extract_files")
+        .help(t!("root_max_extract_files_help").to_string())
+        .value_parser(value_parser! { usize })
+        .default_value("10000");
+
     let styles = styling::Styles::styled()
         .header(styling::AnsiColor::Green.on_default() | styling::Effects::BOLD)
         .usage(styling::AnsiColor::Green.on_default() | styling::Effects::BOLD)
@@ -65,6 +252,88 @@ pub fn cli_command() -> Command {
         .arg(output_path)
         .arg(zip)
         .arg(zip_name)
+        .arg(zip_compression)
+        .arg(zip_timestamp)
+        .arg(zip_folder)
+        .arg(clean)
+        .arg(no_overwrite)
+        .arg(force)
+        .arg(verbose)
+        .arg(quiet)
+        .arg(log_file)
+        .arg(log_format)
+        .arg(map)
+        .arg(interactive)
+        .arg(positive_planes)
+        .arg(report)
+        .arg(notes_summary)
+        .arg(outline_tolerance)
+        .arg(merge_outline)
+        .arg(generate_outline)
+        .arg(outline_margin)
+        .arg(outline_radius)
+        .arg(import_outline)
+        .arg(import_outline_mode)
+        .arg(preflight)
+        .arg(config)
+        .arg(max_extract_size)
+        .arg(max_extract_files)
+        .subcommand(
+            Command::new("config")
+                .about(t!("config_about").to_string())
+                .subcommand_required(true)
+                .subcommand(Command::new("show").about(t!("config_show_about").to_string())),
+        )
+        .subcommand(
+            Command::new("styles")
+                .about(t!("styles_about").to_string())
+                .subcommand_required(true)
+                .subcommand(Command::new("list").about(t!("styles_list_about").to_string()))
+                .subcommand(
+                    Command::new("show")
+                        .about(t!("styles_show_about").to_string())
+                        .arg(Arg::new("name").required(true)),
+                )
+                .subcommand(
+                    Command::new("test")
+                        .about(t!("styles_test_about").to_string())
+                        .arg(Arg::new("name").required(true))
+                        .arg(Arg::new("files").required(true).num_args(1..)),
+                ),
+        )
+        .subcommand(
+            Command::new("render")
+                .about(t!("render_about").to_string())
+                .arg(
+                    Arg::new("side")
+                        .long("side")
+                        .help(t!("render_side_help").to_string())
+                        .value_parser(["top", "bottom", "both"])
+                        .default_value("both"),
+                )
+                .arg(
+                    Arg::new("color")
+                        .long("color")
+                        .value_name("NAME=COLOR")
+                        .help(t!("render_color_help").to_string())
+                        .action(ArgAction::Append)
+                        .value_parser(value_parser! { String }),
+                )
+                .arg(
+                    Arg::new("png")
+                        .long("png")
+                        .help(t!("render_png_help").to_string())
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("dpi")
+                        .long("dpi")
+                        .help(t!("render_dpi_help").to_string())
+                        .value_parser(value_parser!(u32).range(1..=4800))
+                        .default_value("300"),
+                ),
+        )
+        .subcommand(Command::new("dxf").about(t!("dxf_about").to_string()))
 }
 
 pub struct TransJLC {
@@ -74,6 +343,45 @@ pub struct TransJLC {
     pub output_path: String,
     pub zip: bool,
     pub zip_name: String,
+    pub zip_compression: String,
+    pub zip_timestamp: Option<String>,
+    pub zip_folder: Option<String>,
+    pub clean: bool,
+    pub no_overwrite: bool,
+    pub force: bool,
+    pub verbose: u8,
+    pub quiet: u8,
+    pub log_file: Option<String>,
+    pub log_format: String,
+    pub config: Option<String>,
+    /// `--map` 指定的手动映射，`<通配符>=<JLC层>`
+    pub map: Vec<String>,
+    pub interactive: bool,
+    /// 是否将负片内电层转换为正片
+    pub positive_planes: bool,
+    /// 是否在输出中附带审阅报告 report.html
+    pub report: bool,
+    /// 是否把板子尺寸和铜层数写到下单必读文件中
+    pub notes_summary: bool,
+    /// 检查板框时端点相连的容差（毫米）
+    pub outline_tolerance: f64,
+    /// `--merge-outline` 指定的需要合并到板框层的文件名通配符
+    pub merge_outline: Vec<String>,
+    /// 没有板框文件时生成板框，`copper` 或 `<宽>x<高>`
+    pub generate_outline: Option<String>,
+    /// 生成板框时铜层范围之外的边距（毫米）
+    pub outline_margin: f64,
+    /// 生成板框的圆角半径（毫米）
+    pub outline_radius: f64,
+    /// 导入的 DXF 或 SVG 板框文件
+    pub import_outline: Option<String>,
+    /// 导入的板框替换（`replace`）还是添加到（`add`）EDA导出的板框
+    pub import_outline_mode: String,
+    /// 转换后检查使用的工艺能力名称
+    pub preflight: Option<String>,
+    /// 解压后总大小上限（MiB）
+    pub max_extract_size: u64,
+    pub max_extract_files: usize,
 }
 
 impl TransJLC {
@@ -88,6 +396,163 @@ impl TransJLC {
                 .to_string(),
             zip: *matches.get_one::<bool>("zip").unwrap(),
             zip_name: matches.get_one::<String>("zip_name").unwrap().to_string(),
+            zip_compression: matches
+                .get_one::<String>("zip_compression")
+                .unwrap()
+                .to_string(),
+            zip_timestamp: matches.get_one::<String>("zip_timestamp").cloned(),
+            zip_folder: matches.get_one::<String>("zip_folder").cloned(),
+            clean: matches.get_flag("clean"),
+            no_overwrite: matches.get_flag("no_overwrite"),
+            force: matches.get_flag("force"),
+            verbose: matches.get_count("verbose"),
+            quiet: matches.get_count("quiet"),
+            log_file: matches.get_one::<String>("log_file").cloned(),
+            log_format: matches.get_one::<String>("log_format").unwrap().to_string(),
+            config: matches.get_one::<String>("config").cloned(),
+            map: matches
+                .get_many::<String>("map")
+                .map(|values| values.cloned().collect())
+                .unwrap_or_default(),
+            interactive: matches.get_flag("interactive"),
+            positive_planes: matches.get_flag("positive_planes"),
+            report: matches.get_flag("report"),
+            notes_summary: matches.get_flag("notes_summary"),
+            outline_tolerance: *matches.get_one::<f64>("outline_tolerance").unwrap(),
+            merge_outline: matches
+                .get_many::<String>("merge_outline")
+                .map(|values| values.cloned().collect())
+                .unwrap_or_default(),
+            generate_outline: matches.get_one::<String>("generate_outline").cloned(),
+            outline_margin: *matches.get_one::<f64>("outline_margin").unwrap(),
+            outline_radius: *matches.get_one::<f64>("outline_radius").unwrap(),
+            import_outline: matches.get_one::<String>("import_outline").cloned(),
+            import_outline_mode: matches.get_one::<String>("import_outline_mode").unwrap().to_string(),
+            preflight: matches.get_one::<String>("preflight").cloned(),
+            max_extract_size: *matches.get_one::<u64>("max_extract_size").unwrap(),
+            max_extract_files: *matches.get_one::<usize>("max_extract_files").unwrap(),
+        }
+    }
+}
+
+/// 解析正数，例如毫米为单位的容差
+fn positive_number(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(number) if number > 0.0 && number.is_finite() => Ok(number),
+        _ => Err(format!("'{}' is not a positive number", value)),
+    }
+}
+
+/// 解析非负数，例如毫米为单位的边距
+fn non_negative_number(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(number) if number >= 0.0 && number.is_finite() => Ok(number),
+        _ => Err(format!("'{}' is not a non-negative number", value)),
+    }
+}
+
+/// 参数是否在命令行中显式指定
+fn from_command_line(matches: &ArgMatches, id: &str) -> bool {
+    matches.value_source(id) == Some(ValueSource::CommandLine)
+}
+
+impl TransJLC {
+    /// 用配置文件中的值替换没有在命令行中指定的参数
+    pub fn apply_config(&mut self, matches: &ArgMatches, config: &::TransJLC::Config) {
+        fn set<T: Clone>(matches: &ArgMatches, id: &str, field: &mut T, value: &Option<T>) {
+            if let Some(value) = value {
+                if !from_command_line(matches, id) {
+                    *field = value.clone();
+                }
+            }
+        }
+
+        set(matches, "EDA", &mut self.EDA, &config.eda);
+        set(matches, "output_path", &mut self.output_path, &config.output.path);
+        set(matches, "zip", &mut self.zip, &config.output.zip);
+        set(matches, "zip_name", &mut self.zip_name, &config.output.zip_name);
+        set(matches, "zip_compression", &mut self.zip_compression, &config.output.zip_compression);
+        set(matches, "positive_planes", &mut self.positive_planes, &config.planes.positive);
+        set(matches, "report", &mut self.report, &config.output.report);
+        set(matches, "notes_summary", &mut self.notes_summary, &config.output.notes_summary);
+        set(matches, "outline_tolerance", &mut self.outline_tolerance, &config.outline.tolerance);
+        set(matches, "outline_margin", &mut self.outline_margin, &config.outline.margin);
+        set(matches, "outline_radius", &mut self.outline_radius, &config.outline.radius);
+        set(matches, "import_outline_mode", &mut self.import_outline_mode, &config.outline.import_mode);
+        if let Some(file) = &config.outline.import {
+            set(matches, "import_outline", &mut self.import_outline, &Some(Some(file.clone())));
+        }
+        if let Some(profile) = &config.preflight.profile {
+            set(matches, "preflight", &mut self.preflight, &Some(Some(profile.clone())));
+        }
+        if let Some(shape) = &config.outline.generate {
+            set(matches, "generate_outline", &mut self.generate_outline, &Some(Some(shape.clone())));
+        }
+        if let Some(timestamp) = &config.output.zip_timestamp {
+            set(matches, "zip_timestamp", &mut self.zip_timestamp, &Some(Some(timestamp.clone())));
+        }
+        if let Some(folder) = &config.output.zip_folder {
+            set(matches, "zip_folder", &mut self.zip_folder, &Some(Some(folder.clone())));
+        }
+
+        // 命令行中指定了任意一个覆盖策略时忽略配置文件中的策略
+        let policy_given = ["clean", "no_overwrite", "force"]
+            .iter()
+            .any(|id| from_command_line(matches, id));
+        if let (Some(policy), false) = (&config.output.overwrite, policy_given) {
+            self.clean = policy == "clean";
+            self.no_overwrite = policy == "no-overwrite";
+            self.force = policy == "force";
+        }
+    }
+
+    /// 当前生效的设置，用于 `config show`
+    pub fn effective_config(&self, config: &::TransJLC::Config) -> ::TransJLC::Config {
+        let overwrite = if self.clean {
+            "clean"
+        } else if self.no_overwrite {
+            "no-overwrite"
+        } else if self.force {
+            "force"
+        } else {
+            "safe"
+        };
+
+        let mut effective = config.clone();
+        effective.eda = Some(self.EDA.clone());
+        effective.output.path = Some(self.output_path.clone());
+        effective.output.zip = Some(self.zip);
+        effective.output.zip_name = Some(self.zip_name.clone());
+        effective.output.zip_compression = Some(self.zip_compression.clone());
+        effective.output.zip_timestamp = self.zip_timestamp.clone();
+        effective.output.zip_folder = self.zip_folder.clone();
+        effective.output.overwrite = Some(overwrite.to_string());
+        effective.fingerprint.hash_aperture = Some(config.fingerprint.hash_aperture.unwrap_or(true));
+        effective.planes.positive = Some(self.positive_planes);
+        effective.output.report = Some(self.report);
+        effective.output.notes_summary = Some(self.notes_summary);
+        effective.outline.tolerance = Some(self.outline_tolerance);
+        effective.outline.merge = self.merge_outline.clone();
+        effective.outline.generate = self.generate_outline.clone();
+        effective.outline.import = self.import_outline.clone();
+        effective.outline.import_mode = Some(self.import_outline_mode.clone());
+        effective.outline.margin = Some(self.outline_margin);
+        effective.outline.radius = Some(self.outline_radius);
+        effective.preflight.profile = self.preflight.clone();
+        effective.outline.merge.extend(config.outline.merge.iter().cloned());
+
+        // 命令行中的映射排在配置文件的映射之前
+        let mut map = indexmap::IndexMap::new();
+        for mapping in &self.map {
+            if let Some((pattern, target)) = mapping.rsplit_once('=') {
+                map.entry(pattern.trim().to_string())
+                    .or_insert_with(|| target.trim().to_string());
+            }
+        }
+        for (pattern, target) in &config.map {
+            map.entry(pattern.clone()).or_insert_with(|| target.clone());
         }
+        effective.map = map;
+        effective
     }
 }
@@ -1,7 +1,8 @@
 // SPDX-FileCopyrightText: 2025 HalfSweet
 // SPDX-License-Identifier: Apache-2.0
 
-#[allow(non_snake_case)]
+#![allow(non_snake_case)]
+
 use lazy_static::lazy_static;
 
 #[derive(Debug, Clone)]
@@ -26,6 +27,9 @@ pub struct FileName {
 
     pub InnerLayer: &'static str,
 
+    /// 内电层（负片），转换为JLC的内层，例如 Altium 的 `.GP1`
+    pub InnerPlane: &'static str,
+
     pub InnerLayer_Templete: &'static str,
 
     pub Other: Vec<&'static str>,
@@ -50,6 +54,7 @@ impl IntoIterator for FileName {
             ("Top_Layer", vec![self.Top_Layer]),
             ("Board_Outline", vec![self.Board_Outline]),
             ("InnerLayer", vec![self.InnerLayer]),
+            ("InnerPlane", vec![self.InnerPlane]),
             ("Other", self.Other),
         ]
         .into_iter()
@@ -72,11 +77,32 @@ impl FileName {
             "Top_Layer" => Some(self.Top_Layer),
             "Board_Outline" => Some(self.Board_Outline),
             "InnerLayer" => Some(self.InnerLayer),
+            "InnerPlane" => Some(self.InnerPlane),
             _ => None,
         }
     }
 }
 
+/// 所有JLC层名称，顺序与 `IntoIterator` 一致（不含 `Other`）
+///
+/// `InnerLayer` 和 `InnerPlane` 只用于匹配文件名，映射时使用带层号的 `InnerLayer<N>`。
+pub const KEYS: [&str; 14] = [
+    "NPTH_Through",
+    "PTH_Through",
+    "PTH_Through_Via",
+    "Bottom_Silkscreen",
+    "Bottom_Soldermask",
+    "Bottom_PasteMask",
+    "Bottom_Layer",
+    "Top_Silkscreen",
+    "Top_Soldermask",
+    "Top_PasteMask",
+    "Top_Layer",
+    "Board_Outline",
+    "InnerLayer",
+    "InnerPlane",
+];
+
 lazy_static! {
     pub static ref JLC_STYLE: FileName = FileName {
         EDA_Name: "JLC",
@@ -99,6 +125,8 @@ lazy_static! {
 
         InnerLayer: "^Gerber_InnerLayer(\\d+)\\.G(\\d+)$",
 
+        InnerPlane: "null",
+
         InnerLayer_Templete: "Gerber_InnerLayer{0}.G{1}",
 
         Other: vec!["null"],
@@ -125,7 +153,9 @@ lazy_static! {
 
         Board_Outline: "-Edge.Cuts",
 
-        InnerLayer: "In\\d+_Cu\\.g\\d+$",
+        InnerLayer: "-In(\\d+)_Cu",
+
+        InnerPlane: "null",
 
         InnerLayer_Templete: "In{0}_Cu.g{1}",
 
@@ -153,7 +183,9 @@ lazy_static! {
 
         Board_Outline: "null",
 
-        InnerLayer: "null",
+        InnerLayer: "(?i)\\.g(\\d+)$",
+
+        InnerPlane: "(?i)\\.gp(\\d+)$",
 
         InnerLayer_Templete: "null",
 
@@ -164,3 +196,8 @@ lazy_static! {
 lazy_static! {
     pub static ref ALL_STYLE: [&'static FileName; 3] = [&JLC_STYLE, &KICAD_STYLE, &PROTEL_STYLE];
 }
+
+lazy_static! {
+    /// JLC内层文件名的正则表达式，只编译一次
+    pub static ref JLC_INNER_LAYER_RE: regex::Regex = regex::Regex::new(JLC_STYLE.InnerLayer).unwrap();
+}
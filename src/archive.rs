@@ -0,0 +1,544 @@
+// SPDX-FileCopyrightText: 2025 HalfSweet
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io::{Read, Seek};
+use std::path::{Component, Path, PathBuf};
+
+use crate::log::LOG;
+
+/// 解压时的安全限制，用于防御 zip 炸弹
+#[derive(Debug, Clone, Copy)]
+pub struct ExtractLimits {
+    /// 解压后所有文件的总大小上限（字节）
+    pub max_total_size: u64,
+
+    /// 压缩包内条目数量上限
+    pub max_entries: usize,
+}
+
+impl Default for ExtractLimits {
+    fn default() -> Self {
+        Self {
+            max_total_size: 1024 * 1024 * 1024,
+            max_entries: 10_000,
+        }
+    }
+}
+
+/// 支持的输入压缩包格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+    TarXz,
+    TarZst,
+    SevenZ,
+}
+
+impl ArchiveKind {
+    /// 根据文件头部的魔数判断压缩包格式，不依赖扩展名
+    ///
+    /// gzip、xz 和 zstd 压缩流会解压开头的一个块，只有其中是 tar 头时才视为压缩包，
+    /// 单独压缩的 `.gbr.gz` 等文件不是压缩包。
+    pub fn detect(path: &Path) -> Result<Option<ArchiveKind>, std::io::Error> {
+        let mut header = Vec::with_capacity(512);
+        std::fs::File::open(path)?
+            .take(512)
+            .read_to_end(&mut header)?;
+        let kind = Self::from_magic(&header);
+
+        let file = std::fs::File::open(path)?;
+        let reader: Box<dyn Read> = match kind {
+            Some(ArchiveKind::TarGz) => Box::new(flate2::read::MultiGzDecoder::new(file)),
+            Some(ArchiveKind::TarXz) => Box::new(xz2::read::XzDecoder::new_multi_decoder(file)),
+            Some(ArchiveKind::TarZst) => Box::new(zstd::stream::read::Decoder::new(file)?),
+            _ => return Ok(kind),
+        };
+        let mut block = Vec::with_capacity(512);
+        // 损坏的压缩流仍按压缩包处理，由解压时报告错误
+        if reader.take(512).read_to_end(&mut block).is_err() {
+            return Ok(kind);
+        }
+        Ok(kind.filter(|_| is_tar_header(&block)))
+    }
+
+    /// 根据魔数字节判断压缩包格式
+    ///
+    /// 压缩流只根据压缩格式的魔数判断，其中是否为 tar 由 [`ArchiveKind::detect`] 确认。
+    pub fn from_magic(header: &[u8]) -> Option<ArchiveKind> {
+        const MAGICS: [(&[u8], ArchiveKind); 6] = [
+            (b"PK\x03\x04", ArchiveKind::Zip),
+            // 空的ZIP文件只有中央目录结束记录
+            (b"PK\x05\x06", ArchiveKind::Zip),
+            (b"\x1f\x8b", ArchiveKind::TarGz),
+            (b"\xfd7zXZ\x00", ArchiveKind::TarXz),
+            (b"\x28\xb5\x2f\xfd", ArchiveKind::TarZst),
+            (b"7z\xbc\xaf\x27\x1c", ArchiveKind::SevenZ),
+        ];
+
+        MAGICS
+            .iter()
+            .find(|(magic, _)| header.starts_with(magic))
+            .map(|(_, kind)| *kind)
+            // tar 在文件开头没有魔数，检查偏移 257 处的 "ustar"
+            .or_else(|| is_tar_header(header).then_some(ArchiveKind::Tar))
+    }
+}
+
+/// 是否以 tar 头开始：tar 的魔数 "ustar" 不在文件开头，而在偏移 257 处；
+/// 没有魔数的旧格式头按校验和判断
+fn is_tar_header(header: &[u8]) -> bool {
+    if header.get(257..262) == Some(b"ustar".as_slice()) {
+        return true;
+    }
+    if header.len() < 512 {
+        return false;
+    }
+    // 校验和按校验和字段本身为空格计算，字段中是八进制数
+    let stored = std::str::from_utf8(&header[148..156])
+        .ok()
+        .map(|field| field.trim_matches(|c: char| c == '\0' || c == ' '))
+        .and_then(|field| u32::from_str_radix(field, 8).ok());
+    let sum: u32 = header[..512]
+        .iter()
+        .enumerate()
+        .map(|(i, byte)| if (148..156).contains(&i) { b' ' as u32 } else { *byte as u32 })
+        .sum();
+    stored == Some(sum)
+}
+
+fn invalid_data(msg: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, msg)
+}
+
+/// 嵌套压缩包允许的最大层数
+const MAX_NESTING_DEPTH: usize = 8;
+
+/// 根据格式将压缩包安全地解压到 `dest` 目录
+///
+/// 解压出来的文件中如果还有压缩包，会递归解压到同名目录中并删除原压缩包，
+/// 所有层级共享同一份大小和数量限制。只有带压缩包扩展名的文件才会解压，
+/// `.xlsx`、`.docx` 等基于ZIP的文档保持原样。
+pub fn extract(
+    path: &Path,
+    kind: ArchiveKind,
+    dest: &Path,
+    limits: &ExtractLimits,
+) -> Result<(), std::io::Error> {
+    let mut extractor = Extractor::new(dest, limits);
+    extractor.archive(path, kind)?;
+    extractor.nested(dest, 1)
+}
+
+/// 各种格式共用的解压状态，负责路径检查和大小、数量限制
+struct Extractor<'a> {
+    dest: PathBuf,
+    limits: &'a ExtractLimits,
+    total_size: u64,
+    entries: usize,
+}
+
+impl<'a> Extractor<'a> {
+    fn new(dest: &Path, limits: &'a ExtractLimits) -> Self {
+        Self {
+            dest: dest.to_path_buf(),
+            limits,
+            total_size: 0,
+            entries: 0,
+        }
+    }
+
+    /// 按格式解压一个压缩包文件到当前的 `dest`
+    fn archive(&mut self, path: &Path, kind: ArchiveKind) -> Result<(), std::io::Error> {
+        let file = std::fs::File::open(path)?;
+        match kind {
+            ArchiveKind::Zip => self.zip(file),
+            ArchiveKind::Tar => self.tar(file),
+            ArchiveKind::TarGz => self.tar(flate2::read::MultiGzDecoder::new(file)),
+            ArchiveKind::TarXz => self.tar(xz2::read::XzDecoder::new_multi_decoder(file)),
+            ArchiveKind::TarZst => self.tar(zstd::stream::read::Decoder::new(file)?),
+            ArchiveKind::SevenZ => self.seven_z(file),
+        }
+    }
+
+    /// 在 `dir` 中查找嵌套的压缩包并递归解压
+    fn nested(&mut self, dir: &Path, depth: usize) -> Result<(), std::io::Error> {
+        let mut entries = std::fs::read_dir(dir)?
+            .map(|res| res.map(|e| e.path()))
+            .collect::<Result<Vec<_>, std::io::Error>>()?;
+        entries.sort();
+
+        for path in entries {
+            if path.is_dir() {
+                self.nested(&path, depth)?;
+                continue;
+            }
+
+            let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            if strip_archive_extension(&name) == name {
+                continue;
+            }
+            let Some(kind) = ArchiveKind::detect(&path)? else {
+                continue;
+            };
+
+            if depth >= MAX_NESTING_DEPTH {
+                return Err(invalid_data(format!(
+                    "Archive nesting exceeds {} levels at '{}'",
+                    MAX_NESTING_DEPTH,
+                    path.display()
+                )));
+            }
+
+            LOG.info_no_color(&format!("Extracting nested archive: {}", path.display()));
+            let target = nested_target(&path);
+            std::fs::create_dir_all(&target)?;
+            self.dest = target.clone();
+            self.archive(&path, kind)?;
+            std::fs::remove_file(&path)?;
+            self.nested(&target, depth + 1)?;
+        }
+
+        Ok(())
+    }
+
+    /// 计数一个条目，超过数量限制时返回错误
+    fn count_entry(&mut self) -> Result<(), std::io::Error> {
+        self.entries += 1;
+        if self.entries > self.limits.max_entries {
+            return Err(invalid_data(format!(
+                "Archive contains more than {} entries",
+                self.limits.max_entries
+            )));
+        }
+        Ok(())
+    }
+
+    /// 将条目名称转换为解压目录下的路径，拒绝绝对路径和上级目录穿越
+    fn enclosed_path(&self, name: &Path) -> Result<PathBuf, std::io::Error> {
+        let mut relative = PathBuf::new();
+        for component in name.components() {
+            match component {
+                Component::Normal(part) => relative.push(part),
+                Component::CurDir => {}
+                _ => {
+                    return Err(invalid_data(format!(
+                        "Archive entry '{}' has an unsafe path (absolute or parent traversal)",
+                        name.display()
+                    )))
+                }
+            }
+        }
+        Ok(self.dest.join(relative))
+    }
+
+    fn create_dir(&self, name: &Path) -> Result<(), std::io::Error> {
+        std::fs::create_dir_all(self.enclosed_path(name)?)
+    }
+
+    /// 写出一个文件条目，实际读取的字节数受剩余大小限制，防止头部信息作假
+    fn write_file(
+        &mut self,
+        name: &Path,
+        declared_size: u64,
+        reader: &mut dyn Read,
+    ) -> Result<(), std::io::Error> {
+        let outpath = self.enclosed_path(name)?;
+
+        let remaining = self.limits.max_total_size.saturating_sub(self.total_size);
+        if declared_size > remaining {
+            return Err(self.size_exceeded());
+        }
+
+        if let Some(p) = outpath.parent() {
+            std::fs::create_dir_all(p)?;
+        }
+
+        let mut outfile = std::fs::File::create(&outpath)?;
+        let written = std::io::copy(&mut reader.take(remaining + 1), &mut outfile)?;
+        if written > remaining {
+            drop(outfile);
+            let _ = std::fs::remove_file(&outpath);
+            return Err(self.size_exceeded());
+        }
+        self.total_size += written;
+        Ok(())
+    }
+
+    fn size_exceeded(&self) -> std::io::Error {
+        invalid_data(format!(
+            "Archive exceeds the decompressed size limit of {} bytes",
+            self.limits.max_total_size
+        ))
+    }
+
+    fn zip<R: Read + Seek>(&mut self, reader: R) -> Result<(), std::io::Error> {
+        let mut archive =
+            zip::ZipArchive::new(reader).map_err(|e| invalid_data(e.to_string()))?;
+
+        for i in 0..archive.len() {
+            self.count_entry()?;
+            let mut file = archive
+                .by_index(i)
+                .map_err(|e| invalid_data(e.to_string()))?;
+
+            // 只接受位于解压目录内部的相对路径
+            let relative = file.enclosed_name().ok_or_else(|| {
+                invalid_data(format!(
+                    "Archive entry '{}' has an unsafe path (absolute or parent traversal)",
+                    file.name()
+                ))
+            })?;
+
+            if file.is_symlink() {
+                LOG.warn(&format!("Skipping symbolic link in archive: {}", file.name()));
+            } else if file.is_dir() {
+                self.create_dir(&relative)?;
+            } else {
+                let size = file.size();
+                self.write_file(&relative, size, &mut file)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn tar<R: Read>(&mut self, reader: R) -> Result<(), std::io::Error> {
+        let mut archive = tar::Archive::new(reader);
+
+        for entry in archive.entries()? {
+            self.count_entry()?;
+            let mut entry = entry?;
+            let name = entry.path()?.into_owned();
+            let entry_type = entry.header().entry_type();
+
+            if entry_type.is_symlink() || entry_type.is_hard_link() {
+                LOG.warn(&format!("Skipping link in archive: {}", name.display()));
+            } else if entry_type.is_dir() {
+                self.create_dir(&name)?;
+            } else if entry_type.is_file() {
+                let size = entry.size();
+                self.write_file(&name, size, &mut entry)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn seven_z<R: Read + Seek>(&mut self, mut reader: R) -> Result<(), std::io::Error> {
+        // Windows 属性高16位存放 unix 权限时的标志位
+        const UNIX_EXTENSION: u32 = 0x8000;
+        const S_IFMT: u32 = 0o170000;
+        const S_IFLNK: u32 = 0o120000;
+
+        let len = reader.seek(std::io::SeekFrom::End(0))?;
+        reader.seek(std::io::SeekFrom::Start(0))?;
+        let mut archive =
+            sevenz_rust::SevenZReader::new(reader, len, sevenz_rust::Password::empty())
+                .map_err(|e| invalid_data(e.to_string()))?;
+
+        // 回调内部的IO错误需要原样带出，以保留错误类型
+        let mut failure: Option<std::io::Error> = None;
+        archive
+            .for_each_entries(|entry, data| {
+                let result = self.count_entry().and_then(|_| {
+                    let name = Path::new(entry.name());
+                    let attributes = entry.windows_attributes;
+                    let is_symlink = entry.has_windows_attributes
+                        && attributes & UNIX_EXTENSION != 0
+                        && (attributes >> 16) & S_IFMT == S_IFLNK;
+
+                    if entry.is_directory {
+                        self.create_dir(name)
+                    } else if is_symlink || entry.is_anti_item {
+                        LOG.warn(&format!("Skipping link in archive: {}", entry.name()));
+                        // 跳过的条目仍需读完，后续条目才能从正确的位置开始
+                        std::io::copy(data, &mut std::io::sink()).map(|_| ())
+                    } else {
+                        self.write_file(name, entry.size, data)
+                    }
+                });
+
+                match result {
+                    Ok(()) => Ok(true),
+                    Err(e) => {
+                        failure = Some(e);
+                        Ok(false)
+                    }
+                }
+            })
+            .map_err(|e| invalid_data(e.to_string()))?;
+
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 去掉文件名末尾的压缩包扩展名（如 `.tar.gz`），没有时原样返回
+///
+/// 先去掉压缩格式的扩展名，再去掉归档格式的扩展名，所有压缩方式的结果一致。
+pub(crate) fn strip_archive_extension(name: &str) -> &str {
+    const COMPRESSIONS: [&str; 3] = [".gz", ".xz", ".zst"];
+    const ARCHIVES: [&str; 6] = [".tgz", ".txz", ".tzst", ".tar", ".zip", ".7z"];
+
+    fn strip<'a>(name: &'a str, suffixes: &[&str]) -> &'a str {
+        suffixes
+            .iter()
+            .filter(|suffix| name.len() > suffix.len())
+            .map(|suffix| (name.len() - suffix.len(), suffix))
+            .find(|(start, suffix)| name.is_char_boundary(*start) && name[*start..].eq_ignore_ascii_case(suffix))
+            .map_or(name, |(start, _)| &name[..start])
+    }
+    strip(strip(name, &COMPRESSIONS), &ARCHIVES)
+}
+
+/// 嵌套压缩包的解压目录：去掉压缩扩展名后的同名目录，已存在时追加后缀
+fn nested_target(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let stem = strip_archive_extension(&name);
+
+    let mut target = path.with_file_name(stem);
+    let mut index = 1;
+    while target.exists() {
+        target = path.with_file_name(format!("{}_{}", stem, index));
+        index += 1;
+    }
+    target
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use zip::write::SimpleFileOptions;
+
+    fn build_zip(entries: &[(&str, &[u8])]) -> Cursor<Vec<u8>> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data) in entries {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        let mut cursor = zip.finish().unwrap();
+        cursor.set_position(0);
+        cursor
+    }
+
+    fn build_tar(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, data) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, *data).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    #[test]
+    fn rejects_parent_traversal() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let archive = build_zip(&[("../evil.txt", b"x")]);
+        let err = Extractor::new(dir.path(), &ExtractLimits::default()).zip(archive).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(!dir.path().parent().unwrap().join("evil.txt").exists());
+    }
+
+    #[test]
+    fn enforces_limits() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let limits = ExtractLimits {
+            max_total_size: 4,
+            max_entries: 10,
+        };
+        let archive = build_zip(&[("a.gbr", b"12345678")]);
+        assert!(Extractor::new(dir.path(), &limits).zip(archive).is_err());
+
+        let limits = ExtractLimits {
+            max_total_size: 1024,
+            max_entries: 1,
+        };
+        let archive = build_zip(&[("a.gbr", b"1"), ("b.gbr", b"2")]);
+        assert!(Extractor::new(dir.path(), &limits).zip(archive).is_err());
+    }
+
+    #[test]
+    fn extracts_nested_entries() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let archive = build_zip(&[("gerber/board-F_Cu.gbr", b"G04*")]);
+        Extractor::new(dir.path(), &ExtractLimits::default()).zip(archive).unwrap();
+        assert!(dir.path().join("gerber/board-F_Cu.gbr").is_file());
+    }
+
+    #[test]
+    fn strips_every_archive_extension() {
+        for name in [
+            "board.zip", "board.7z", "board.tar", "board.tgz", "board.TXZ", "board.tar.gz",
+            "board.tar.xz", "board.Tar.Zst", "board.gz", "board.xz", "board.zst",
+        ] {
+            assert_eq!(strip_archive_extension(name), "board", "{}", name);
+        }
+        assert_eq!(strip_archive_extension("board.gbr"), "board.gbr");
+        assert_eq!(strip_archive_extension(".zip"), ".zip");
+    }
+
+    #[test]
+    fn detects_formats_by_magic() {
+        let tar = build_tar(&[("board-F_Cu.gbr", b"G04*")]);
+        assert_eq!(ArchiveKind::from_magic(&tar), Some(ArchiveKind::Tar));
+        assert_eq!(
+            ArchiveKind::from_magic(build_zip(&[]).get_ref()),
+            Some(ArchiveKind::Zip)
+        );
+
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(&tar).unwrap();
+        let gz = gz.finish().unwrap();
+        assert_eq!(ArchiveKind::from_magic(&gz), Some(ArchiveKind::TarGz));
+
+        let zst = zstd::stream::encode_all(tar.as_slice(), 0).unwrap();
+        assert_eq!(ArchiveKind::from_magic(&zst), Some(ArchiveKind::TarZst));
+        assert_eq!(ArchiveKind::from_magic(b"G04 not an archive*"), None);
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("gerbers.bin");
+        std::fs::write(&path, &gz).unwrap();
+        assert_eq!(ArchiveKind::detect(&path).unwrap(), Some(ArchiveKind::TarGz));
+
+        // 单独压缩的Gerber文件不是压缩包
+        let mut gbr = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gbr.write_all(&[b"G04 plain layer*\n".as_slice(); 64].concat()).unwrap();
+        let plain = dir.path().join("board-F_Cu.gbr.gz");
+        std::fs::write(&plain, gbr.finish().unwrap()).unwrap();
+        assert_eq!(ArchiveKind::detect(&plain).unwrap(), None);
+
+        let out = dir.path().join("out");
+        extract(&path, ArchiveKind::TarGz, &out, &ExtractLimits::default()).unwrap();
+        assert!(out.join("board-F_Cu.gbr").is_file());
+    }
+
+    #[test]
+    fn extracts_nested_archives() {
+        let inner = build_zip(&[("board-F_Cu.gbr", b"G04*")]).into_inner();
+        let outer = build_zip(&[("gerbers.zip", &inner), ("drill/board.drl", b"M48"), ("BOM.xlsx", &inner)]);
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("order.zip");
+        std::fs::write(&path, outer.get_ref()).unwrap();
+        let out = dir.path().join("out");
+        extract(&path, ArchiveKind::Zip, &out, &ExtractLimits::default()).unwrap();
+
+        assert!(out.join("gerbers/board-F_Cu.gbr").is_file());
+        assert!(out.join("drill/board.drl").is_file());
+        assert!(!out.join("gerbers.zip").exists());
+        // 基于ZIP的文档不解压
+        assert!(out.join("BOM.xlsx").is_file());
+        assert!(!out.join("BOM").exists());
+    }
+}
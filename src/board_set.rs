@@ -0,0 +1,192 @@
+// SPDX-FileCopyrightText: 2025 HalfSweet
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+
+use crate::log::LOG;
+use crate::FileName::ALL_STYLE;
+
+/// Protel 风格没有板框的文件名规则，按 Altium 常用的板框扩展名识别板子
+const PROTEL_OUTLINE: &str = r"(?i)\.(gko|gm1)$";
+
+/// 一组属于同一块电路板的Gerber/钻孔文件
+#[derive(Debug, Clone)]
+pub struct BoardSet {
+    /// 输出名称，取自板子所在的文件夹
+    pub name: String,
+
+    /// 板子所在的文件夹
+    pub root: PathBuf,
+
+    /// 属于这块板子的所有文件（包含子文件夹中的文件）
+    pub files: Vec<PathBuf>,
+}
+
+/// 递归列出 `dir` 下的所有文件，按路径排序
+pub fn list_files_recursive(dir: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+    let mut files = vec![];
+    let mut entries = std::fs::read_dir(dir)?
+        .map(|res| res.map(|e| e.path()))
+        .collect::<Result<Vec<_>, std::io::Error>>()?;
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            files.extend(list_files_recursive(&path)?);
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// 在解压后的目录中查找独立的板子
+///
+/// 含有边框层文件（任意风格的板框规则，或者 `.GKO`、`.GM1` 文件）的文件夹被视为一块板子，并向上扩展到不包含其他板子的最高一级文件夹，
+/// 这样 `board/gerber` 和 `board/drill` 会被归为同一块板子。
+/// 只找到一块（或没有找到）板子时，返回包含全部文件的单个集合，名称为 `default_name`。
+pub fn find_board_sets(root: &Path, default_name: &str) -> Result<Vec<BoardSet>, std::io::Error> {
+    let files = list_files_recursive(root)?;
+
+    let outline_res: Vec<Regex> = ALL_STYLE
+        .iter()
+        .map(|style| style.Board_Outline)
+        .filter(|pattern| !pattern.is_empty() && *pattern != "null")
+        .chain([PROTEL_OUTLINE])
+        .map(|pattern| Regex::new(pattern).unwrap())
+        .collect();
+
+    let mut board_dirs: Vec<PathBuf> = files
+        .iter()
+        .filter(|file| {
+            let name = file.file_name().unwrap().to_string_lossy();
+            outline_res.iter().any(|re| re.is_match(&name))
+        })
+        .filter_map(|file| file.parent().map(Path::to_path_buf))
+        .collect();
+    board_dirs.sort();
+    board_dirs.dedup();
+
+    if board_dirs.len() <= 1 {
+        return Ok(vec![BoardSet {
+            name: default_name.to_string(),
+            root: root.to_path_buf(),
+            files,
+        }]);
+    }
+
+    // 向上扩展到不包含其他板子的最高一级文件夹
+    let mut roots: Vec<PathBuf> = board_dirs
+        .iter()
+        .map(|dir| {
+            let mut current = dir.clone();
+            while current != root {
+                let parent = match current.parent() {
+                    Some(parent) => parent,
+                    None => break,
+                };
+                let boards_inside = board_dirs.iter().filter(|d| d.starts_with(parent)).count();
+                if boards_inside > 1 {
+                    break;
+                }
+                current = parent.to_path_buf();
+            }
+            current
+        })
+        .collect();
+    roots.sort();
+    roots.dedup();
+
+    let mut sets: Vec<BoardSet> = roots
+        .iter()
+        .map(|board_root| BoardSet {
+            name: board_name(root, board_root, default_name),
+            root: board_root.clone(),
+            files: vec![],
+        })
+        .collect();
+
+    // 每个文件归属到路径最深的那块板子
+    for file in files {
+        let owner = sets
+            .iter_mut()
+            .filter(|set| file.starts_with(&set.root))
+            .max_by_key(|set| set.root.components().count());
+        match owner {
+            Some(set) => set.files.push(file),
+            None => LOG.warn(&format!("Ignoring file outside of any board: {}", file.display())),
+        }
+    }
+
+    Ok(sets)
+}
+
+/// 由板子文件夹相对于解压根目录的路径生成输出名称
+fn board_name(root: &Path, board_root: &Path, default_name: &str) -> String {
+    let relative = board_root.strip_prefix(root).unwrap_or(board_root);
+    let parts: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    if parts.is_empty() {
+        default_name.to_string()
+    } else {
+        parts.join("_")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn touch(root: &Path, name: &str) {
+        let path = root.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, "G04*").unwrap();
+    }
+
+    #[test]
+    fn single_board_with_drill_folder() {
+        let dir = tempfile::TempDir::new().unwrap();
+        touch(dir.path(), "gerbers/board-Edge.Cuts.gm1");
+        touch(dir.path(), "gerbers/board-F_Cu.gtl");
+        touch(dir.path(), "drill/board-PTH.drl");
+
+        let sets = find_board_sets(dir.path(), "Gerber").unwrap();
+        assert_eq!(sets.len(), 1);
+        assert_eq!(sets[0].name, "Gerber");
+        assert_eq!(sets[0].files.len(), 3);
+    }
+
+    #[test]
+    fn multiple_boards_in_folders() {
+        let dir = tempfile::TempDir::new().unwrap();
+        touch(dir.path(), "main/gerber/main-Edge.Cuts.gm1");
+        touch(dir.path(), "main/drill/main-PTH.drl");
+        touch(dir.path(), "sensor/sensor-Edge.Cuts.gm1");
+        touch(dir.path(), "sensor/sensor-F_Cu.gtl");
+
+        let sets = find_board_sets(dir.path(), "Gerber").unwrap();
+        let names: Vec<&str> = sets.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["main", "sensor"]);
+        assert_eq!(sets[0].files.len(), 2);
+        assert_eq!(sets[1].files.len(), 2);
+    }
+
+    #[test]
+    fn multiple_protel_boards() {
+        let dir = tempfile::TempDir::new().unwrap();
+        touch(dir.path(), "main/main.GTL");
+        touch(dir.path(), "main/main.GKO");
+        touch(dir.path(), "main/main.TXT");
+        touch(dir.path(), "sensor/sensor.GTL");
+        touch(dir.path(), "sensor/sensor.GM1");
+
+        let sets = find_board_sets(dir.path(), "Gerber").unwrap();
+        let names: Vec<&str> = sets.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["main", "sensor"]);
+        assert_eq!(sets[0].files.len(), 3);
+    }
+}
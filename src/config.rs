@@ -0,0 +1,692 @@
+// SPDX-FileCopyrightText: 2025 HalfSweet
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use indexmap::IndexMap;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::mapping::{FileMapping, MapTarget};
+use crate::outline::OutlineShape;
+use crate::preflight::{Profile, BUILTIN_PROFILES};
+use crate::render::Colors;
+use crate::FileName::JLC_STYLE;
+
+/// 工程配置文件名，放在输入文件夹（或压缩包所在文件夹）中
+pub const PROJECT_CONFIG_NAME: &str = ".transjlc.toml";
+
+/// 配置文件内容，所有字段都是可选的，未设置时使用命令行参数的默认值
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// EDA 风格，与 `--eda` 相同
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eda: Option<String>,
+
+    /// 输出设置
+    pub output: OutputConfig,
+
+    /// 文件指纹设置
+    pub fingerprint: FingerprintConfig,
+
+    /// 内电层设置
+    pub planes: PlanesConfig,
+
+    /// 渲染设置
+    pub render: RenderConfig,
+
+    /// 板框检查设置
+    pub outline: OutlineConfig,
+
+    /// 工艺能力检查设置
+    pub preflight: PreflightConfig,
+
+    /// 附加的文件名规则：JLC层名称（例如 `Board_Outline`）到正则表达式列表
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub mappings: BTreeMap<String, Vec<String>>,
+
+    /// 需要忽略的文件名通配符，例如 `*.pos`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>,
+
+    /// 手动映射：文件名通配符到JLC层名称、`InnerLayer<N>` 或 `ignore`，按顺序匹配
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    pub map: IndexMap<String, String>,
+}
+
+/// 配置文件中的输出设置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputConfig {
+    /// 输出路径，相对路径相对于配置文件所在的文件夹
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zip: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zip_name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zip_compression: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zip_timestamp: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zip_folder: Option<String>,
+
+    /// 输出目录中已有文件的处理策略：`safe`、`no-overwrite`、`force` 或 `clean`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overwrite: Option<String>,
+
+    /// 是否在输出中附带审阅报告 report.html
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub report: Option<bool>,
+
+    /// 是否把板子尺寸和铜层数写到下单必读文件中
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes_summary: Option<bool>,
+}
+
+/// 配置文件中的内电层设置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PlanesConfig {
+    /// 是否将负片内电层转换为正片，与 `--positive-planes` 相同
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub positive: Option<bool>,
+}
+
+/// 配置文件中的板框设置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutlineConfig {
+    /// 端点相连的容差（毫米），与 `--outline-tolerance` 相同
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tolerance: Option<f64>,
+
+    /// 需要合并到板框层的文件名通配符，与 `--merge-outline` 相同
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub merge: Vec<String>,
+
+    /// 只对某个EDA风格生效的设置，风格名称不区分大小写，例如 `[outline.styles.protel]`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub styles: BTreeMap<String, OutlineStyleConfig>,
+
+    /// 导入的 DXF 或 SVG 板框文件，相对路径相对于配置文件所在的文件夹，与 `--import-outline` 相同
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub import: Option<String>,
+
+    /// `replace` 或 `add`，与 `--import-outline-mode` 相同
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub import_mode: Option<String>,
+
+    /// 没有板框文件时生成板框，`copper` 或 `<宽>x<高>`，与 `--generate-outline` 相同
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generate: Option<String>,
+
+    /// 生成板框时铜层范围之外的边距（毫米），与 `--outline-margin` 相同
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub margin: Option<f64>,
+
+    /// 生成板框的圆角半径（毫米），与 `--outline-radius` 相同
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub radius: Option<f64>,
+}
+
+/// 配置文件中某个EDA风格的板框设置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutlineStyleConfig {
+    /// 需要合并到板框层的文件名通配符
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub merge: Vec<String>,
+}
+
+/// 配置文件中的工艺能力检查设置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PreflightConfig {
+    /// 检查使用的工艺能力，内置的或 `[preflight.profiles]` 中的名称，与 `--preflight` 相同
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+
+    /// 自定义的工艺能力，例如 `[preflight.profiles.my-fab]`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+}
+
+/// 配置文件中自定义的工艺能力，未设置的值取自 `base`，长度单位为毫米
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfileConfig {
+    /// 作为基础的内置工艺能力，默认为 `jlc-standard`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_trace: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_space: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_inner_trace: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_inner_space: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_drill: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_annular_ring: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_silkscreen_width: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_width: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_height: Option<f64>,
+}
+
+/// 配置文件中的渲染设置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RenderConfig {
+    /// 颜色名称（例如 `mask`）到SVG颜色值，与 `--color` 相同
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub colors: BTreeMap<String, String>,
+}
+
+/// 配置文件中的文件指纹设置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FingerprintConfig {
+    /// 是否向Gerber文件添加哈希孔径
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash_aperture: Option<bool>,
+}
+
+impl Config {
+    /// 读取并校验一个配置文件
+    pub fn load(path: &Path) -> Result<Config, std::io::Error> {
+        let content = std::fs::read_to_string(path)?;
+        let mut config: Config = toml::from_str(&content).map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Invalid config file {}: {}", path.display(), e),
+            )
+        })?;
+        config.validate().map_err(|e| {
+            std::io::Error::new(e.kind(), format!("Invalid config file {}: {}", path.display(), e))
+        })?;
+
+        // 输出路径相对于配置文件所在的文件夹，而不是当前工作目录
+        if let (Some(output), Some(dir)) = (&config.output.path, path.parent()) {
+            if Path::new(output).is_relative() && output != crate::STDIO_PATH {
+                config.output.path = Some(dir.join(output).to_string_lossy().to_string());
+            }
+        }
+        if let (Some(import), Some(dir)) = (&config.outline.import, path.parent()) {
+            if Path::new(import).is_relative() {
+                config.outline.import = Some(dir.join(import).to_string_lossy().to_string());
+            }
+        }
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), std::io::Error> {
+        if let Some(eda) = &self.eda {
+            let names = crate::style_names();
+            if !eda.eq_ignore_ascii_case("auto") && !names.iter().any(|name| name.eq_ignore_ascii_case(eda)) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("unknown EDA '{}' in key 'eda', expected auto or one of: {}", eda, names.join(", ")),
+                ));
+            }
+        }
+        for (key, patterns) in &self.mappings {
+            if JLC_STYLE.get(key).is_none() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("unknown layer '{}' in [mappings]", key),
+                ));
+            }
+            for pattern in patterns {
+                Regex::new(pattern).map_err(|e| {
+                    std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string())
+                })?;
+            }
+        }
+        for pattern in &self.ignore {
+            glob_regex(pattern)?;
+        }
+        for pattern in self.outline.merge.iter().chain(self.outline.styles.values().flat_map(|style| &style.merge)) {
+            glob_regex(pattern)?;
+        }
+        self.file_mappings()?;
+        self.render_colors()?;
+        if let Some(tolerance) = self.outline.tolerance {
+            if tolerance <= 0.0 || !tolerance.is_finite() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("outline tolerance must be positive, got {}", tolerance),
+                ));
+            }
+        }
+        if let Some(mode) = &self.outline.import_mode {
+            if !["replace", "add"].contains(&mode.as_str()) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("unknown outline import mode '{}'", mode),
+                ));
+            }
+        }
+        if let Some(shape) = &self.outline.generate {
+            shape.parse::<OutlineShape>()?;
+        }
+        for (name, value) in [("margin", self.outline.margin), ("radius", self.outline.radius)] {
+            if let Some(value) = value.filter(|value| *value < 0.0 || !value.is_finite()) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("outline {} must not be negative, got {}", name, value),
+                ));
+            }
+        }
+        for name in self.preflight.profiles.keys() {
+            self.preflight_profile(name)?;
+        }
+        if let Some(profile) = &self.preflight.profile {
+            self.preflight_profile(profile)?;
+        }
+        if let Some(policy) = &self.output.overwrite {
+            if !["safe", "no-overwrite", "force", "clean"].contains(&policy.as_str()) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("unknown overwrite policy '{}'", policy),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// 用 `other` 中设置了的值覆盖当前配置；规则和忽略列表会合并
+    pub fn merge(&mut self, other: Config) {
+        fn take<T>(value: &mut Option<T>, other: Option<T>) {
+            if other.is_some() {
+                *value = other;
+            }
+        }
+
+        take(&mut self.eda, other.eda);
+        take(&mut self.output.path, other.output.path);
+        take(&mut self.output.zip, other.output.zip);
+        take(&mut self.output.zip_name, other.output.zip_name);
+        take(&mut self.output.zip_compression, other.output.zip_compression);
+        take(&mut self.output.zip_timestamp, other.output.zip_timestamp);
+        take(&mut self.output.zip_folder, other.output.zip_folder);
+        take(&mut self.output.report, other.output.report);
+        take(&mut self.output.notes_summary, other.output.notes_summary);
+        take(&mut self.output.overwrite, other.output.overwrite);
+        take(&mut self.fingerprint.hash_aperture, other.fingerprint.hash_aperture);
+        take(&mut self.planes.positive, other.planes.positive);
+        take(&mut self.outline.tolerance, other.outline.tolerance);
+        self.outline.merge.extend(other.outline.merge);
+        take(&mut self.outline.import, other.outline.import);
+        take(&mut self.outline.import_mode, other.outline.import_mode);
+        take(&mut self.outline.generate, other.outline.generate);
+        take(&mut self.outline.margin, other.outline.margin);
+        take(&mut self.outline.radius, other.outline.radius);
+        take(&mut self.preflight.profile, other.preflight.profile);
+        self.preflight.profiles.extend(other.preflight.profiles);
+        for (style, settings) in other.outline.styles {
+            self.outline.styles.entry(style.to_lowercase()).or_default().merge.extend(settings.merge);
+        }
+        self.render.colors.extend(other.render.colors);
+        for (key, patterns) in other.mappings {
+            self.mappings.entry(key).or_default().extend(patterns);
+        }
+        self.ignore.extend(other.ignore);
+
+        // 优先级更高的映射排在前面，先被匹配
+        let mut map = other.map;
+        for (pattern, target) in std::mem::take(&mut self.map) {
+            map.entry(pattern).or_insert(target);
+        }
+        self.map = map;
+    }
+
+    /// 解析 `[map]` 表中的手动映射
+    pub fn file_mappings(&self) -> Result<Vec<FileMapping>, std::io::Error> {
+        self.map
+            .iter()
+            .map(|(pattern, target)| FileMapping::new(pattern, target.parse()?))
+            .collect()
+    }
+
+    /// 查找工艺能力，`[preflight.profiles]` 中的同名设置优先于内置的工艺能力
+    pub fn preflight_profile(&self, name: &str) -> Result<Profile, std::io::Error> {
+        let Some(settings) = self.preflight.profiles.get(name) else {
+            return Profile::builtin(name).ok_or_else(|| {
+                let mut names: Vec<&str> = BUILTIN_PROFILES.to_vec();
+                names.extend(self.preflight.profiles.keys().map(String::as_str));
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("unknown pre-flight profile '{}', expected one of: {}", name, names.join(", ")),
+                )
+            });
+        };
+
+        let base = settings.base.as_deref().unwrap_or(BUILTIN_PROFILES[0]);
+        let mut profile = Profile::builtin(base).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("unknown base profile '{}' for pre-flight profile '{}'", base, name),
+            )
+        })?;
+        profile.name = name.to_string();
+        for (value, setting) in [
+            (&mut profile.min_trace, settings.min_trace),
+            (&mut profile.min_space, settings.min_space),
+            (&mut profile.min_inner_trace, settings.min_inner_trace),
+            (&mut profile.min_inner_space, settings.min_inner_space),
+            (&mut profile.min_drill, settings.min_drill),
+            (&mut profile.min_annular_ring, settings.min_annular_ring),
+            (&mut profile.min_silkscreen_width, settings.min_silkscreen_width),
+            (&mut profile.max_width, settings.max_width),
+            (&mut profile.max_height, settings.max_height),
+        ] {
+            if let Some(setting) = setting {
+                if setting < 0.0 || !setting.is_finite() {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        format!("pre-flight profile '{}' has an invalid value {}", name, setting),
+                    ));
+                }
+                *value = setting;
+            }
+        }
+        Ok(profile)
+    }
+
+    /// 应用 `[render.colors]` 之后的渲染颜色
+    pub fn render_colors(&self) -> Result<Colors, std::io::Error> {
+        let mut colors = Colors::default();
+        for (name, value) in &self.render.colors {
+            colors.set(&format!("{}={}", name, value))?;
+        }
+        Ok(colors)
+    }
+
+    /// 以TOML格式输出配置
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// 用户级配置文件的路径
+///
+/// Windows 下为 `%APPDATA%\transjlc\config.toml`，其他系统为
+/// `$XDG_CONFIG_HOME/transjlc/config.toml`（默认 `~/.config/transjlc/config.toml`）。
+pub fn user_config_path() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+    base.map(|dir| dir.join("transjlc").join("config.toml"))
+}
+
+/// 查找输入对应的工程配置文件
+///
+/// 从输入文件夹（输入为压缩包时从压缩包所在的文件夹）开始向上查找 `.transjlc.toml`，
+/// 使用找到的第一个。从标准输入读取时从当前工作目录开始查找。
+pub fn find_project_config(input: &str) -> Option<PathBuf> {
+    let input = if input == crate::STDIO_PATH {
+        std::env::current_dir().ok()?
+    } else {
+        std::fs::canonicalize(input).ok()?
+    };
+    let start = if input.is_dir() {
+        input.as_path()
+    } else {
+        input.parent()?
+    };
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG_NAME))
+        .find(|path| path.is_file())
+}
+
+/// 加载用户级配置和工程配置，工程配置优先；`explicit` 指定时只使用该文件作为工程配置
+///
+/// 返回合并后的配置和实际读取的配置文件列表。
+pub fn load_config(
+    input: &str,
+    explicit: Option<&Path>,
+) -> Result<(Config, Vec<PathBuf>), std::io::Error> {
+    let mut config = Config::default();
+    let mut sources = vec![];
+
+    if let Some(path) = user_config_path().filter(|path| path.is_file()) {
+        config.merge(Config::load(&path)?);
+        sources.push(path);
+    }
+
+    let project = match explicit {
+        Some(path) => Some(path.to_path_buf()),
+        None => find_project_config(input),
+    };
+    if let Some(path) = project {
+        config.merge(Config::load(&path)?);
+        sources.push(path);
+    }
+
+    Ok((config, sources))
+}
+
+/// 修改配置文件，保留文件中已有的内容和注释；文件不存在时新建
+fn edit_config(
+    path: &Path,
+    edit: impl FnOnce(&mut toml_edit::DocumentMut),
+) -> Result<(), std::io::Error> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    let mut document: toml_edit::DocumentMut = content.parse().map_err(|e| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Invalid config file {}: {}", path.display(), e),
+        )
+    })?;
+    edit(&mut document);
+
+    if let Some(parent) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, document.to_string())
+}
+
+/// 取得文档中的一个表，不存在时创建
+fn table<'a>(document: &'a mut toml_edit::DocumentMut, name: &str) -> &'a mut toml_edit::Table {
+    if !document.contains_table(name) {
+        document.insert(name, toml_edit::Item::Table(toml_edit::Table::new()));
+    }
+    document[name].as_table_mut().expect("checked above")
+}
+
+/// 将手动映射写入配置文件的 `[map]` 表，已有的同名条目会被替换
+pub fn save_file_mappings(path: &Path, mappings: &[(String, MapTarget)]) -> Result<(), std::io::Error> {
+    edit_config(path, |document| {
+        let map = table(document, "map");
+        for (pattern, target) in mappings {
+            map.insert(pattern, toml_edit::value(target.to_string()));
+        }
+    })
+}
+
+/// 将规则追加到配置文件的 `[mappings]` 表，已存在的规则不会重复添加
+pub fn save_rules(path: &Path, rules: &[(&str, String)]) -> Result<(), std::io::Error> {
+    edit_config(path, |document| {
+        let mappings = table(document, "mappings");
+        for (key, rule) in rules {
+            if mappings.get(key).and_then(|item| item.as_array()).is_none() {
+                mappings.insert(key, toml_edit::value(toml_edit::Array::new()));
+            }
+            let array = mappings[*key].as_array_mut().expect("checked above");
+            if !array.iter().any(|value| value.as_str() == Some(rule.as_str())) {
+                array.push(rule.as_str());
+            }
+        }
+    })
+}
+
+/// 将文件名通配符转换为正则表达式，支持 `*` 和 `?`，不区分大小写
+pub(crate) fn glob_regex(pattern: &str) -> Result<Regex, std::io::Error> {
+    let mut re = String::from("(?i)^");
+    for c in pattern.chars() {
+        match c {
+            '*' => re.push_str(".*"),
+            '?' => re.push('.'),
+            c => re.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    re.push('$');
+    Regex::new(&re).map_err(|e| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Invalid file pattern '{}': {}", pattern, e),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn project_config_overrides_user_config() {
+        let mut config: Config = toml::from_str(
+            r#"
+            eda = "protel"
+            ignore = ["*.pos"]
+
+            [output]
+            zip = true
+            zip_name = "user"
+            "#,
+        )
+        .unwrap();
+        let project: Config = toml::from_str(
+            r#"
+            eda = "kicad"
+            ignore = ["*-job.gbrjob"]
+
+            [output]
+            zip_name = "board_v3"
+
+            [mappings]
+            Board_Outline = ['(?i)^outline\.gbr$']
+
+            [map]
+            "outline.gbr" = "Board_Outline"
+            "*.gm1" = "InnerLayer1"
+            "#,
+        )
+        .unwrap();
+        project.validate().unwrap();
+        config.map.insert("*.gm1".to_string(), "ignore".to_string());
+        config.merge(project);
+
+        assert_eq!(config.eda.as_deref(), Some("kicad"));
+        assert_eq!(config.output.zip, Some(true));
+        assert_eq!(config.output.zip_name.as_deref(), Some("board_v3"));
+        assert_eq!(config.ignore, ["*.pos", "*-job.gbrjob"]);
+        assert_eq!(config.mappings["Board_Outline"].len(), 1);
+        let targets: Vec<String> = config
+            .file_mappings()
+            .unwrap()
+            .iter()
+            .map(|mapping| mapping.target.to_string())
+            .collect();
+        assert_eq!(targets, ["Board_Outline", "InnerLayer1"]);
+    }
+
+    #[test]
+    fn rejects_unknown_layers_and_keys() {
+        let config: Config = toml::from_str("[mappings]\nTop = ['top']").unwrap();
+        assert!(config.validate().is_err());
+        assert!(toml::from_str::<Config>("zip = true").is_err());
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(PROJECT_CONFIG_NAME);
+        std::fs::write(&path, "eda = \"altium\"\n").unwrap();
+        let err = Config::load(&path).unwrap_err();
+        assert!(err.to_string().contains(PROJECT_CONFIG_NAME) && err.to_string().contains("'eda'"));
+        std::fs::write(&path, "eda = \"KiCad\"\n").unwrap();
+        assert!(Config::load(&path).is_ok());
+    }
+
+    #[test]
+    fn saves_mappings_and_keeps_comments() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(PROJECT_CONFIG_NAME);
+        std::fs::write(&path, "# board defaults\neda = \"kicad\"\n").unwrap();
+
+        save_file_mappings(&path, &[("outline.gbr".to_string(), MapTarget::Layer("Board_Outline"))]).unwrap();
+        save_rules(&path, &[("Top_Layer", "(?i)-top\\.gbr$".to_string())]).unwrap();
+        save_rules(&path, &[("Top_Layer", "(?i)-top\\.gbr$".to_string())]).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("# board defaults"));
+        let config = Config::load(&path).unwrap();
+        assert_eq!(config.map["outline.gbr"], "Board_Outline");
+        assert_eq!(config.mappings["Top_Layer"], ["(?i)-top\\.gbr$"]);
+    }
+
+    #[test]
+    fn resolves_preflight_profiles() {
+        let config: Config = toml::from_str(
+            r#"
+            [preflight]
+            profile = "my-fab"
+
+            [preflight.profiles.my-fab]
+            base = "jlc-advanced"
+            min_drill = 0.25
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+        let profile = config.preflight_profile("my-fab").unwrap();
+        assert_eq!(profile.name, "my-fab");
+        assert_eq!(profile.min_drill, 0.25);
+        assert_eq!(profile.min_trace, 0.09);
+        assert_eq!(config.preflight_profile("jlc-standard").unwrap().min_drill, 0.3);
+        assert!(config.preflight_profile("other").is_err());
+
+        let config: Config = toml::from_str("[preflight.profiles.bad]
+base = \"my-fab\"").unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn glob_matches_file_names() {
+        let re = glob_regex("*.POS").unwrap();
+        assert!(re.is_match("board-top.pos"));
+        assert!(!re.is_match("board.pos.bak"));
+        assert!(glob_regex("board?.gbr").unwrap().is_match("board1.gbr"));
+    }
+}
@@ -0,0 +1,194 @@
+// SPDX-FileCopyrightText: 2025 HalfSweet
+// SPDX-License-Identifier: Apache-2.0
+
+use std::f64::consts::{FRAC_PI_2, TAU};
+use std::fmt::Write;
+
+use crate::excellon::Drill;
+use crate::gerber::{arc_sweep, Layer, Point, Segment, Shape};
+
+/// 导出的 DXF 的默认文件名
+pub const DXF_NAME: &str = "board.dxf";
+
+/// 板框、金属化孔和非金属化孔所在的图层
+pub const OUTLINE_LAYER: &str = "OUTLINE";
+pub const PLATED_LAYER: &str = "PLATED";
+pub const NON_PLATED_LAYER: &str = "NON_PLATED";
+
+/// 导出板框和钻孔位置的 DXF，单位为毫米
+///
+/// 使用 AutoCAD R12 格式，板框的线段和圆弧在 `OUTLINE` 图层，孔为 `PLATED` 或
+/// `NON_PLATED` 图层上的圆，槽孔画成长圆形的轮廓。R12 没有 `$INSUNITS`，
+/// 坐标按毫米写出，不声明单位。
+pub fn board_dxf(outline: Option<&Layer>, plated: &[&Drill], non_plated: &[&Drill]) -> String {
+    let mut dxf = String::new();
+    pair(&mut dxf, 0, "SECTION");
+    pair(&mut dxf, 2, "HEADER");
+    pair(&mut dxf, 9, "$ACADVER");
+    pair(&mut dxf, 1, "AC1009");
+    pair(&mut dxf, 0, "ENDSEC");
+
+    // 图层的颜色：黄色、红色、青色
+    let layers = [(OUTLINE_LAYER, 2), (PLATED_LAYER, 1), (NON_PLATED_LAYER, 4)];
+    pair(&mut dxf, 0, "SECTION");
+    pair(&mut dxf, 2, "TABLES");
+    pair(&mut dxf, 0, "TABLE");
+    pair(&mut dxf, 2, "LAYER");
+    pair(&mut dxf, 70, layers.len());
+    for (name, color) in layers {
+        pair(&mut dxf, 0, "LAYER");
+        pair(&mut dxf, 2, name);
+        pair(&mut dxf, 70, 0);
+        pair(&mut dxf, 62, color);
+        pair(&mut dxf, 6, "CONTINUOUS");
+    }
+    pair(&mut dxf, 0, "ENDTAB");
+    pair(&mut dxf, 0, "ENDSEC");
+
+    pair(&mut dxf, 0, "SECTION");
+    pair(&mut dxf, 2, "ENTITIES");
+    if let Some(outline) = outline {
+        for object in &outline.objects {
+            match &object.shape {
+                Shape::Draw { from, segment, .. } => segment_entity(&mut dxf, OUTLINE_LAYER, *from, *segment),
+                Shape::Region(contours) => {
+                    for contour in contours {
+                        let mut from = contour.start;
+                        for segment in &contour.segments {
+                            segment_entity(&mut dxf, OUTLINE_LAYER, from, *segment);
+                            from = segment.end();
+                        }
+                    }
+                }
+                Shape::Flash { .. } => {}
+            }
+        }
+    }
+    for (layer, drills) in [(PLATED_LAYER, plated), (NON_PLATED_LAYER, non_plated)] {
+        for drill in drills {
+            let radius = |tool: u32| drill.tools.get(&tool).copied().unwrap_or(0.0) / 2.0;
+            for hole in &drill.holes {
+                circle(&mut dxf, layer, hole.at, radius(hole.tool));
+            }
+            for slot in &drill.slots {
+                slot_entities(&mut dxf, layer, slot.from, slot.to, radius(slot.tool));
+            }
+        }
+    }
+    pair(&mut dxf, 0, "ENDSEC");
+    pair(&mut dxf, 0, "EOF");
+    dxf
+}
+
+fn pair(dxf: &mut String, code: i32, value: impl std::fmt::Display) {
+    let _ = write!(dxf, "{}\n{}\n", code, value);
+}
+
+fn number(value: f64) -> String {
+    let value = format!("{:.6}", value);
+    let value = value.trim_end_matches('0').trim_end_matches('.');
+    if value == "-0" {
+        "0".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn entity(dxf: &mut String, kind: &str, layer: &str) {
+    pair(dxf, 0, kind);
+    pair(dxf, 8, layer);
+}
+
+fn line(dxf: &mut String, layer: &str, from: Point, to: Point) {
+    entity(dxf, "LINE", layer);
+    pair(dxf, 10, number(from.x));
+    pair(dxf, 20, number(from.y));
+    pair(dxf, 11, number(to.x));
+    pair(dxf, 21, number(to.y));
+}
+
+fn circle(dxf: &mut String, layer: &str, center: Point, radius: f64) {
+    entity(dxf, "CIRCLE", layer);
+    pair(dxf, 10, number(center.x));
+    pair(dxf, 20, number(center.y));
+    pair(dxf, 40, number(radius));
+}
+
+/// 逆时针从 `start` 到 `end` 的圆弧（弧度）
+fn arc(dxf: &mut String, layer: &str, center: Point, radius: f64, start: f64, end: f64) {
+    entity(dxf, "ARC", layer);
+    pair(dxf, 10, number(center.x));
+    pair(dxf, 20, number(center.y));
+    pair(dxf, 40, number(radius));
+    pair(dxf, 50, number(start.to_degrees().rem_euclid(360.0)));
+    pair(dxf, 51, number(end.to_degrees().rem_euclid(360.0)));
+}
+
+fn segment_entity(dxf: &mut String, layer: &str, from: Point, segment: Segment) {
+    match segment {
+        Segment::Line(to) => line(dxf, layer, from, to),
+        Segment::Arc { to, center, clockwise } => {
+            let radius = center.distance(from);
+            let (start, sweep) = arc_sweep(from, to, center, clockwise);
+            if (sweep.abs() - TAU).abs() < 1e-9 {
+                circle(dxf, layer, center, radius);
+            } else if sweep > 0.0 {
+                arc(dxf, layer, center, radius, start, start + sweep);
+            } else {
+                // DXF 的圆弧总是逆时针，顺时针的圆弧交换起点和终点
+                arc(dxf, layer, center, radius, start + sweep, start);
+            }
+        }
+    }
+}
+
+/// 槽孔的长圆形轮廓
+fn slot_entities(dxf: &mut String, layer: &str, from: Point, to: Point, radius: f64) {
+    let length = from.distance(to);
+    if length < 1e-9 {
+        circle(dxf, layer, from, radius);
+        return;
+    }
+    let angle = (to.y - from.y).atan2(to.x - from.x);
+    let (nx, ny) = (-(to.y - from.y) / length * radius, (to.x - from.x) / length * radius);
+    line(dxf, layer, Point::new(from.x + nx, from.y + ny), Point::new(to.x + nx, to.y + ny));
+    line(dxf, layer, Point::new(to.x - nx, to.y - ny), Point::new(from.x - nx, from.y - ny));
+    arc(dxf, layer, to, radius, angle - FRAC_PI_2, angle + FRAC_PI_2);
+    arc(dxf, layer, from, radius, angle + FRAC_PI_2, angle + 3.0 * FRAC_PI_2);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{excellon, gerber, import};
+
+    #[test]
+    fn exports_outline_and_holes() {
+        let outline = gerber::parse(
+            "%FSLAX46Y46*%\n%MOMM*%\n%ADD10C,0.1*%\nD10*\nG75*\nG01*\nX0Y0D02*\nX0Y10000000D01*\n\
+             G02*\nX20000000Y10000000I10000000J0D01*\nG01*\nX20000000Y0D01*\nX0Y0D01*\nM02*\n",
+        )
+        .unwrap();
+        let plated = excellon::parse("M48\nMETRIC\nT1C0.8\n%\nT1\nX5.0Y5.0\nX15.0Y5.0\nM30\n").unwrap();
+        let non_plated = excellon::parse("M48\nMETRIC\nT1C3.0\n%\nT1\nX10.0Y2.0G85X12.0Y2.0\nM30\n").unwrap();
+        let dxf = board_dxf(Some(&outline), &[&plated], &[&non_plated]);
+
+        assert!(dxf.starts_with("0\nSECTION\n2\nHEADER\n9\n$ACADVER\n1\nAC1009\n0\nENDSEC\n"));
+        assert!(!dxf.contains("$INSUNITS"));
+        assert!(dxf.ends_with("0\nENDSEC\n0\nEOF\n"));
+        assert!(dxf.contains("0\nCIRCLE\n8\nPLATED\n10\n15\n20\n5\n40\n0.4\n"));
+        // 顺时针的圆弧（上边的半圆）写为从 0° 到 180° 的逆时针圆弧
+        assert!(dxf.contains("0\nARC\n8\nOUTLINE\n10\n10\n20\n10\n40\n10\n50\n0\n51\n180\n"));
+
+        // 用导入功能读回来检查图元
+        let drawing = import::parse_dxf(&dxf).unwrap();
+        assert_eq!(drawing.warnings, Vec::<String>::new());
+        // 板框 4 条边，2 个金属化孔，非金属化的槽孔是 2 条直线和 2 段圆弧
+        assert_eq!(drawing.edges.len(), 4 + 2 + 4);
+        let slot = import::Drawing { edges: drawing.edges[6..].to_vec(), warnings: vec![] };
+        let layer = gerber::parse(&slot.to_gerber()).unwrap();
+        let bounds = layer.bounds().unwrap().expand(-0.05);
+        assert!((bounds.min_x - 8.5).abs() < 1e-6 && (bounds.max_x - 13.5).abs() < 1e-6);
+        assert!((bounds.min_y - 0.5).abs() < 1e-6 && (bounds.max_y - 3.5).abs() < 1e-6);
+    }
+}
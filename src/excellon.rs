@@ -0,0 +1,289 @@
+// SPDX-FileCopyrightText: 2025 HalfSweet
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+
+use regex::Regex;
+
+use crate::gerber::{self, Aperture, Object, Point, Primitive, Segment, Shape};
+
+/// 钻孔
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hole {
+    pub tool: u32,
+    pub at: Point,
+}
+
+/// 槽孔（G85 或铣刀路径）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Slot {
+    pub tool: u32,
+    pub from: Point,
+    pub to: Point,
+}
+
+/// 解析之后的Excellon钻孔文件，所有坐标单位为毫米
+#[derive(Debug, Clone, Default)]
+pub struct Drill {
+    /// 刀具编号到直径
+    pub tools: BTreeMap<u32, f64>,
+    pub holes: Vec<Hole>,
+    pub slots: Vec<Slot>,
+
+    /// 解析时忽略的有问题的指令
+    pub warnings: Vec<String>,
+}
+
+impl Drill {
+    /// 转换为Gerber层，每把刀具对应一个圆形孔径，便于与其他层一起渲染
+    pub fn to_layer(&self) -> gerber::Layer {
+        let mut layer = gerber::Layer::default();
+        for (tool, diameter) in &self.tools {
+            layer.apertures.insert(
+                *tool,
+                Aperture {
+                    shapes: vec![(true, Primitive::Circle { center: Point::default(), diameter: *diameter })],
+                },
+            );
+        }
+        for hole in &self.holes {
+            layer.objects.push(Object {
+                shape: Shape::Flash { at: hole.at, aperture: hole.tool },
+                dark: true,
+            });
+        }
+        for slot in &self.slots {
+            layer.objects.push(Object {
+                shape: Shape::Draw {
+                    from: slot.from,
+                    segment: Segment::Line(slot.to),
+                    aperture: slot.tool,
+                },
+                dark: true,
+            });
+        }
+        layer.warnings = self.warnings.clone();
+        layer
+    }
+
+    /// 每把刀具的直径和孔数（槽孔计入孔数），按直径排序
+    pub fn tool_counts(&self) -> Vec<(f64, usize)> {
+        let mut counts: Vec<(f64, usize)> = self
+            .tools
+            .iter()
+            .map(|(tool, diameter)| {
+                let holes = self.holes.iter().filter(|hole| hole.tool == *tool).count();
+                let slots = self.slots.iter().filter(|slot| slot.tool == *tool).count();
+                (*diameter, holes + slots)
+            })
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        counts.sort_by(|a, b| a.0.total_cmp(&b.0));
+        counts
+    }
+}
+
+/// 坐标的零省略方式和位数
+#[derive(Debug, Clone, Copy)]
+struct Format {
+    inch: bool,
+
+    /// 保留开头的零（`LZ`），即省略末尾的零
+    leading: bool,
+    integers: usize,
+    decimals: usize,
+}
+
+impl Format {
+    fn to_mm(self, value: &str) -> Option<f64> {
+        let scale = if self.inch { 25.4 } else { 1.0 };
+        if value.contains('.') {
+            return value.parse::<f64>().ok().map(|v| v * scale);
+        }
+        let (sign, digits) = match value.strip_prefix('-') {
+            Some(digits) => (-1.0, digits),
+            None => (1.0, value.strip_prefix('+').unwrap_or(value)),
+        };
+        let digits = if self.leading {
+            format!("{:0<width$}", digits, width = self.integers + self.decimals)
+        } else {
+            digits.to_string()
+        };
+        let raw: f64 = digits.parse().ok()?;
+        Some(sign * raw / 10f64.powi(self.decimals as i32) * scale)
+    }
+}
+
+/// 解析Excellon钻孔文件
+///
+/// 支持文件头中的刀具定义、`METRIC`/`INCH` 与零省略方式、KiCad 的 `;FORMAT` 注释，
+/// 以及 G85 槽孔和 M15/M16 铣刀路径。
+pub fn parse(content: &str) -> Result<Drill, std::io::Error> {
+    let tool_re = Regex::new(r"^T0*(\d+)(?:[FS][\d.]+)*C([\d.]+)").unwrap();
+    let coord_re = Regex::new(r"^(?:G0?([01]))?(?:X([+-]?[\d.]+))?(?:Y([+-]?[\d.]+))?(?:G85X([+-]?[\d.]+)?(?:Y([+-]?[\d.]+))?)?$").unwrap();
+    let format_re = Regex::new(r"(\d)[:.](\d)").unwrap();
+    let digits_re = Regex::new(r",0*(\d+)\.(0*)\b").unwrap();
+
+    let mut drill = Drill::default();
+    let mut format = Format { inch: false, leading: true, integers: 3, decimals: 3 };
+    let mut header = false;
+    let mut tool: Option<u32> = None;
+    let mut current = Point::default();
+    let mut routing = false;
+    let mut tool_down = false;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix(';') {
+            // KiCad 在注释中写出格式，例如 ;FORMAT={3:3/ absolute / metric / decimal}
+            if let Some(caps) = comment.contains("FORMAT").then(|| format_re.captures(comment)).flatten() {
+                format.integers = caps[1].parse().unwrap_or(format.integers);
+                format.decimals = caps[2].parse().unwrap_or(format.decimals);
+            }
+            continue;
+        }
+
+        match line {
+            "M48" => {
+                header = true;
+                continue;
+            }
+            "%" | "M95" => {
+                header = false;
+                continue;
+            }
+            "M30" | "M00" => break,
+            "M71" | "METRIC" => {
+                format = metric(format);
+                continue;
+            }
+            "M72" | "INCH" => {
+                format = inch(format);
+                continue;
+            }
+            "M15" => {
+                tool_down = true;
+                continue;
+            }
+            "M16" | "M17" => {
+                tool_down = false;
+                continue;
+            }
+            "G05" | "G5" => {
+                routing = false;
+                continue;
+            }
+            "G90" | "FMAT,2" => continue,
+            _ => {}
+        }
+
+        if line.starts_with("METRIC") || line.starts_with("INCH") {
+            format = if line.starts_with("METRIC") { metric(format) } else { inch(format) };
+            if line.contains("TZ") {
+                format.leading = false;
+            } else if line.contains("LZ") {
+                format.leading = true;
+            }
+            if let Some(caps) = digits_re.captures(line) {
+                format.integers = caps[1].len();
+                format.decimals = caps[2].len();
+            }
+            continue;
+        }
+
+        if let Some(caps) = tool_re.captures(line) {
+            let diameter: f64 = caps[2].parse().unwrap_or(0.0);
+            let scale = if format.inch { 25.4 } else { 1.0 };
+            drill.tools.insert(caps[1].parse().unwrap_or(0), diameter * scale);
+            if !header {
+                tool = caps[1].parse().ok();
+            }
+            continue;
+        }
+        if let Some(number) = line.strip_prefix('T') {
+            if let Ok(number) = number.trim_start_matches('0').parse::<u32>() {
+                tool = Some(number);
+            } else if number.chars().all(|c| c == '0') {
+                tool = None;
+            }
+            continue;
+        }
+        if header {
+            continue;
+        }
+
+        let Some(caps) = coord_re.captures(line) else {
+            if !line.starts_with('M') && !line.starts_with('G') {
+                drill.warnings.push(format!("Unknown drill command '{}' ignored", line));
+            }
+            continue;
+        };
+        if caps.get(2).is_none() && caps.get(3).is_none() && caps.get(1).is_none() {
+            continue;
+        }
+        let to = Point::new(
+            caps.get(2).and_then(|v| format.to_mm(v.as_str())).unwrap_or(current.x),
+            caps.get(3).and_then(|v| format.to_mm(v.as_str())).unwrap_or(current.y),
+        );
+        let Some(tool) = tool else {
+            drill.warnings.push(format!("Drill command '{}' without a selected tool ignored", line));
+            current = to;
+            continue;
+        };
+
+        match caps.get(1).map(|g| g.as_str()) {
+            // G00 开始铣刀路径，G01 在下刀时铣出槽
+            Some("0") => routing = true,
+            Some("1") if routing && tool_down => drill.slots.push(Slot { tool, from: current, to }),
+            _ if caps.get(4).is_some() || caps.get(5).is_some() => {
+                let end = Point::new(
+                    caps.get(4).and_then(|v| format.to_mm(v.as_str())).unwrap_or(to.x),
+                    caps.get(5).and_then(|v| format.to_mm(v.as_str())).unwrap_or(to.y),
+                );
+                drill.slots.push(Slot { tool, from: to, to: end });
+                current = end;
+                continue;
+            }
+            _ if !routing => drill.holes.push(Hole { tool, at: to }),
+            _ => {}
+        }
+        current = to;
+    }
+    Ok(drill)
+}
+
+fn metric(format: Format) -> Format {
+    Format { inch: false, integers: 3, decimals: 3, ..format }
+}
+
+fn inch(format: Format) -> Format {
+    Format { inch: true, integers: 2, decimals: 4, ..format }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_holes_and_slots() {
+        let content = "M48\n;FORMAT={-:-/ absolute / metric / decimal}\nMETRIC\nT1C0.300\nT2C1.000\n%\nG90\nG05\nT1\nX10.0Y5.0\nX12.5Y5.0\nT2\nX1.0Y1.0G85X3.0Y1.0\nM30\n";
+        let drill = parse(content).unwrap();
+        assert_eq!(drill.tools.len(), 2);
+        assert_eq!(drill.holes.len(), 2);
+        assert_eq!(drill.holes[1].at, Point::new(12.5, 5.0));
+        assert_eq!(drill.slots, [Slot { tool: 2, from: Point::new(1.0, 1.0), to: Point::new(3.0, 1.0) }]);
+        assert_eq!(drill.tool_counts(), [(0.3, 2), (1.0, 1)]);
+    }
+
+    #[test]
+    fn parses_implied_decimal_coordinates() {
+        let content = "M48\nINCH,TZ\nT01C0.0350\n%\nT01\nX015Y-0025\nM30\n";
+        let drill = parse(content).unwrap();
+        assert!((drill.tools[&1] - 0.889).abs() < 1e-9);
+        let at = drill.holes[0].at;
+        assert!((at.x - 0.0015 * 25.4).abs() < 1e-9 && (at.y + 0.0025 * 25.4).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,1387 @@
+// SPDX-FileCopyrightText: 2025 HalfSweet
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{BTreeMap, HashMap};
+use std::f64::consts::{FRAC_PI_2, TAU};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    /// 解析器对每条指令都要使用的正则表达式，只编译一次
+    static ref COMMENT_RE: Regex = Regex::new(r"^G0?4(?:\D|$)").unwrap();
+    static ref OPERATION_RE: Regex = Regex::new(
+        r"^(?:G0?([123]))?(?:X([+-]?\d+))?(?:Y([+-]?\d+))?(?:I([+-]?\d+))?(?:J([+-]?\d+))?(?:D0?(\d+))?$",
+    )
+    .unwrap();
+    static ref APERTURE_RE: Regex = Regex::new(r"^ADD(\d+)([^,*]+),?([^*]*)\*?$").unwrap();
+    static ref STEP_REPEAT_RE: Regex = Regex::new(r"^SRX(\d+)Y(\d+)I([\d.+-]+)J([\d.+-]+)").unwrap();
+}
+
+/// Gerber 文件的坐标格式（`%FS` 和 `%MO`）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoordFormat {
+    pub x_integers: u32,
+    pub x_decimals: u32,
+    pub y_integers: u32,
+    pub y_decimals: u32,
+
+    /// 省略末尾的零（`%FST`），默认省略开头的零
+    pub trailing: bool,
+
+    /// 单位是否为英寸
+    pub inch: bool,
+}
+
+impl Default for CoordFormat {
+    fn default() -> Self {
+        CoordFormat {
+            x_integers: 4,
+            x_decimals: 6,
+            y_integers: 4,
+            y_decimals: 6,
+            trailing: false,
+            inch: false,
+        }
+    }
+}
+
+impl CoordFormat {
+    /// 从文件内容中读取坐标格式，没有 `%FS` 时返回 `None`
+    pub fn parse(content: &str) -> Option<CoordFormat> {
+        let inch = Regex::new(r"%MOIN\*%").unwrap().is_match(content)
+            // 旧的 RS-274-X 文件可能使用 G70 表示英寸
+            || (!content.contains("%MOMM*%") && content.contains("G70"));
+        let mut format = CoordFormat::from_fs(content)?;
+        format.inch = inch;
+        Some(format)
+    }
+
+    /// 解析 `%FS` 指令，单位默认为毫米
+    fn from_fs(text: &str) -> Option<CoordFormat> {
+        let fs_re = Regex::new(r"FS([LT]?)([AI]?)X(\d)(\d)Y(\d)(\d)").unwrap();
+        let caps = fs_re.captures(text)?;
+        Some(CoordFormat {
+            x_integers: caps[3].parse().ok()?,
+            x_decimals: caps[4].parse().ok()?,
+            y_integers: caps[5].parse().ok()?,
+            y_decimals: caps[6].parse().ok()?,
+            trailing: &caps[1] == "T",
+            inch: false,
+        })
+    }
+
+    fn scale(&self) -> f64 {
+        if self.inch {
+            25.4
+        } else {
+            1.0
+        }
+    }
+
+    /// 把坐标字符串转换为毫米
+    pub fn to_mm(&self, value: &str, decimals: u32, integers: u32) -> Option<f64> {
+        let (sign, digits) = match value.strip_prefix('-') {
+            Some(digits) => (-1.0, digits),
+            None => (1.0, value.strip_prefix('+').unwrap_or(value)),
+        };
+        let digits = if self.trailing {
+            // 省略末尾零时按整数位补齐
+            format!("{:0<width$}", digits, width = (integers + decimals) as usize)
+        } else {
+            digits.to_string()
+        };
+        let raw: f64 = digits.parse().ok()?;
+        Some(sign * raw / 10f64.powi(decimals as i32) * self.scale())
+    }
+
+    pub fn x_to_mm(&self, value: &str) -> Option<f64> {
+        self.to_mm(value, self.x_decimals, self.x_integers)
+    }
+
+    pub fn y_to_mm(&self, value: &str) -> Option<f64> {
+        self.to_mm(value, self.y_decimals, self.y_integers)
+    }
+
+    /// 把毫米转换为文件格式的坐标字符串（不省略零，所有格式都能正确读取）
+    pub fn format(&self, mm: f64, decimals: u32) -> String {
+        let value = (mm / self.scale() * 10f64.powi(decimals as i32)).round() as i64;
+        value.to_string()
+    }
+}
+
+/// 以毫米为单位的矩形范围
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+impl Bounds {
+    pub fn point(x: f64, y: f64) -> Bounds {
+        Bounds {
+            min_x: x,
+            min_y: y,
+            max_x: x,
+            max_y: y,
+        }
+    }
+
+    pub fn include(&mut self, x: f64, y: f64) {
+        self.min_x = self.min_x.min(x);
+        self.min_y = self.min_y.min(y);
+        self.max_x = self.max_x.max(x);
+        self.max_y = self.max_y.max(y);
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max_x - self.min_x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max_y - self.min_y
+    }
+
+    /// 合并两个范围
+    pub fn union(&self, other: &Bounds) -> Bounds {
+        Bounds {
+            min_x: self.min_x.min(other.min_x),
+            min_y: self.min_y.min(other.min_y),
+            max_x: self.max_x.max(other.max_x),
+            max_y: self.max_y.max(other.max_y),
+        }
+    }
+
+    /// 两个范围是否有重叠（含边界）
+    pub fn overlaps(&self, other: &Bounds) -> bool {
+        self.min_x <= other.max_x && other.min_x <= self.max_x && self.min_y <= other.max_y && other.min_y <= self.max_y
+    }
+
+    /// 向四周扩大 `margin`
+    pub fn expand(&self, margin: f64) -> Bounds {
+        Bounds {
+            min_x: self.min_x - margin,
+            min_y: self.min_y - margin,
+            max_x: self.max_x + margin,
+            max_y: self.max_y + margin,
+        }
+    }
+
+    fn include_bounds(bounds: &mut Option<Bounds>, other: Bounds) {
+        *bounds = Some(match bounds {
+            Some(bounds) => bounds.union(&other),
+            None => other,
+        });
+    }
+}
+
+/// 所有坐标点的范围（毫米），不考虑孔径大小
+pub fn coordinate_bounds(content: &str) -> Option<Bounds> {
+    let format = CoordFormat::parse(content)?;
+    let xy_re = Regex::new(r"^(?:G0?[123])?(?:X([+-]?\d+))?(?:Y([+-]?\d+))?(?:I[+-]?\d+)?(?:J[+-]?\d+)?D0?[123]\*").unwrap();
+
+    let (mut x, mut y) = (0.0, 0.0);
+    let mut bounds: Option<Bounds> = None;
+    for line in content.lines() {
+        for statement in line.split_inclusive('*') {
+            let Some(caps) = xy_re.captures(statement.trim()) else {
+                continue;
+            };
+            if let Some(value) = caps.get(1).and_then(|v| format.x_to_mm(v.as_str())) {
+                x = value;
+            }
+            if let Some(value) = caps.get(2).and_then(|v| format.y_to_mm(v.as_str())) {
+                y = value;
+            }
+            match &mut bounds {
+                Some(bounds) => bounds.include(x, y),
+                None => bounds = Some(Bounds::point(x, y)),
+            }
+        }
+    }
+    bounds
+}
+
+/// 平面上的一个点（毫米）
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    pub fn new(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+
+    pub fn distance(&self, other: Point) -> f64 {
+        (self.x - other.x).hypot(self.y - other.y)
+    }
+
+    fn offset(&self, dx: f64, dy: f64) -> Point {
+        Point::new(self.x + dx, self.y + dy)
+    }
+
+    /// 绕原点逆时针旋转
+    fn rotate(&self, degrees: f64) -> Point {
+        if degrees == 0.0 {
+            return *self;
+        }
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Point::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
+}
+
+/// 孔径中的基本图形，坐标相对于孔径中心（毫米）
+#[derive(Debug, Clone, PartialEq)]
+pub enum Primitive {
+    Circle { center: Point, diameter: f64 },
+
+    /// 与坐标轴对齐的圆角矩形，`radius` 为 0 时是普通矩形，等于短边一半时是长圆形
+    Rect {
+        center: Point,
+        width: f64,
+        height: f64,
+        radius: f64,
+    },
+
+    Polygon(Vec<Point>),
+}
+
+impl Primitive {
+    fn bounds(&self) -> Bounds {
+        match self {
+            Primitive::Circle { center, diameter } => Bounds::point(center.x, center.y).expand(diameter / 2.0),
+            Primitive::Rect {
+                center,
+                width,
+                height,
+                ..
+            } => Bounds {
+                min_x: center.x - width / 2.0,
+                min_y: center.y - height / 2.0,
+                max_x: center.x + width / 2.0,
+                max_y: center.y + height / 2.0,
+            },
+            Primitive::Polygon(points) => {
+                let mut bounds = Bounds::point(points[0].x, points[0].y);
+                for point in points {
+                    bounds.include(point.x, point.y);
+                }
+                bounds
+            }
+        }
+    }
+
+    /// 绕孔径中心旋转，矩形旋转后变为多边形
+    fn rotate(self, degrees: f64) -> Primitive {
+        if degrees == 0.0 {
+            return self;
+        }
+        match self {
+            Primitive::Circle { center, diameter } => Primitive::Circle {
+                center: center.rotate(degrees),
+                diameter,
+            },
+            Primitive::Rect {
+                center,
+                width,
+                height,
+                ..
+            } => Primitive::Polygon(
+                rect_points(center, width, height)
+                    .into_iter()
+                    .map(|point| point.rotate(degrees))
+                    .collect(),
+            ),
+            Primitive::Polygon(points) => {
+                Primitive::Polygon(points.into_iter().map(|point| point.rotate(degrees)).collect())
+            }
+        }
+    }
+}
+
+fn rect_points(center: Point, width: f64, height: f64) -> Vec<Point> {
+    let (w, h) = (width / 2.0, height / 2.0);
+    vec![
+        center.offset(-w, -h),
+        center.offset(w, -h),
+        center.offset(w, h),
+        center.offset(-w, h),
+    ]
+}
+
+/// 孔径（`%AD`），标准孔径和宏孔径都展开为基本图形
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aperture {
+    /// 按顺序绘制的图形，`false` 表示清除（孔径中的孔或宏中曝光关闭的图形）
+    pub shapes: Vec<(bool, Primitive)>,
+}
+
+impl Aperture {
+    /// 圆形孔径的直径，用于描边线段和圆弧
+    pub fn diameter(&self) -> Option<f64> {
+        match self.shapes.first() {
+            Some((true, Primitive::Circle { center, diameter })) if *center == Point::default() => Some(*diameter),
+            _ => None,
+        }
+    }
+
+    pub fn bounds(&self) -> Option<Bounds> {
+        let mut bounds = None;
+        for (_, shape) in self.shapes.iter().filter(|(exposure, _)| *exposure) {
+            Bounds::include_bounds(&mut bounds, shape.bounds());
+        }
+        bounds
+    }
+}
+
+/// 线段或圆弧，起点为上一个点
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Segment {
+    Line(Point),
+    Arc {
+        to: Point,
+        center: Point,
+        clockwise: bool,
+    },
+}
+
+impl Segment {
+    pub fn end(&self) -> Point {
+        match self {
+            Segment::Line(to) | Segment::Arc { to, .. } => *to,
+        }
+    }
+
+    /// 从 `from` 开始的这一段的范围，圆弧包括经过的象限点
+    pub fn bounds(&self, from: Point) -> Bounds {
+        let mut bounds = Bounds::point(from.x, from.y);
+        let end = self.end();
+        bounds.include(end.x, end.y);
+        if let Segment::Arc { center, clockwise, .. } = *self {
+            let radius = center.distance(from);
+            let (start, sweep) = arc_sweep(from, end, center, clockwise);
+            for quadrant in 0..4 {
+                let angle = quadrant as f64 * FRAC_PI_2;
+                let delta = if sweep > 0.0 {
+                    (angle - start).rem_euclid(TAU)
+                } else {
+                    (start - angle).rem_euclid(TAU)
+                };
+                if delta <= sweep.abs() {
+                    bounds.include(center.x + radius * angle.cos(), center.y + radius * angle.sin());
+                }
+            }
+        }
+        bounds
+    }
+
+    fn translate(&self, dx: f64, dy: f64) -> Segment {
+        match *self {
+            Segment::Line(to) => Segment::Line(to.offset(dx, dy)),
+            Segment::Arc { to, center, clockwise } => Segment::Arc {
+                to: to.offset(dx, dy),
+                center: center.offset(dx, dy),
+                clockwise,
+            },
+        }
+    }
+}
+
+/// 圆弧的起始角和扫过的角度（弧度，逆时针为正），起点和终点重合时为整圆
+pub fn arc_sweep(from: Point, to: Point, center: Point, clockwise: bool) -> (f64, f64) {
+    let start = (from.y - center.y).atan2(from.x - center.x);
+    let end = (to.y - center.y).atan2(to.x - center.x);
+    let mut sweep = end - start;
+    if clockwise {
+        while sweep >= -1e-9 {
+            sweep -= TAU;
+        }
+        while sweep < -TAU {
+            sweep += TAU;
+        }
+    } else {
+        while sweep <= 1e-9 {
+            sweep += TAU;
+        }
+        while sweep > TAU {
+            sweep -= TAU;
+        }
+    }
+    (start, sweep)
+}
+
+/// 区域的一个轮廓
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contour {
+    pub start: Point,
+    pub segments: Vec<Segment>,
+}
+
+impl Contour {
+    pub fn bounds(&self) -> Bounds {
+        let mut bounds = Bounds::point(self.start.x, self.start.y);
+        let mut from = self.start;
+        for segment in &self.segments {
+            bounds = bounds.union(&segment.bounds(from));
+            from = segment.end();
+        }
+        bounds
+    }
+}
+
+/// 图形对象
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    /// 用孔径描绘的线段或圆弧（D01）
+    Draw {
+        from: Point,
+        segment: Segment,
+        aperture: u32,
+    },
+
+    /// 闪烁孔径（D03）
+    Flash { at: Point, aperture: u32 },
+
+    /// 区域（G36/G37），可以包含多个轮廓
+    Region(Vec<Contour>),
+}
+
+impl Shape {
+    fn translate(&self, dx: f64, dy: f64) -> Shape {
+        match self {
+            Shape::Draw {
+                from,
+                segment,
+                aperture,
+            } => Shape::Draw {
+                from: from.offset(dx, dy),
+                segment: segment.translate(dx, dy),
+                aperture: *aperture,
+            },
+            Shape::Flash { at, aperture } => Shape::Flash {
+                at: at.offset(dx, dy),
+                aperture: *aperture,
+            },
+            Shape::Region(contours) => Shape::Region(
+                contours
+                    .iter()
+                    .map(|contour| Contour {
+                        start: contour.start.offset(dx, dy),
+                        segments: contour.segments.iter().map(|s| s.translate(dx, dy)).collect(),
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// 带极性的图形对象
+#[derive(Debug, Clone, PartialEq)]
+pub struct Object {
+    pub shape: Shape,
+
+    /// `true` 为添加（`%LPD`），`false` 为清除（`%LPC`）
+    pub dark: bool,
+}
+
+/// 解析之后的Gerber层，所有坐标单位为毫米
+#[derive(Debug, Clone, Default)]
+pub struct Layer {
+    pub apertures: BTreeMap<u32, Aperture>,
+    pub objects: Vec<Object>,
+
+    /// 整个图像为负片（`%IPNEG*%`）
+    pub negative: bool,
+
+    /// 解析时忽略的不支持或有问题的指令
+    pub warnings: Vec<String>,
+}
+
+impl Layer {
+    /// 所有图形的范围，包括孔径的大小
+    pub fn bounds(&self) -> Option<Bounds> {
+        let mut bounds = None;
+        for object in &self.objects {
+            let shape_bounds = match &object.shape {
+                Shape::Draw {
+                    from,
+                    segment,
+                    aperture,
+                } => {
+                    let size = self.apertures.get(aperture).and_then(Aperture::bounds);
+                    let margin = size.map(|b| b.width().max(b.height()) / 2.0).unwrap_or(0.0);
+                    segment.bounds(*from).expand(margin)
+                }
+                Shape::Flash { at, aperture } => match self.apertures.get(aperture).and_then(Aperture::bounds) {
+                    Some(b) => Bounds {
+                        min_x: b.min_x + at.x,
+                        min_y: b.min_y + at.y,
+                        max_x: b.max_x + at.x,
+                        max_y: b.max_y + at.y,
+                    },
+                    None => Bounds::point(at.x, at.y),
+                },
+                Shape::Region(contours) => match contours.iter().map(Contour::bounds).reduce(|a, b| a.union(&b)) {
+                    Some(b) => b,
+                    None => continue,
+                },
+            };
+            Bounds::include_bounds(&mut bounds, shape_bounds);
+        }
+        bounds
+    }
+
+    /// 线段、圆弧和区域的几何范围，不包括孔径大小，用于计算板框尺寸
+    pub fn path_bounds(&self) -> Option<Bounds> {
+        let mut bounds = None;
+        for object in &self.objects {
+            let shape_bounds = match &object.shape {
+                Shape::Draw { from, segment, .. } => segment.bounds(*from),
+                Shape::Flash { at, .. } => Bounds::point(at.x, at.y),
+                Shape::Region(contours) => match contours.iter().map(Contour::bounds).reduce(|a, b| a.union(&b)) {
+                    Some(b) => b,
+                    None => continue,
+                },
+            };
+            Bounds::include_bounds(&mut bounds, shape_bounds);
+        }
+        bounds
+    }
+}
+
+/// 把线段和圆弧首尾相连组成轮廓
+///
+/// 端点距离不超过 `tolerance`（毫米）时视为相连，区域中的轮廓直接作为闭合轮廓。
+/// 返回闭合的轮廓和无法闭合的轮廓。
+pub fn chain_contours(layer: &Layer, tolerance: f64) -> (Vec<Contour>, Vec<Contour>) {
+    let mut closed = vec![];
+    let mut edges: Vec<(Point, Segment)> = vec![];
+    for object in &layer.objects {
+        match &object.shape {
+            Shape::Draw { from, segment, .. } => {
+                if matches!(segment, Segment::Line(to) if to.distance(*from) <= tolerance) {
+                    continue;
+                }
+                edges.push((*from, *segment));
+            }
+            Shape::Region(contours) => closed.extend(contours.iter().cloned()),
+            Shape::Flash { .. } => {}
+        }
+    }
+
+    let mut open = vec![];
+    let mut used = vec![false; edges.len()];
+    for first in 0..edges.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+        let mut contour = Contour {
+            start: edges[first].0,
+            segments: vec![edges[first].1],
+        };
+        let mut reversed = false;
+        loop {
+            let end = contour.segments.last().unwrap().end();
+            if end.distance(contour.start) <= tolerance {
+                closed.push(contour);
+                break;
+            }
+
+            let next = (0..edges.len()).filter(|i| !used[*i]).find_map(|i| {
+                let (from, segment) = edges[i];
+                if from.distance(end) <= tolerance {
+                    Some((i, segment))
+                } else if segment.end().distance(end) <= tolerance {
+                    Some((i, reverse_segment(from, segment).1))
+                } else {
+                    None
+                }
+            });
+            match next {
+                Some((index, segment)) => {
+                    used[index] = true;
+                    contour.segments.push(segment);
+                }
+                // 向后找不到时，从轮廓的起点向另一个方向继续查找
+                None if !reversed => {
+                    reversed = true;
+                    contour = reverse_contour(&contour);
+                }
+                None => {
+                    open.push(contour);
+                    break;
+                }
+            }
+        }
+    }
+    (closed, open)
+}
+
+/// 反向的线段或圆弧，返回新的起点和线段
+fn reverse_segment(from: Point, segment: Segment) -> (Point, Segment) {
+    match segment {
+        Segment::Line(to) => (to, Segment::Line(from)),
+        Segment::Arc { to, center, clockwise } => (
+            to,
+            Segment::Arc {
+                to: from,
+                center,
+                clockwise: !clockwise,
+            },
+        ),
+    }
+}
+
+fn reverse_contour(contour: &Contour) -> Contour {
+    let mut starts = vec![contour.start];
+    for segment in &contour.segments {
+        starts.push(segment.end());
+    }
+    let segments = contour
+        .segments
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(index, segment)| reverse_segment(starts[index], *segment).1)
+        .collect();
+    Contour {
+        start: *starts.last().unwrap(),
+        segments,
+    }
+}
+
+/// 插补模式（G01/G02/G03）
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Interpolation {
+    Linear,
+    Clockwise,
+    CounterClockwise,
+}
+
+/// 阶梯重复最多展开的副本数和展开后最多的图形数，超过时不展开并记录警告
+const MAX_STEP_REPEAT_COPIES: u64 = 10_000;
+const MAX_STEP_REPEAT_OBJECTS: u64 = 5_000_000;
+
+/// Gerber 规范中正多边形的顶点数范围
+const POLYGON_VERTICES: std::ops::RangeInclusive<usize> = 3..=12;
+
+/// 正在记录的阶梯重复块（`%SR`）
+struct StepRepeat {
+    start: usize,
+    x: u32,
+    y: u32,
+    i: f64,
+    j: f64,
+}
+
+struct Parser {
+    format: Option<CoordFormat>,
+    inch: bool,
+    layer: Layer,
+    macros: HashMap<String, Vec<String>>,
+    current: Point,
+    aperture: Option<u32>,
+    interpolation: Interpolation,
+    single_quadrant: bool,
+    dark: bool,
+    last_op: u32,
+    region: Option<Vec<Contour>>,
+    contour: Option<Contour>,
+    step_repeat: Option<StepRepeat>,
+}
+
+/// 解析Gerber（RS-274X）文件
+///
+/// 支持标准孔径、宏孔径、圆弧（单象限和多象限）、区域、极性和阶梯重复；
+/// 不支持的指令（例如块孔径）会被跳过并记录在 [`Layer::warnings`] 中。
+pub fn parse(content: &str) -> Result<Layer, std::io::Error> {
+    let mut parser = Parser {
+        format: None,
+        inch: false,
+        layer: Layer::default(),
+        macros: HashMap::new(),
+        current: Point::default(),
+        aperture: None,
+        interpolation: Interpolation::Linear,
+        single_quadrant: false,
+        dark: true,
+        last_op: 2,
+        region: None,
+        contour: None,
+        step_repeat: None,
+    };
+
+    let mut rest = content;
+    loop {
+        rest = rest.trim_start();
+        if let Some(block) = rest.strip_prefix('%') {
+            let Some(end) = block.find('%') else {
+                break;
+            };
+            parser.extended(&block[..end])?;
+            rest = &block[end + 1..];
+        } else {
+            let Some(end) = rest.find('*') else {
+                break;
+            };
+            if !parser.word(&rest[..end])? {
+                break;
+            }
+            rest = &rest[end + 1..];
+        }
+    }
+    parser.close_step_repeat();
+
+    if parser.format.is_none() && !parser.layer.objects.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "Gerber file has no coordinate format (%FS)",
+        ));
+    }
+    Ok(parser.layer)
+}
+
+impl Parser {
+    fn scale(&self) -> f64 {
+        if self.inch {
+            25.4
+        } else {
+            1.0
+        }
+    }
+
+    fn warn(&mut self, message: String) {
+        if !self.layer.warnings.contains(&message) {
+            self.layer.warnings.push(message);
+        }
+    }
+
+    /// 处理 `%...%` 中的扩展指令
+    fn extended(&mut self, block: &str) -> Result<(), std::io::Error> {
+        let block: String = block.chars().filter(|c| !c.is_whitespace()).collect();
+        let code = block.get(..2).unwrap_or_default();
+        match code {
+            "FS" => {
+                let inch = self.inch;
+                self.format = CoordFormat::from_fs(&block).map(|format| CoordFormat { inch, ..format });
+                if self.format.is_none() {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("Invalid coordinate format '%{}%'", block),
+                    ));
+                }
+            }
+            "MO" => self.set_inch(block.starts_with("MOIN")),
+            "IP" => self.layer.negative = block.starts_with("IPNEG"),
+            "LP" => self.dark = !block.starts_with("LPC"),
+            "AD" => self.aperture_definition(&block)?,
+            "AM" => {
+                let mut statements = block[2..].split('*').map(str::to_string);
+                let name = statements.next().unwrap_or_default();
+                self.macros.insert(name, statements.filter(|s| !s.is_empty()).collect());
+            }
+            "SR" => self.step_repeat(&block),
+            "LM" | "LR" | "LS"
+                if !matches!(block.trim_end_matches('*'), "LMN" | "LR0" | "LR0.0" | "LS1" | "LS1.0") =>
+            {
+                self.warn(format!("Unsupported transformation '%{}%' ignored", block));
+            }
+            "AB" => self.warn("Block apertures (%AB) are not supported".to_string()),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn set_inch(&mut self, inch: bool) {
+        self.inch = inch;
+        if let Some(format) = &mut self.format {
+            format.inch = inch;
+        }
+    }
+
+    fn aperture_definition(&mut self, block: &str) -> Result<(), std::io::Error> {
+        let invalid = || {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Invalid aperture definition '%{}%'", block),
+            )
+        };
+        let caps = APERTURE_RE.captures(block).ok_or_else(invalid)?;
+        let number: u32 = caps[1].parse().map_err(|_| invalid())?;
+        let params: Vec<f64> = if caps[3].is_empty() {
+            vec![]
+        } else {
+            caps[3]
+                .split('X')
+                .map(|value| value.parse::<f64>())
+                .collect::<Result<_, _>>()
+                .map_err(|_| invalid())?
+        };
+        let scale = self.scale();
+        let param = |index: usize| params.get(index).copied().unwrap_or(0.0) * scale;
+        let origin = Point::default();
+
+        let mut shapes = match &caps[2] {
+            "C" => vec![(true, Primitive::Circle { center: origin, diameter: param(0) })],
+            "R" | "O" => {
+                let (width, height) = (param(0), param(1));
+                let radius = if &caps[2] == "O" { width.min(height) / 2.0 } else { 0.0 };
+                vec![(true, Primitive::Rect { center: origin, width, height, radius })]
+            }
+            "P" => {
+                let vertices = self.polygon_vertices(params.get(1).copied().unwrap_or(3.0), &format!("D{}", number));
+                let rotation = params.get(2).copied().unwrap_or(0.0);
+                vec![(true, regular_polygon(origin, param(0), vertices, rotation))]
+            }
+            name => match self.macros.get(name).cloned() {
+                Some(statements) => self.evaluate_macro(name, &statements, &params)?,
+                None => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("Aperture D{} uses undefined macro '{}'", number, name),
+                    ))
+                }
+            },
+        };
+
+        // 标准孔径的孔
+        let hole_index = match &caps[2] {
+            "C" => Some(1),
+            "R" | "O" => Some(2),
+            "P" => Some(3),
+            _ => None,
+        };
+        if let Some(hole) = hole_index.map(param).filter(|hole| *hole > 0.0) {
+            shapes.push((false, Primitive::Circle { center: origin, diameter: hole }));
+        }
+
+        self.layer.apertures.insert(number, Aperture { shapes });
+        Ok(())
+    }
+
+    /// 用参数展开宏孔径
+    fn evaluate_macro(
+        &mut self,
+        name: &str,
+        statements: &[String],
+        params: &[f64],
+    ) -> Result<Vec<(bool, Primitive)>, std::io::Error> {
+        let mut vars: HashMap<u32, f64> = params.iter().enumerate().map(|(i, v)| (i as u32 + 1, *v)).collect();
+        let scale = self.scale();
+        let mut shapes = vec![];
+
+        for statement in statements {
+            // 代码 0 是注释
+            if statement.starts_with('0') {
+                continue;
+            }
+            if let Some(assignment) = statement.strip_prefix('$') {
+                if let Some((var, expr)) = assignment.split_once('=') {
+                    let value = evaluate_expression(expr, &vars).map_err(|e| macro_error(name, &e))?;
+                    vars.insert(var.parse().map_err(|_| macro_error(name, statement))?, value);
+                }
+                continue;
+            }
+
+            let values: Vec<f64> = statement
+                .split(',')
+                .map(|expr| evaluate_expression(expr, &vars))
+                .collect::<Result<_, _>>()
+                .map_err(|e| macro_error(name, &e))?;
+            let value = |index: usize| values.get(index).copied().unwrap_or(0.0);
+            let size = |index: usize| value(index) * scale;
+            let point = |index: usize| Point::new(size(index), size(index + 1));
+            let exposure = value(1) != 0.0;
+
+            match value(0) as u32 {
+                // 圆：曝光、直径、中心、旋转
+                1 => shapes.push((
+                    exposure,
+                    Primitive::Circle { center: point(3), diameter: size(2) }.rotate(value(5)),
+                )),
+                // 矢量线：曝光、宽度、起点、终点、旋转
+                2 | 20 => {
+                    let (start, end) = (point(3), point(5));
+                    let (dx, dy) = (end.x - start.x, end.y - start.y);
+                    let length = dx.hypot(dy);
+                    let (nx, ny) = if length > 0.0 {
+                        (-dy / length * size(2) / 2.0, dx / length * size(2) / 2.0)
+                    } else {
+                        (0.0, size(2) / 2.0)
+                    };
+                    let points = vec![
+                        start.offset(nx, ny),
+                        end.offset(nx, ny),
+                        end.offset(-nx, -ny),
+                        start.offset(-nx, -ny),
+                    ];
+                    shapes.push((exposure, Primitive::Polygon(points).rotate(value(7))));
+                }
+                // 中心线：曝光、宽度、高度、中心、旋转
+                21 => shapes.push((
+                    exposure,
+                    Primitive::Rect { center: point(4), width: size(2), height: size(3), radius: 0.0 }.rotate(value(6)),
+                )),
+                // 旧的左下角线：曝光、宽度、高度、左下角、旋转
+                22 => {
+                    let center = point(4).offset(size(2) / 2.0, size(3) / 2.0);
+                    shapes.push((
+                        exposure,
+                        Primitive::Rect { center, width: size(2), height: size(3), radius: 0.0 }.rotate(value(6)),
+                    ));
+                }
+                // 轮廓：曝光、顶点数、n+1 个点、旋转
+                4 => {
+                    // 顶点数不能超过语句中实际给出的坐标数
+                    let listed = values.len().saturating_sub(3) / 2;
+                    let count = (value(2).max(0.0) as usize).saturating_add(1);
+                    if count > listed {
+                        self.warn(format!(
+                            "Outline primitive in macro '{}' declares {} vertices but lists {}",
+                            name,
+                            count,
+                            listed
+                        ));
+                    }
+                    let count = count.min(listed);
+                    let points: Vec<Point> = (0..count).map(|i| point(3 + i * 2)).collect();
+                    shapes.push((exposure, Primitive::Polygon(points).rotate(value(3 + count * 2))));
+                }
+                // 正多边形：曝光、顶点数、中心、外接圆直径、旋转
+                5 => {
+                    let vertices = self.polygon_vertices(value(2), &format!("macro '{}'", name));
+                    let polygon = regular_polygon(point(3), size(5), vertices, 0.0);
+                    shapes.push((exposure, polygon.rotate(value(6))));
+                }
+                // 散热焊盘：中心、外径、内径、缺口宽度、旋转
+                7 => {
+                    let (center, outer, inner, gap, rotation) = (
+                        Point::new(size(1), size(2)),
+                        size(3),
+                        size(4),
+                        size(5),
+                        value(6),
+                    );
+                    shapes.push((true, Primitive::Circle { center, diameter: outer }.rotate(rotation)));
+                    shapes.push((false, Primitive::Circle { center, diameter: inner }.rotate(rotation)));
+                    for (width, height) in [(outer, gap), (gap, outer)] {
+                        shapes.push((
+                            false,
+                            Primitive::Rect { center, width, height, radius: 0.0 }.rotate(rotation),
+                        ));
+                    }
+                }
+                code => self.warn(format!("Unsupported primitive {} in macro '{}' ignored", code, name)),
+            }
+        }
+        Ok(shapes)
+    }
+
+    /// 正多边形的顶点数，超出规范的范围时限制在范围内并记录警告
+    fn polygon_vertices(&mut self, value: f64, source: &str) -> usize {
+        let vertices = value.max(0.0) as usize;
+        if !POLYGON_VERTICES.contains(&vertices) {
+            self.warn(format!(
+                "Polygon in {} has {} vertices, limited to {}..={}",
+                source,
+                vertices,
+                POLYGON_VERTICES.start(),
+                POLYGON_VERTICES.end()
+            ));
+        }
+        vertices.clamp(*POLYGON_VERTICES.start(), *POLYGON_VERTICES.end())
+    }
+
+    fn step_repeat(&mut self, block: &str) {
+        self.close_step_repeat();
+        if let Some(caps) = STEP_REPEAT_RE.captures(block) {
+            let scale = self.scale();
+            self.step_repeat = Some(StepRepeat {
+                start: self.layer.objects.len(),
+                x: caps[1].parse().unwrap_or(1),
+                y: caps[2].parse().unwrap_or(1),
+                i: caps[3].parse::<f64>().unwrap_or(0.0) * scale,
+                j: caps[4].parse::<f64>().unwrap_or(0.0) * scale,
+            });
+        }
+    }
+
+    fn close_step_repeat(&mut self) {
+        let Some(sr) = self.step_repeat.take() else {
+            return;
+        };
+        let copies = sr.x as u64 * sr.y as u64;
+        let objects = (self.layer.objects.len() - sr.start) as u64 * copies;
+        if copies > MAX_STEP_REPEAT_COPIES || objects > MAX_STEP_REPEAT_OBJECTS {
+            self.warn(format!(
+                "Step and repeat X{}Y{} is too large to expand, only the first copy is kept",
+                sr.x, sr.y
+            ));
+            return;
+        }
+        let block: Vec<Object> = self.layer.objects[sr.start..].to_vec();
+        for iy in 0..sr.y {
+            for ix in 0..sr.x {
+                if ix == 0 && iy == 0 {
+                    continue;
+                }
+                let (dx, dy) = (ix as f64 * sr.i, iy as f64 * sr.j);
+                for object in &block {
+                    self.layer.objects.push(Object {
+                        shape: object.shape.translate(dx, dy),
+                        dark: object.dark,
+                    });
+                }
+            }
+        }
+    }
+
+    /// 处理以 `*` 结尾的普通指令，读到 `M02` 时返回 `false`
+    fn word(&mut self, word: &str) -> Result<bool, std::io::Error> {
+        let word = word.trim();
+        if word.is_empty() || COMMENT_RE.is_match(word) {
+            return Ok(true);
+        }
+        let word: String = word.chars().filter(|c| !c.is_whitespace()).collect();
+        let word = word.trim_start_matches("G54").trim_start_matches("G55");
+
+        match word {
+            "M02" | "M2" | "M00" | "M0" => return Ok(false),
+            "M01" | "M1" | "G90" => return Ok(true),
+            "G36" => {
+                self.region = Some(vec![]);
+                self.contour = None;
+                return Ok(true);
+            }
+            "G37" => {
+                self.close_contour();
+                if let Some(contours) = self.region.take().filter(|c| !c.is_empty()) {
+                    self.push(Shape::Region(contours));
+                }
+                return Ok(true);
+            }
+            "G70" => {
+                self.set_inch(true);
+                return Ok(true);
+            }
+            "G71" => {
+                self.set_inch(false);
+                return Ok(true);
+            }
+            "G74" => {
+                self.single_quadrant = true;
+                return Ok(true);
+            }
+            "G75" => {
+                self.single_quadrant = false;
+                return Ok(true);
+            }
+            "G91" => {
+                self.warn("Incremental coordinates (G91) are not supported".to_string());
+                return Ok(true);
+            }
+            _ => {}
+        }
+
+        let Some(caps) = OPERATION_RE.captures(word) else {
+            self.warn(format!("Unknown command '{}' ignored", word));
+            return Ok(true);
+        };
+
+        if let Some(code) = caps.get(1) {
+            self.interpolation = match code.as_str() {
+                "1" => Interpolation::Linear,
+                "2" => Interpolation::Clockwise,
+                _ => Interpolation::CounterClockwise,
+            };
+        }
+        let has_coords = (2..=5).any(|index| caps.get(index).is_some());
+        let op = match caps.get(6).map(|d| d.as_str().parse::<u32>().unwrap_or(0)) {
+            Some(number) if number >= 10 => {
+                self.aperture = Some(number);
+                return Ok(true);
+            }
+            Some(number) => number,
+            // 省略 D 码的坐标沿用上一次的操作（已弃用的写法）
+            None if has_coords => self.last_op,
+            None => return Ok(true),
+        };
+        self.last_op = op;
+
+        let format = self.format.unwrap_or_default();
+        let coordinate = |index: usize, x: bool| {
+            caps.get(index).and_then(|value| {
+                if x {
+                    format.x_to_mm(value.as_str())
+                } else {
+                    format.y_to_mm(value.as_str())
+                }
+            })
+        };
+        let to = Point::new(
+            coordinate(2, true).unwrap_or(self.current.x),
+            coordinate(3, false).unwrap_or(self.current.y),
+        );
+        let offset = Point::new(coordinate(4, true).unwrap_or(0.0), coordinate(5, false).unwrap_or(0.0));
+
+        match op {
+            1 => {
+                let segment = self.segment(self.current, to, offset);
+                if self.region.is_some() {
+                    let start = self.current;
+                    self.contour
+                        .get_or_insert_with(|| Contour { start, segments: vec![] })
+                        .segments
+                        .push(segment);
+                } else if let Some(aperture) = self.current_aperture() {
+                    self.push(Shape::Draw { from: self.current, segment, aperture });
+                }
+            }
+            2 => {
+                if self.region.is_some() {
+                    self.close_contour();
+                }
+            }
+            3 => {
+                if let Some(aperture) = self.current_aperture() {
+                    self.push(Shape::Flash { at: to, aperture });
+                }
+            }
+            _ => self.warn(format!("Unknown operation D{:02} ignored", op)),
+        }
+        self.current = to;
+        Ok(true)
+    }
+
+    fn current_aperture(&mut self) -> Option<u32> {
+        match self.aperture {
+            Some(aperture) if self.layer.apertures.contains_key(&aperture) => Some(aperture),
+            Some(aperture) => {
+                self.warn(format!("Aperture D{} is used but not defined", aperture));
+                None
+            }
+            None => {
+                self.warn("Drawing without a selected aperture ignored".to_string());
+                None
+            }
+        }
+    }
+
+    /// 根据当前插补模式生成线段或圆弧
+    fn segment(&self, from: Point, to: Point, offset: Point) -> Segment {
+        let clockwise = match self.interpolation {
+            Interpolation::Linear => return Segment::Line(to),
+            Interpolation::Clockwise => true,
+            Interpolation::CounterClockwise => false,
+        };
+        let center = if self.single_quadrant {
+            // 单象限模式中 I、J 没有符号，选择使两端半径最接近且不超过 90° 的圆心
+            let (i, j) = (offset.x.abs(), offset.y.abs());
+            [(i, j), (-i, j), (i, -j), (-i, -j)]
+                .into_iter()
+                .map(|(dx, dy)| from.offset(dx, dy))
+                .filter(|center| arc_sweep(from, to, *center, clockwise).1.abs() <= FRAC_PI_2 + 1e-6)
+                .min_by(|a, b| {
+                    let error = |c: &Point| (c.distance(from) - c.distance(to)).abs();
+                    error(a).total_cmp(&error(b))
+                })
+                .unwrap_or(from.offset(offset.x, offset.y))
+        } else {
+            from.offset(offset.x, offset.y)
+        };
+        Segment::Arc { to, center, clockwise }
+    }
+
+    fn close_contour(&mut self) {
+        if let Some(contour) = self.contour.take() {
+            if !contour.segments.is_empty() {
+                if let Some(region) = &mut self.region {
+                    region.push(contour);
+                }
+            }
+        }
+    }
+
+    fn push(&mut self, shape: Shape) {
+        self.layer.objects.push(Object { shape, dark: self.dark });
+    }
+}
+
+fn macro_error(name: &str, detail: &str) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("Invalid aperture macro '{}': {}", name, detail),
+    )
+}
+
+/// 正多边形，`diameter` 为外接圆直径，第一个顶点位于 `rotation` 度
+fn regular_polygon(center: Point, diameter: f64, vertices: usize, rotation: f64) -> Primitive {
+    Primitive::Polygon(
+        (0..vertices)
+            .map(|i| {
+                let angle = (rotation + 360.0 * i as f64 / vertices as f64).to_radians();
+                center.offset(diameter / 2.0 * angle.cos(), diameter / 2.0 * angle.sin())
+            })
+            .collect(),
+    )
+}
+
+/// 计算宏中的算术表达式，支持 `+ - x /`、括号和 `$n` 变量
+fn evaluate_expression(expr: &str, vars: &HashMap<u32, f64>) -> Result<f64, String> {
+    struct Expr<'a> {
+        chars: std::iter::Peekable<std::str::Chars<'a>>,
+        vars: &'a HashMap<u32, f64>,
+    }
+
+    impl Expr<'_> {
+        fn sum(&mut self) -> Result<f64, String> {
+            let mut value = self.product()?;
+            while let Some(op) = self.chars.next_if(|c| *c == '+' || *c == '-') {
+                let rhs = self.product()?;
+                value = if op == '+' { value + rhs } else { value - rhs };
+            }
+            Ok(value)
+        }
+
+        fn product(&mut self) -> Result<f64, String> {
+            let mut value = self.factor()?;
+            while let Some(op) = self.chars.next_if(|c| matches!(c, 'x' | 'X' | '/')) {
+                let rhs = self.factor()?;
+                value = if op == '/' { value / rhs } else { value * rhs };
+            }
+            Ok(value)
+        }
+
+        fn factor(&mut self) -> Result<f64, String> {
+            match self.chars.next() {
+                Some('-') => Ok(-self.factor()?),
+                Some('+') => self.factor(),
+                Some('(') => {
+                    let value = self.sum()?;
+                    match self.chars.next() {
+                        Some(')') => Ok(value),
+                        _ => Err("missing ')'".to_string()),
+                    }
+                }
+                Some('$') => {
+                    let mut number = String::new();
+                    while let Some(c) = self.chars.next_if(char::is_ascii_digit) {
+                        number.push(c);
+                    }
+                    let index: u32 = number.parse().map_err(|_| "invalid variable".to_string())?;
+                    Ok(self.vars.get(&index).copied().unwrap_or(0.0))
+                }
+                Some(c) if c.is_ascii_digit() || c == '.' => {
+                    let mut number = c.to_string();
+                    while let Some(c) = self.chars.next_if(|c| c.is_ascii_digit() || *c == '.') {
+                        number.push(c);
+                    }
+                    number.parse().map_err(|_| format!("invalid number '{}'", number))
+                }
+                Some(c) => Err(format!("unexpected '{}'", c)),
+                None => Err("unexpected end of expression".to_string()),
+            }
+        }
+    }
+
+    let expr: String = expr.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut parser = Expr { chars: expr.chars().peekable(), vars };
+    let value = parser.sum()?;
+    match parser.chars.next() {
+        Some(c) => Err(format!("unexpected '{}' in '{}'", c, expr)),
+        None => Ok(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_coordinate_format() {
+        let format = CoordFormat::parse("%FSLAX46Y46*%\n%MOIN*%\n").unwrap();
+        assert!(format.inch);
+        assert_eq!(format.x_decimals, 6);
+        assert!((format.x_to_mm("1000000").unwrap() - 25.4).abs() < 1e-9);
+        assert_eq!(format.format(25.4, 6), "1000000");
+    }
+
+    #[test]
+    fn computes_coordinate_bounds() {
+        let content = "%FSLAX46Y46*%\n%MOMM*%\nX0Y0D02*\nX10000000D01*\nY-5000000D01*\nG01X2000000Y3000000D03*\nM02*\n";
+        let bounds = coordinate_bounds(content).unwrap();
+        assert_eq!(bounds, Bounds { min_x: 0.0, min_y: -5.0, max_x: 10.0, max_y: 3.0 });
+    }
+
+    #[test]
+    fn parses_apertures_and_macros() {
+        let content = "%FSLAX36Y36*%\n%MOIN*%\n%AMTHERM*7,0,0,$1,$1x0.6,0.01,45*%\n%AMBOX*$2=$1/2*21,1,$1,$2,0,0,0*1,0,0.01,0,0*%\n%ADD10C,0.01X0.005*%\n%ADD11THERM,0.1*%\n%ADD12BOX,0.2*%\nD11*\nX1000000Y0D03*\nD12*\nX2000000Y0D03*\nM02*\n";
+        let layer = parse(content).unwrap();
+        assert_eq!(layer.objects.len(), 2);
+        assert_eq!(layer.apertures[&10].shapes.len(), 2);
+        assert_eq!(layer.apertures[&10].diameter(), Some(0.254));
+
+        let thermal = &layer.apertures[&11].shapes;
+        assert_eq!(thermal.iter().filter(|(exposure, _)| !exposure).count(), 3);
+        let bounds = layer.apertures[&12].bounds().unwrap();
+        assert!((bounds.width() - 5.08).abs() < 1e-9 && (bounds.height() - 2.54).abs() < 1e-9);
+        assert_eq!(layer.objects[1].shape, Shape::Flash { at: Point::new(50.8, 0.0), aperture: 12 });
+    }
+
+    #[test]
+    fn limits_repeats_and_vertices_from_the_file() {
+        let content = "%FSLAX46Y46*%\n%MOMM*%\n%AMOUT*4,1,100000000,0,0,1,0,1,1,0*%\n%ADD10P,1X1000000000*%\n%ADD11OUT*%\n%SRX100000Y100000I1J1*%\nD10*\nX0Y0D03*\n%SR*%\nM02*\n";
+        let layer = parse(content).unwrap();
+        assert_eq!(layer.objects.len(), 1);
+        match &layer.apertures[&10].shapes[0].1 {
+            Primitive::Polygon(points) => assert_eq!(points.len(), 12),
+            shape => panic!("unexpected shape {:?}", shape),
+        }
+        match &layer.apertures[&11].shapes[0].1 {
+            Primitive::Polygon(points) => assert_eq!(points.len(), 3),
+            shape => panic!("unexpected shape {:?}", shape),
+        }
+        assert_eq!(layer.warnings.len(), 3, "{:?}", layer.warnings);
+    }
+
+    #[test]
+    fn parses_arcs_regions_and_polarity() {
+        let content = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10C,0.1*%\nD10*\nG75*\nX10000000Y0D02*\nG03X-10000000Y0I-10000000J0D01*\n%LPC*%\nG36*\nX0Y0D02*\nG01X1000000D01*\nY1000000D01*\nX0Y0D01*\nG37*\nM02*\n";
+        let layer = parse(content).unwrap();
+        assert!(layer.objects[0].dark && !layer.objects[1].dark);
+        // 逆时针半圆经过 (0, 10)
+        let bounds = layer.path_bounds().unwrap();
+        assert!((bounds.max_y - 10.0).abs() < 1e-9 && bounds.min_y.abs() < 1e-9);
+        match &layer.objects[1].shape {
+            Shape::Region(contours) => assert_eq!(contours[0].segments.len(), 3),
+            shape => panic!("unexpected shape {:?}", shape),
+        }
+    }
+
+    #[test]
+    fn chains_outline_segments() {
+        // 最后一段反向绘制，并且与起点有 0.005 mm 的误差
+        let content = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10C,0.1*%\nD10*\nX0Y0D02*\nX10000000Y0D01*\nX10000000Y5000000D01*\nX0Y5000000D01*\nX0Y5000D02*\nX0Y5000000D01*\nX20000000Y0D02*\nX30000000Y0D01*\nM02*\n";
+        let layer = parse(content).unwrap();
+        let (closed, open) = chain_contours(&layer, 0.01);
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].segments.len(), 4);
+        assert_eq!(open.len(), 1);
+    }
+
+    #[test]
+    fn parses_large_layers() {
+        // 20 万条指令
+        let mut content = String::from("%FSLAX46Y46*%\n%MOMM*%\n%ADD10C,0.150000*%\nD10*\n");
+        for i in 0..100_000 {
+            content.push_str(&format!("X{}Y{}D02*\nX{}Y{}D01*\n", i * 100, i % 1000 * 1000, i * 100 + 5000, i % 1000 * 1000));
+        }
+        let layer = parse(&content).unwrap();
+        assert_eq!(layer.objects.len(), 100_000);
+    }
+}
@@ -0,0 +1,177 @@
+// SPDX-FileCopyrightText: 2025 HalfSweet
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::mapping::MapTarget;
+
+/// 只读取文件开头的这部分内容用于判断，文件属性和钻孔文件头都在开头
+const HEAD_SIZE: u64 = 64 * 1024;
+
+/// 根据文件内容猜测文件对应的JLC层
+///
+/// 识别 Gerber X2 的 `%TF.FileFunction` 属性（包括 KiCad 写在 `G04 #@!` 注释中的形式）
+/// 以及 Excellon 钻孔文件头。
+pub fn guess_from_content(path: &Path) -> Option<MapTarget> {
+    let head = read_head(path)?;
+    if let Some(function) = file_function(&head) {
+        return from_file_function(&function);
+    }
+
+    // Excellon 钻孔文件以 M48 开头
+    if head.lines().any(|line| line.trim() == "M48") {
+        let non_plated = head.contains("NonPlated") || head.contains("NPTH");
+        return Some(MapTarget::Layer(if non_plated { "NPTH_Through" } else { "PTH_Through" }));
+    }
+    None
+}
+
+/// 内层在X2属性中的物理位置（第几个内层），没有属性时返回 `None`
+pub fn inner_position(path: &Path) -> Option<u32> {
+    match from_file_function(&file_function(&read_head(path)?)?)? {
+        MapTarget::InnerLayer(num) => Some(num),
+        _ => None,
+    }
+}
+
+fn read_head(path: &Path) -> Option<String> {
+    use std::io::Read;
+
+    let mut head = Vec::new();
+    std::fs::File::open(path)
+        .ok()?
+        .take(HEAD_SIZE)
+        .read_to_end(&mut head)
+        .ok()?;
+    Some(String::from_utf8_lossy(&head).to_string())
+}
+
+fn file_function(head: &str) -> Option<String> {
+    let function_re = Regex::new(r"TF\.FileFunction,([^*%\r\n]*)").unwrap();
+    function_re.captures(head).map(|caps| caps[1].to_string())
+}
+
+/// 解析 `FileFunction` 属性的值，例如 `Copper,L2,Inr`、`Soldermask,Top`
+fn from_file_function(value: &str) -> Option<MapTarget> {
+    let fields: Vec<&str> = value.split(',').map(str::trim).collect();
+    let side = |index: usize| fields.get(index).copied().unwrap_or_default();
+    let layer = |top: &'static str, bottom: &'static str| match side(1) {
+        "Top" => Some(MapTarget::Layer(top)),
+        "Bot" => Some(MapTarget::Layer(bottom)),
+        _ => None,
+    };
+
+    match fields.first().copied()? {
+        "Copper" => match side(2) {
+            "Top" => Some(MapTarget::Layer("Top_Layer")),
+            "Bot" => Some(MapTarget::Layer("Bottom_Layer")),
+            // 内层从 L2 开始编号，对应 JLC 的第 1 个内层
+            "Inr" => side(1)
+                .trim_start_matches('L')
+                .parse::<u32>()
+                .ok()
+                .filter(|num| *num > 1)
+                .map(|num| MapTarget::InnerLayer(num - 1)),
+            _ => None,
+        },
+        "Soldermask" => layer("Top_Soldermask", "Bottom_Soldermask"),
+        "Legend" => layer("Top_Silkscreen", "Bottom_Silkscreen"),
+        "Paste" => layer("Top_PasteMask", "Bottom_PasteMask"),
+        "Profile" => Some(MapTarget::Layer("Board_Outline")),
+        "Plated" => Some(MapTarget::Layer("PTH_Through")),
+        "NonPlated" => Some(MapTarget::Layer("NPTH_Through")),
+        _ => None,
+    }
+}
+
+/// 根据文件名猜测文件对应的JLC层，识别常见的扩展名和层名称关键字
+pub fn guess_from_name(file_name: &str) -> Option<MapTarget> {
+    let name = file_name.to_lowercase();
+    let extension = name.rsplit_once('.').map(|(_, ext)| ext).unwrap_or_default();
+
+    let by_extension = match extension {
+        "gtl" => Some("Top_Layer"),
+        "gbl" => Some("Bottom_Layer"),
+        "gto" => Some("Top_Silkscreen"),
+        "gbo" => Some("Bottom_Silkscreen"),
+        "gts" => Some("Top_Soldermask"),
+        "gbs" => Some("Bottom_Soldermask"),
+        "gtp" => Some("Top_PasteMask"),
+        "gbp" => Some("Bottom_PasteMask"),
+        "gko" | "gm1" | "gml" | "gm" => Some("Board_Outline"),
+        _ => None,
+    };
+    if let Some(key) = by_extension {
+        return Some(MapTarget::Layer(key));
+    }
+
+    // 内层：.g1、.g2 或 .gp1 等扩展名，或者 In1_Cu 之类的名称
+    let inner_re = Regex::new(r"^(?:g|gp)(\d+)$|in(\d+)[_.-]?cu|inner(?:layer)?[_.-]?(\d+)").unwrap();
+    let inner_text = if inner_re.is_match(extension) { extension } else { name.as_str() };
+    if let Some(caps) = inner_re.captures(inner_text) {
+        let num = caps.iter().skip(1).flatten().next()?.as_str().parse::<u32>().ok()?;
+        if num > 0 {
+            return Some(MapTarget::InnerLayer(num));
+        }
+    }
+
+    let has = |words: &[&str]| words.iter().any(|word| name.contains(word));
+    // KiCad 的 F_Cu、B_Mask 等名称
+    let side_re = Regex::new(r"(?:^|[^a-z])([fb])[_.]").unwrap();
+    let side = side_re.captures(&name).map(|caps| caps[1].to_string());
+    let top = has(&["top", "front"]) || side.as_deref() == Some("f");
+    let bottom = has(&["bottom", "bot", "back"]) || side.as_deref() == Some("b");
+    let sided = |top_key: &'static str, bottom_key: &'static str| {
+        if top && !bottom {
+            Some(MapTarget::Layer(top_key))
+        } else if bottom && !top {
+            Some(MapTarget::Layer(bottom_key))
+        } else {
+            None
+        }
+    };
+
+    if has(&["npth", "non-plated", "nonplated"]) {
+        Some(MapTarget::Layer("NPTH_Through"))
+    } else if has(&["drl", "drill", "pth", "xln"]) {
+        Some(MapTarget::Layer("PTH_Through"))
+    } else if has(&["outline", "edge", "profile", "border", "keepout"]) {
+        Some(MapTarget::Layer("Board_Outline"))
+    } else if has(&["silk", "legend", "overlay"]) {
+        sided("Top_Silkscreen", "Bottom_Silkscreen")
+    } else if has(&["mask", "solder"]) {
+        sided("Top_Soldermask", "Bottom_Soldermask")
+    } else if has(&["paste", "stencil"]) {
+        sided("Top_PasteMask", "Bottom_PasteMask")
+    } else if has(&["cu", "copper", "signal", "layer"]) {
+        sided("Top_Layer", "Bottom_Layer")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guesses_from_file_function() {
+        assert_eq!(from_file_function("Copper,L1,Top"), Some(MapTarget::Layer("Top_Layer")));
+        assert_eq!(from_file_function("Copper,L3,Inr"), Some(MapTarget::InnerLayer(2)));
+        assert_eq!(from_file_function("Soldermask,Bot"), Some(MapTarget::Layer("Bottom_Soldermask")));
+        assert_eq!(from_file_function("Profile,NP"), Some(MapTarget::Layer("Board_Outline")));
+    }
+
+    #[test]
+    fn guesses_from_file_name() {
+        assert_eq!(guess_from_name("board.GTO"), Some(MapTarget::Layer("Top_Silkscreen")));
+        assert_eq!(guess_from_name("board.g2"), Some(MapTarget::InnerLayer(2)));
+        assert_eq!(guess_from_name("board-In3_Cu.gbr"), Some(MapTarget::InnerLayer(3)));
+        assert_eq!(guess_from_name("outline.gbr"), Some(MapTarget::Layer("Board_Outline")));
+        assert_eq!(guess_from_name("bottom_mask.gbr"), Some(MapTarget::Layer("Bottom_Soldermask")));
+        assert_eq!(guess_from_name("pcb_top-F_Cu.gbr"), Some(MapTarget::Layer("Top_Layer")));
+        assert_eq!(guess_from_name("readme.txt"), None);
+    }
+}
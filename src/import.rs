@@ -0,0 +1,430 @@
+// SPDX-FileCopyrightText: 2025 HalfSweet
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use crate::gerber::{CoordFormat, Point};
+
+/// 曲线展开为折线时每一段的最大长度（毫米）
+const CURVE_STEP: f64 = 0.1;
+
+/// SVG 的像素按 96 DPI 换算为毫米
+const MM_PER_PX: f64 = 25.4 / 96.0;
+
+/// 图层名称包含这些词时视为尺寸标注、图框等注释图层，其中的图元不导入
+const ANNOTATION_LAYERS: [&str; 7] = ["DIM", "TITLE", "TEXT", "ANNO", "NOTE", "BORDER", "FRAME"];
+
+/// 从机械图纸导入的板框
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutlineImport {
+    /// DXF 或 SVG 文件
+    pub path: PathBuf,
+
+    /// 是否替换EDA导出的板框，为 `false` 时与其合并
+    pub replace: bool,
+}
+
+/// 板框中的一条边，坐标单位为毫米
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Edge {
+    Line { from: Point, to: Point },
+
+    /// 圆弧，起点与终点相同时为整圆
+    Arc { from: Point, to: Point, center: Point, ccw: bool },
+}
+
+/// 解析后的图纸
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Drawing {
+    pub edges: Vec<Edge>,
+
+    /// 忽略的不支持的图元
+    pub warnings: Vec<String>,
+}
+
+impl Drawing {
+    /// 按文件扩展名读取 DXF 或 SVG 文件
+    pub fn load(path: &Path) -> Result<Drawing, std::io::Error> {
+        let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("").to_ascii_lowercase();
+        let parse = match extension.as_str() {
+            "dxf" => parse_dxf,
+            "svg" => parse_svg,
+            _ => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("Cannot import outline '{}': only .dxf and .svg files are supported", path.display()),
+                ))
+            }
+        };
+        // 旧版本的 DXF 使用本地代码页保存，不一定是 UTF-8
+        let content = std::fs::read(path).map_err(|e| {
+            std::io::Error::new(e.kind(), format!("Cannot read outline '{}': {}", path.display(), e))
+        })?;
+        parse(&String::from_utf8_lossy(&content))
+    }
+
+    /// 输出为毫米单位的Gerber板框层
+    pub fn to_gerber(&self) -> String {
+        let format = CoordFormat::default();
+        let xy = |point: Point| format!("X{}Y{}", format.format(point.x, 6), format.format(point.y, 6));
+
+        let mut gerber = String::from(
+            "%FSLAX46Y46*%\n%MOMM*%\n%TF.FileFunction,Profile,NP*%\nG04 Board outline imported by TransJLC*\n%ADD10C,0.100000*%\nG75*\nG01*\nD10*\n",
+        );
+        let mut current: Option<Point> = None;
+        for edge in &self.edges {
+            let (from, to) = match *edge {
+                Edge::Line { from, to } | Edge::Arc { from, to, .. } => (from, to),
+            };
+            if current.is_none_or(|current| current.distance(from) > 1e-6) {
+                let _ = writeln!(gerber, "{}D02*", xy(from));
+            }
+            match *edge {
+                Edge::Line { .. } => {
+                    let _ = writeln!(gerber, "{}D01*", xy(to));
+                }
+                Edge::Arc { center, ccw, .. } => {
+                    let _ = writeln!(
+                        gerber,
+                        "{}*\n{}I{}J{}D01*\nG01*",
+                        if ccw { "G03" } else { "G02" },
+                        xy(to),
+                        format.format(center.x - from.x, 6),
+                        format.format(center.y - from.y, 6)
+                    );
+                }
+            }
+            current = Some(to);
+        }
+        gerber.push_str("M02*\n");
+        gerber
+    }
+}
+
+/// 解析 DXF 文件 `ENTITIES` 段中的 LINE、ARC、CIRCLE 和 LWPOLYLINE
+///
+/// 单位取自 `$INSUNITS`，没有设置时按毫米处理。尺寸标注、标题栏、图框等注释图层上的图元会被跳过。
+pub fn parse_dxf(content: &str) -> Result<Drawing, std::io::Error> {
+    let invalid = |msg: String| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Invalid DXF file: {}", msg));
+
+    let lines: Vec<&str> = content.lines().map(str::trim).collect();
+    let mut pairs = vec![];
+    for pair in lines.chunks(2) {
+        let [code, value] = pair else {
+            break;
+        };
+        let code: i32 = code.parse().map_err(|_| invalid(format!("'{}' is not a group code", code)))?;
+        pairs.push((code, *value));
+    }
+
+    let mut drawing = Drawing::default();
+    let mut skipped: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut scale = 1.0;
+    let mut section = "";
+    let mut index = 0;
+    while index < pairs.len() {
+        let (code, value) = pairs[index];
+        index += 1;
+        match (code, value) {
+            (0, "SECTION") => {
+                section = pairs.get(index).filter(|(code, _)| *code == 2).map(|(_, name)| *name).unwrap_or("");
+            }
+            (0, "ENDSEC") => section = "",
+            (9, "$INSUNITS") if section == "HEADER" => {
+                let units = pairs.get(index).map(|(_, value)| *value).unwrap_or("0");
+                scale = match units {
+                    "0" | "4" => 1.0,
+                    "1" => 25.4,
+                    "2" => 304.8,
+                    "5" => 10.0,
+                    "6" => 1000.0,
+                    "8" => 0.0000254,
+                    "9" => 0.0254,
+                    "13" => 0.001,
+                    "14" => 100.0,
+                    _ => {
+                        drawing.warnings.push(format!("unknown $INSUNITS {}, millimeters are assumed", units));
+                        1.0
+                    }
+                };
+            }
+            (0, entity) if section == "ENTITIES" => {
+                let end = pairs[index..].iter().position(|(code, _)| *code == 0).map_or(pairs.len(), |end| index + end);
+                let fields = &pairs[index..end];
+                index = end;
+                let layer = fields.iter().find(|(code, _)| *code == 8).map_or("0", |(_, layer)| *layer);
+                if is_annotation_layer(layer) {
+                    *skipped.entry(layer).or_default() += 1;
+                    continue;
+                }
+                let edges = dxf_entity(entity, fields).map_err(|msg| invalid(format!("{} entity: {}", entity, msg)))?;
+                match edges {
+                    Some(edges) => drawing.edges.extend(edges),
+                    None => drawing.warnings.push(format!("unsupported DXF entity {} ignored", entity)),
+                }
+            }
+            _ => {}
+        }
+    }
+    for (layer, count) in skipped {
+        drawing.warnings.push(format!("{} entities on annotation layer '{}' ignored", count, layer));
+    }
+
+    for edge in &mut drawing.edges {
+        *edge = match *edge {
+            Edge::Line { from, to } => Edge::Line { from: scaled(from, scale), to: scaled(to, scale) },
+            Edge::Arc { from, to, center, ccw } => {
+                Edge::Arc { from: scaled(from, scale), to: scaled(to, scale), center: scaled(center, scale), ccw }
+            }
+        };
+    }
+    Ok(drawing)
+}
+
+fn is_annotation_layer(layer: &str) -> bool {
+    let layer = layer.to_ascii_uppercase();
+    ANNOTATION_LAYERS.iter().any(|word| layer.contains(word))
+}
+
+fn scaled(point: Point, scale: f64) -> Point {
+    Point::new(point.x * scale, point.y * scale)
+}
+
+/// 把一个图元转换为板框的边，不支持的图元返回 `None`
+fn dxf_entity(entity: &str, fields: &[(i32, &str)]) -> Result<Option<Vec<Edge>>, String> {
+    let number = |code: i32| -> Result<f64, String> {
+        let (_, value) = fields.iter().find(|(c, _)| *c == code).ok_or(format!("group code {} is missing", code))?;
+        value.parse::<f64>().map_err(|_| format!("'{}' is not a number", value))
+    };
+    // 法向量为 (0, 0, -1) 的图元坐标是镜像的
+    let mirrored = fields.iter().any(|(code, value)| *code == 230 && value.parse::<f64>().is_ok_and(|z| z < 0.0));
+    let ocs = |point: Point| if mirrored { Point::new(-point.x, point.y) } else { point };
+
+    let edges = match entity {
+        "LINE" => vec![Edge::Line {
+            from: Point::new(number(10)?, number(20)?),
+            to: Point::new(number(11)?, number(21)?),
+        }],
+        "CIRCLE" => {
+            let center = ocs(Point::new(number(10)?, number(20)?));
+            let start = Point::new(center.x + number(40)?, center.y);
+            vec![Edge::Arc { from: start, to: start, center, ccw: true }]
+        }
+        "ARC" => {
+            let center = Point::new(number(10)?, number(20)?);
+            let radius = number(40)?;
+            let point = |degrees: f64| {
+                let angle = degrees.to_radians();
+                ocs(Point::new(center.x + radius * angle.cos(), center.y + radius * angle.sin()))
+            };
+            vec![Edge::Arc { from: point(number(50)?), to: point(number(51)?), center: ocs(center), ccw: !mirrored }]
+        }
+        "LWPOLYLINE" => {
+            let closed = fields.iter().any(|(code, value)| *code == 70 && value.parse::<u32>().is_ok_and(|flags| flags & 1 != 0));
+            // 每个顶点的坐标和到下一个顶点的凸度
+            let mut vertices: Vec<(Point, f64)> = vec![];
+            for (code, value) in fields {
+                let value = || value.parse::<f64>().map_err(|_| format!("'{}' is not a number", value));
+                match code {
+                    10 => vertices.push((Point::new(value()?, 0.0), 0.0)),
+                    20 => vertices.last_mut().ok_or("vertex without X")?.0.y = value()?,
+                    42 => vertices.last_mut().ok_or("bulge without vertex")?.1 = value()?,
+                    _ => {}
+                }
+            }
+            let count = if closed { vertices.len() } else { vertices.len().saturating_sub(1) };
+            (0..count)
+                .map(|index| {
+                    let (from, bulge) = vertices[index];
+                    let (to, _) = vertices[(index + 1) % vertices.len()];
+                    bulge_edge(ocs(from), ocs(to), if mirrored { -bulge } else { bulge })
+                })
+                .collect()
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(edges))
+}
+
+/// 凸度 `bulge` 是圆弧圆心角四分之一的正切，为正时逆时针
+fn bulge_edge(from: Point, to: Point, bulge: f64) -> Edge {
+    let length = from.distance(to);
+    if bulge.abs() < 1e-9 || length < 1e-9 {
+        return Edge::Line { from, to };
+    }
+    // 圆心位于弦的中垂线上，逆时针时在弦的左侧
+    let offset = (1.0 - bulge * bulge) / (4.0 * bulge);
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    let center = Point::new((from.x + to.x) / 2.0 - dy * offset, (from.y + to.y) / 2.0 + dx * offset);
+    Edge::Arc { from, to, center, ccw: bulge > 0.0 }
+}
+
+/// 解析 SVG 文件中所有可见的路径和基本图形，曲线展开为折线
+///
+/// 坐标按 96 DPI 从像素换算为毫米，Y 轴翻转为向上，图纸的左下角位于原点。
+pub fn parse_svg(content: &str) -> Result<Drawing, std::io::Error> {
+    use resvg::usvg::{tiny_skia_path::PathSegment, Group, Node, Options, Tree};
+
+    let tree = Tree::from_str(content, &Options::default())
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Invalid SVG file: {}", e)))?;
+    let height = tree.size().height() as f64;
+    let point = |p: resvg::usvg::tiny_skia_path::Point| Point::new(p.x as f64 * MM_PER_PX, (height - p.y as f64) * MM_PER_PX);
+
+    fn paths(group: &Group, found: &mut Vec<resvg::usvg::tiny_skia_path::Path>) {
+        for node in group.children() {
+            match node {
+                Node::Group(group) => paths(group, found),
+                Node::Path(path) => {
+                    if let Some(data) = path.data().clone().transform(path.abs_transform()) {
+                        found.push(data);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    let mut found = vec![];
+    paths(tree.root(), &mut found);
+
+    let mut drawing = Drawing::default();
+    for path in found {
+        let (mut start, mut current) = (Point::default(), Point::default());
+        let line = |edges: &mut Vec<Edge>, from: Point, to: Point| {
+            if from.distance(to) > 1e-9 {
+                edges.push(Edge::Line { from, to });
+            }
+        };
+        for segment in path.segments() {
+            match segment {
+                PathSegment::MoveTo(p) => {
+                    start = point(p);
+                    current = start;
+                }
+                PathSegment::LineTo(p) => {
+                    line(&mut drawing.edges, current, point(p));
+                    current = point(p);
+                }
+                PathSegment::QuadTo(p1, p) => {
+                    let (p0, p1, p2) = (current, point(p1), point(p));
+                    for t in curve_steps(&[p0, p1, p2]) {
+                        let u = 1.0 - t;
+                        let next = Point::new(
+                            u * u * p0.x + 2.0 * u * t * p1.x + t * t * p2.x,
+                            u * u * p0.y + 2.0 * u * t * p1.y + t * t * p2.y,
+                        );
+                        line(&mut drawing.edges, current, next);
+                        current = next;
+                    }
+                }
+                PathSegment::CubicTo(p1, p2, p) => {
+                    let (p0, p1, p2, p3) = (current, point(p1), point(p2), point(p));
+                    for t in curve_steps(&[p0, p1, p2, p3]) {
+                        let u = 1.0 - t;
+                        let next = Point::new(
+                            u * u * u * p0.x + 3.0 * u * u * t * p1.x + 3.0 * u * t * t * p2.x + t * t * t * p3.x,
+                            u * u * u * p0.y + 3.0 * u * u * t * p1.y + 3.0 * u * t * t * p2.y + t * t * t * p3.y,
+                        );
+                        line(&mut drawing.edges, current, next);
+                        current = next;
+                    }
+                }
+                PathSegment::Close => {
+                    line(&mut drawing.edges, current, start);
+                    current = start;
+                }
+            }
+        }
+    }
+    Ok(drawing)
+}
+
+/// 按控制点连线的长度把贝塞尔曲线等分，返回每一段终点的参数 `t`
+fn curve_steps(points: &[Point]) -> impl Iterator<Item = f64> {
+    let length: f64 = points.windows(2).map(|pair| pair[0].distance(pair[1])).sum();
+    let steps = ((length / CURVE_STEP).ceil() as usize).clamp(1, 256);
+    (1..=steps).map(move |step| if step == steps { 1.0 } else { step as f64 / steps as f64 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gerber;
+    use crate::outline::{self, OutlineIssue};
+
+    // usvg 使用 f32 坐标
+    fn assert_close(a: Point, b: Point) {
+        assert!(a.distance(b) < 1e-4, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn imports_dxf_entities() {
+        // 英寸单位，1 x 0.5 英寸的矩形，右侧为半圆，中间一个圆孔，外加一个不支持的样条曲线
+        let dxf = "0\nSECTION\n2\nHEADER\n9\n$INSUNITS\n70\n1\n0\nENDSEC\n0\nSECTION\n2\nENTITIES\n\
+                   0\nLWPOLYLINE\n8\n0\n90\n4\n70\n1\n10\n0\n20\n0\n10\n1\n20\n0\n42\n1\n10\n1\n20\n0.5\n10\n0\n20\n0.5\n\
+                   0\nCIRCLE\n8\n0\n10\n0.5\n20\n0.25\n40\n0.1\n0\nSPLINE\n8\n0\n0\nENDSEC\n0\nEOF\n";
+        let drawing = parse_dxf(dxf).unwrap();
+        assert_eq!(drawing.warnings, ["unsupported DXF entity SPLINE ignored"]);
+        assert_eq!(drawing.edges.len(), 5);
+        let Edge::Arc { from, to, center, ccw } = drawing.edges[1] else {
+            panic!("expected an arc, got {:?}", drawing.edges[1]);
+        };
+        assert_close(from, Point::new(25.4, 0.0));
+        assert_close(to, Point::new(25.4, 12.7));
+        assert_close(center, Point::new(25.4, 6.35));
+        assert!(ccw);
+
+        let layer = gerber::parse(&drawing.to_gerber()).unwrap();
+        assert_eq!(outline::validate(&layer, outline::DEFAULT_TOLERANCE), []);
+        let bounds = layer.bounds().unwrap().expand(-0.05);
+        assert!((bounds.max_x - (25.4 + 6.35)).abs() < 1e-3);
+
+        let arc = "0\nSECTION\n2\nENTITIES\n0\nARC\n10\n0\n20\n0\n40\n2\n50\n0\n51\n90\n230\n-1\n0\nENDSEC\n0\nEOF\n";
+        let drawing = parse_dxf(arc).unwrap();
+        assert_eq!(
+            drawing.edges,
+            [Edge::Arc { from: Point::new(-2.0, 0.0), to: Point::new(-2.0 * 90f64.to_radians().cos(), 2.0), center: Point::new(-0.0, 0.0), ccw: false }]
+        );
+        assert!(parse_dxf("0\nSECTION\n2\nENTITIES\n0\nLINE\n10\n0\n20\n0\n0\nENDSEC\n").is_err());
+    }
+
+    #[test]
+    fn skips_annotation_layers_and_reads_non_utf8_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("board.dxf");
+        let mut dxf = b"999\nGeh\xe4use\n0\nSECTION\n2\nENTITIES\n".to_vec();
+        dxf.extend_from_slice(b"0\nLWPOLYLINE\n8\nOutline\n90\n4\n70\n1\n10\n0\n20\n0\n10\n10\n20\n0\n10\n10\n20\n5\n10\n0\n20\n5\n");
+        dxf.extend_from_slice(b"0\nLINE\n8\nDimensions\n10\n0\n20\n-2\n11\n10\n21\n-2\n");
+        dxf.extend_from_slice(b"0\nLINE\n8\nTITLE_BLOCK\n10\n0\n20\n-9\n11\n50\n21\n-9\n0\nENDSEC\n0\nEOF\n");
+        std::fs::write(&path, dxf).unwrap();
+
+        let drawing = Drawing::load(&path).unwrap();
+        assert_eq!(drawing.edges.len(), 4);
+        assert_eq!(
+            drawing.warnings,
+            [
+                "1 entities on annotation layer 'Dimensions' ignored",
+                "1 entities on annotation layer 'TITLE_BLOCK' ignored",
+            ]
+        );
+    }
+
+    #[test]
+    fn imports_svg_paths() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="40mm" height="20mm" viewBox="0 0 40 20">
+            <g transform="translate(5 5)"><path d="M0 0 H30 V10 H0 Z" fill="none" stroke="black"/></g>
+            <circle cx="20" cy="10" r="2" fill="none" stroke="black"/>
+        </svg>"#;
+        let drawing = parse_svg(svg).unwrap();
+        let layer = gerber::parse(&drawing.to_gerber()).unwrap();
+        assert_eq!(outline::validate(&layer, outline::DEFAULT_TOLERANCE), Vec::<OutlineIssue>::new());
+
+        let Edge::Line { from, to } = drawing.edges[0] else {
+            panic!("expected a line");
+        };
+        assert_close(from, Point::new(5.0, 15.0));
+        assert_close(to, Point::new(35.0, 15.0));
+        assert!(drawing.edges.len() > 4 + 16);
+    }
+}
@@ -3,25 +3,66 @@
 
 #![allow(non_snake_case)]
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use regex::Regex;
 use rust_embed::RustEmbed;
+use rust_i18n::t;
 use zip::write::SimpleFileOptions;
 
+use crate::log::{event, Level, LOG};
 use crate::FileName::*;
 
 rust_i18n::i18n!("i18n");
 
 mod FileName;
-mod log;
+mod archive;
+mod board_set;
+mod config;
+mod dxf;
+pub mod excellon;
+pub mod gerber;
+mod guess;
+pub mod import;
+pub mod log;
+mod mapping;
+pub mod outline;
+pub mod preflight;
+mod output;
+mod plane;
+pub mod render;
+mod report;
+mod stack;
+mod summary;
+mod template;
+pub mod wizard;
+
+pub use archive::{ArchiveKind, ExtractLimits};
+pub use board_set::BoardSet;
+pub use config::{
+    find_project_config, load_config, save_file_mappings, save_rules, user_config_path, Config,
+    PROJECT_CONFIG_NAME,
+};
+pub use mapping::{FileMapping, MapTarget};
+pub use output::{parse_timestamp, OverwritePolicy, ZipCompression, ZipOptions};
+pub use dxf::DXF_NAME;
+pub use report::REPORT_NAME;
+pub use summary::BoardSummary;
+pub use template::{is_template, TemplateVars};
+
+/// 作为输入或输出路径时表示标准输入/标准输出
+pub const STDIO_PATH: &str = "-";
+
+/// 生成的板框在日志和报告中使用的文件名
+const GENERATED_OUTLINE_NAME: &str = "generated_outline.gbr";
 
 #[derive(RustEmbed)]
 #[folder = "Assets/"]
 struct Asset;
 
+#[derive(Clone)]
 pub enum EDA {
     Kicad,
     Protel,
@@ -53,6 +94,9 @@ pub struct JLC {
     /// The path to the Gerber file
     pub path: String,
 
+    /// 用户指定的原始输入路径（`path` 在解压后会指向临时目录）
+    pub input_path: String,
+
     /// 输出路径
     pub output_path: String,
 
@@ -68,13 +112,93 @@ pub struct JLC {
     /// 是否为导入的PCB文档
     pub is_imported_pcb_doc: bool,
 
-    /// 临时目录（用于解压ZIP文件）
+    /// 临时目录（用于解压输入的压缩包）
     pub temp_dir: Option<tempfile::TempDir>,
+
+    /// 处理文件时使用的临时工作目录，避免转换失败时在输出目录留下不完整的文件
+    pub work_dir: Option<tempfile::TempDir>,
+
+    /// 输出目录中已有文件的处理策略
+    pub overwrite: OverwritePolicy,
+
+    /// 解压输入压缩包时的安全限制
+    pub extract_limits: ExtractLimits,
+
+    /// 指定需要处理的文件列表，为空时读取 `path` 目录下的文件
+    pub source_files: Option<Vec<PathBuf>>,
+
+    /// 源工程名称，写入ZIP注释
+    pub source_name: String,
+
+    /// 输出ZIP的格式选项
+    pub zip_options: ZipOptions,
+
+    /// 配置文件中附加的文件名规则，JLC层名称到正则表达式列表
+    pub extra_rules: BTreeMap<String, Vec<String>>,
+
+    /// 需要忽略的文件名通配符
+    pub ignore_patterns: Vec<String>,
+
+    /// 手动指定的文件映射，按顺序匹配，优先于EDA风格的规则
+    pub file_mappings: Vec<FileMapping>,
+
+    /// 是否将负片内电层转换为正片
+    pub positive_planes: bool,
+
+    /// 在输出中附带的审阅报告的文件名，为 `None` 时不生成报告
+    pub report_name: Option<String>,
+
+    /// 渲染报告时使用的颜色
+    pub colors: render::Colors,
+
+    /// 已转换的文件，原始文件名到JLC文件名
+    pub converted: Vec<(String, String)>,
+
+    /// 是否把板子尺寸和铜层数写到下单必读文件中
+    pub notes_summary: bool,
+
+    /// 检查板框时端点相连的容差（毫米）
+    pub outline_tolerance: f64,
+
+    /// 需要与板框合并为一个文件的文件名通配符
+    pub outline_merge: Vec<String>,
+
+    /// 只对某个EDA风格生效的板框合并通配符，键为小写的风格名称
+    pub style_outline_merge: BTreeMap<String, Vec<String>>,
+
+    /// 从 DXF 或 SVG 文件导入的板框
+    pub import_outline: Option<import::OutlineImport>,
+
+    /// 没有板框文件时生成板框，为 `None` 时不生成
+    pub generate_outline: Option<outline::OutlineGenerator>,
+
+    /// 转换完成后按这个工艺能力检查各层，为 `None` 时不检查
+    pub preflight: Option<preflight::Profile>,
+
+    /// 转换完成后的板子尺寸和铜层数
+    pub summary: Option<BoardSummary>,
+
+    /// 开始转换这块板子时已有的警告数量，报告只包含之后的警告
+    warning_start: usize,
+
+    /// 解析过的处理之后的各层，第一次使用时解析，转换文件后清空
+    parsed: std::cell::OnceCell<Vec<ParsedLayer>>,
 }
 
 impl JlcTrait for JLC {
     fn new(path: String, output_path: String, eda: EDA) -> Self {
+        let source_name = if path == STDIO_PATH {
+            "stdin".to_string()
+        } else {
+            std::fs::canonicalize(&path)
+                .unwrap_or_else(|_| PathBuf::from(&path))
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default()
+        };
+
         Self {
+            input_path: path.clone(),
             path,
             output_path,
             eda,
@@ -82,6 +206,29 @@ impl JlcTrait for JLC {
             ignore_hash: false,
             is_imported_pcb_doc: false,
             temp_dir: None,
+            work_dir: None,
+            overwrite: OverwritePolicy::default(),
+            extract_limits: ExtractLimits::default(),
+            source_files: None,
+            source_name,
+            zip_options: ZipOptions::default(),
+            extra_rules: BTreeMap::new(),
+            ignore_patterns: vec![],
+            file_mappings: vec![],
+            positive_planes: false,
+            report_name: None,
+            colors: render::Colors::default(),
+            converted: vec![],
+            notes_summary: false,
+            outline_tolerance: outline::DEFAULT_TOLERANCE,
+            outline_merge: vec![],
+            style_outline_merge: BTreeMap::new(),
+            import_outline: None,
+            generate_outline: None,
+            preflight: None,
+            summary: None,
+            warning_start: LOG.warning_count(),
+            parsed: std::cell::OnceCell::new(),
         }
     }
 
@@ -94,17 +241,491 @@ impl JlcTrait for JLC {
         // 把这个文件写到工作目录
         let working_dir = self.get_working_dir();
         std::fs::create_dir_all(&working_dir)?;
-        std::fs::write(working_dir.join(NAME), content.data.as_ref())?;
+        let mut data = content.data.to_vec();
+        if let (true, Some(summary)) = (self.notes_summary, &self.summary) {
+            data.extend_from_slice(summary.notes().as_bytes());
+        }
+        std::fs::write(working_dir.join(NAME), data)?;
         self.process_path.insert(working_dir.join(NAME));
         Ok(())
     }
 
     fn copy_file(&mut self) -> Result<(), std::io::Error> {
-        let files = std::fs::read_dir(&self.path)?
-            .map(|res| res.map(|e| e.path()))
+        // 始终在独立的临时目录中处理，输出目录只在最终输出时写入
+        if self.work_dir.is_none() {
+            self.work_dir = Some(tempfile::TempDir::new()?);
+        }
+
+        let files = self.input_files()?;
+        let style = self.detect_style(&files);
+
+        match style {
+            Some(style) => LOG.trace(&format!("Using EDA style: {}", style.EDA_Name)),
+            // 没有识别出风格时，只使用手动映射和配置文件中的规则
+            None if !self.file_mappings.is_empty() || !self.extra_rules.is_empty() => {
+                LOG.trace("No EDA style detected, only manual mappings and config rules are used")
+            }
+            None => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    "No matching EDA style found, use --map or --interactive to assign files manually",
+                ))
+            }
+        }
+
+        let ignore_res = self
+            .ignore_patterns
+            .iter()
+            .map(|pattern| config::glob_regex(pattern))
+            .collect::<Result<Vec<_>, std::io::Error>>()?;
+
+        let rules = compile_rules(&self.rules(style))?;
+
+        // 对KiCad风格的文件进行Dx*到G54Dx*的转换
+        let is_kicad = matches!(self.eda, EDA::Kicad)
+            || style.is_some_and(|style| style.EDA_Name == "KiCAD");
+        let style_name = style.map(|style| style.EDA_Name).unwrap_or("config");
+
+        // 合并到板框的文件：命令行和配置文件中的通配符，以及当前风格的通配符
+        let merge_patterns: Vec<String> = self
+            .outline_merge
+            .iter()
+            .chain(self.style_outline_merge.get(&style_name.to_lowercase()).into_iter().flatten())
+            .cloned()
+            .collect();
+        let merge_res = merge_patterns
+            .iter()
+            .map(|pattern| config::glob_regex(pattern))
             .collect::<Result<Vec<_>, std::io::Error>>()?;
 
-        let style = match &self.eda {
+        // 先确定每个文件对应的层，内层需要统一编号后才能确定文件名
+        let mut plan: Vec<Planned> = vec![];
+        for file in files {
+            if !file.is_file() {
+                continue;
+            }
+
+            // 匹配文件名
+            let file_name = file.file_name().unwrap().to_str().unwrap().to_string();
+            if let Some(pattern) = ignore_res.iter().position(|re| re.is_match(&file_name)) {
+                LOG.event(
+                    Level::Trace,
+                    event::FILE_SKIPPED,
+                    &format!(
+                        "'{}' matched ignore pattern '{}', skipped",
+                        file_name, self.ignore_patterns[pattern]
+                    ),
+                    &[
+                        ("file", file_name.clone()),
+                        ("ignore", self.ignore_patterns[pattern].clone()),
+                    ],
+                );
+                continue;
+            }
+
+            // 手动映射优先于风格的规则
+            if let Some(mapping) = self.file_mappings.iter().find(|m| m.matches(&file_name)) {
+                let Some(key) = mapping.target.key() else {
+                    LOG.event(
+                        Level::Trace,
+                        event::FILE_SKIPPED,
+                        &format!("'{}' is ignored by mapping '{}'", file_name, mapping.pattern),
+                        &[
+                            ("file", file_name.clone()),
+                            ("mapping", mapping.pattern.clone()),
+                        ],
+                    );
+                    continue;
+                };
+                plan.push(Planned {
+                    file: file.clone(),
+                    file_name,
+                    key,
+                    target: mapping.target.clone(),
+                    pattern: mapping.pattern.clone(),
+                    manual: true,
+                });
+                continue;
+            }
+
+            if let Some(pattern) = merge_res.iter().position(|re| re.is_match(&file_name)) {
+                plan.push(Planned {
+                    file: file.clone(),
+                    file_name,
+                    key: "Board_Outline",
+                    target: MapTarget::Layer("Board_Outline"),
+                    pattern: merge_patterns[pattern].clone(),
+                    manual: true,
+                });
+                continue;
+            }
+
+            let mut matched = false;
+            for rule in match_rules(&rules, &file_name)? {
+                // 没有对应JLC层的规则（例如 Other）
+                let Some(target) = rule.target else {
+                    continue;
+                };
+                matched = true;
+                plan.push(Planned {
+                    file: file.clone(),
+                    file_name: file_name.clone(),
+                    key: rule.key,
+                    target,
+                    pattern: rule.pattern,
+                    manual: false,
+                });
+            }
+
+            if !matched {
+                LOG.event(
+                    Level::Trace,
+                    event::FILE_SKIPPED,
+                    &format!(
+                        "'{}' did not match any rule of {} style, skipped",
+                        file_name, style_name
+                    ),
+                    &[
+                        ("file", file_name.clone()),
+                        ("style", style_name.to_string()),
+                    ],
+                );
+            }
+        }
+
+        // 规则匹配到的内层按源文件中的编号重新编号为连续的 1..N，手动映射的编号保持不变
+        let inner: Vec<usize> = (0..plan.len())
+            .filter(|index| !plan[*index].manual && matches!(plan[*index].target, MapTarget::InnerLayer(_)))
+            .collect();
+        let sources: Vec<stack::InnerSource> = inner
+            .iter()
+            .map(|index| match plan[*index].target {
+                MapTarget::InnerLayer(num) => stack::InnerSource {
+                    file: plan[*index].file_name.clone(),
+                    number: num,
+                    plane: plan[*index].key == "InnerPlane",
+                    position: guess::inner_position(&plan[*index].file),
+                },
+                _ => unreachable!(),
+            })
+            .collect();
+        let (numbers, plane_order_inferred) = stack::renumber(&sources)?;
+        if plane_order_inferred {
+            LOG.warn("Plane layers are placed below the signal inner layers, the physical order is unknown; use --map to set it explicitly");
+        }
+        for (index, num) in inner.iter().zip(numbers) {
+            let item = &mut plan[*index];
+            if item.target != MapTarget::InnerLayer(num) {
+                transform_event(
+                    &item.file_name,
+                    "inner_renumber",
+                    &format!("inner layer {} renumbered to {}", item.target, num),
+                );
+                item.target = MapTarget::InnerLayer(num);
+            }
+        }
+
+        // 检查铜层叠层是否完整
+        let all_inner: Vec<(String, u32)> = plan
+            .iter()
+            .filter_map(|item| match item.target {
+                MapTarget::InnerLayer(num) => Some((item.file_name.clone(), num)),
+                _ => None,
+            })
+            .collect();
+        let has_layer = |key: &'static str| plan.iter().any(|item| item.target == MapTarget::Layer(key));
+        for warning in stack::check_stack(&all_inner, has_layer("Top_Layer"), has_layer("Bottom_Layer"))? {
+            LOG.warn(&warning);
+        }
+
+        if !merge_res.is_empty() {
+            self.merge_outlines(&mut plan)?;
+        }
+        if let Some(import) = &self.import_outline {
+            self.plan_imported_outline(&mut plan, import)?;
+        }
+        if let Some(generator) = self.generate_outline {
+            self.plan_generated_outline(&mut plan, generator)?;
+        }
+
+        // 负片转换为正片时使用板框的范围铺铜
+        let outline_item = plan.iter().find(|item| item.target == MapTarget::Layer("Board_Outline"));
+        let outline_name = outline_item.map(|item| item.file_name.clone());
+        let outline = outline_item
+            .and_then(|item| std::fs::read_to_string(&item.file).ok())
+            .and_then(|content| gerber::coordinate_bounds(&content));
+
+        // 多个文件对应同一层时后面的文件会覆盖前面的文件
+        let mut targets: BTreeMap<String, &str> = BTreeMap::new();
+        for item in &plan {
+            let target = item.target.file_name().unwrap();
+            if let Some(previous) = targets.insert(target.clone(), &item.file_name) {
+                LOG.warn(&format!(
+                    "'{}' and '{}' both map to {}, '{}' is used",
+                    previous, item.file_name, target, item.file_name
+                ));
+            }
+        }
+
+        for item in plan {
+            let target = item.target.file_name().unwrap();
+            let message = if item.manual {
+                format!("'{}' matched mapping '{}' -> {}", item.file_name, item.pattern, target)
+            } else {
+                format!(
+                    "'{}' matched rule {} (/{}/) of {} style -> {}",
+                    item.file_name, item.key, item.pattern, style_name, target
+                )
+            };
+            let rule = if item.manual {
+                item.target.to_string()
+            } else {
+                item.key.to_string()
+            };
+            LOG.event(
+                Level::Trace,
+                event::FILE_MATCHED,
+                &message,
+                &[
+                    ("file", item.file_name.clone()),
+                    ("rule", rule),
+                    ("pattern", item.pattern.clone()),
+                    ("style", if item.manual { "mapping" } else { style_name }.to_string()),
+                    ("target", target.clone()),
+                ],
+            );
+            let file_path = self.get_working_dir().join(target);
+            self.convert_file(&item.file, item.key, &file_path, is_kicad, outline)?;
+        }
+
+        // 检查板框是否闭合
+        if let Some(outline_name) = outline_name {
+            self.check_outline(&outline_name);
+        }
+
+        match self.board_summary() {
+            Ok(summary) => {
+                let (width, height) = summary.size().unwrap_or((0.0, 0.0));
+                LOG.event(
+                    Level::Info,
+                    event::BOARD_SUMMARY,
+                    &summary.to_string(),
+                    &[
+                        ("width", format!("{:.2}", width)),
+                        ("height", format!("{:.2}", height)),
+                        ("copper_layers", summary.copper_layers.to_string()),
+                    ],
+                );
+                self.summary = Some(summary);
+            }
+            Err(e) => LOG.warn(&format!("Cannot compute the board size: {}", e)),
+        }
+
+        if let Some(profile) = &self.preflight {
+            let issues = self.preflight_issues(profile);
+            if issues.is_empty() {
+                LOG.info_no_color(&format!("Pre-flight checks against '{}' passed", profile.name));
+            }
+            for issue in issues {
+                LOG.event(
+                    Level::Warn,
+                    event::PREFLIGHT_ISSUE,
+                    &issue.to_string(),
+                    &[("profile", profile.name.clone()), ("rule", issue.rule().to_string())],
+                );
+            }
+        }
+
+        // 将PCB下单必读文件复制到输出路径
+        self.add_pcb_must_read()?;
+
+        Ok(())
+    }
+
+    fn zip_file(&mut self, name: &str) -> Result<(), std::io::Error> {
+        let zip_name = name.to_owned() + ".zip";
+        self.publish(false, |staging| {
+            self.write_zip(std::fs::File::create(staging.join(&zip_name))?)?;
+            self.stage_report(staging)
+        })
+    }
+
+    fn finalize_output(&mut self, create_zip: bool, zip_name: &str) -> Result<(), std::io::Error> {
+        // 展开输出目录和ZIP名称中的模板变量
+        let zip_name = self.render_name(zip_name)?;
+        let zip_name = zip_name.as_str();
+        self.output_path = self.render_name(&self.output_path)?;
+
+        if self.output_path == STDIO_PATH {
+            // 输出到标准输出时只能是ZIP数据流；ZipWriter需要可定位的写入目标，先在内存中生成
+            let mut buffer = std::io::Cursor::new(Vec::new());
+            self.write_zip(&mut buffer)?;
+            let mut stdout = std::io::stdout().lock();
+            stdout.write_all(buffer.get_ref())?;
+            stdout.flush()?;
+            if self.report_name.is_some() {
+                LOG.warn("The report is not written when the output is standard output");
+            }
+        } else if create_zip {
+            // 如果需要ZIP，只创建ZIP文件
+            self.zip_file(zip_name)?;
+        } else {
+            // 如果不需要ZIP，复制所有处理过的文件到最终输出目录
+            self.publish(true, |staging| {
+                for file in &self.process_path {
+                    let file_name = file.file_name().unwrap();
+                    std::fs::copy(file, staging.join(file_name))?;
+                }
+                self.stage_report(staging)
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// 确定了目标层、等待转换的文件
+struct Planned {
+    file: PathBuf,
+    file_name: String,
+    key: &'static str,
+    target: MapTarget,
+
+    /// 匹配到的正则表达式或手动映射的通配符
+    pattern: String,
+
+    /// 是否来自手动映射
+    manual: bool,
+}
+
+/// 解析过的一个处理之后的文件
+struct ParsedLayer {
+    /// JLC文件名
+    name: String,
+    target: MapTarget,
+
+    /// 钻孔文件转换为孔和槽的图形
+    layer: Result<gerber::Layer, std::io::Error>,
+
+    /// 钻孔文件的孔和槽，其他层以及解析失败时为 `None`
+    drill: Option<excellon::Drill>,
+}
+
+impl ParsedLayer {
+    fn gerber(&self) -> Result<&gerber::Layer, std::io::Error> {
+        self.layer.as_ref().map_err(|e| self.error(e))
+    }
+
+    fn error(&self, e: &std::io::Error) -> std::io::Error {
+        std::io::Error::new(e.kind(), format!("Failed to parse '{}': {}", self.name, e))
+    }
+}
+
+/// 文件名匹配到的一条规则
+#[derive(Debug, Clone)]
+pub struct RuleMatch {
+    /// JLC层名称
+    pub key: &'static str,
+
+    /// 匹配到的正则表达式
+    pub pattern: String,
+
+    /// 对应的JLC层，规则没有对应的JLC层（例如 `Other`）时为 `None`
+    pub target: Option<MapTarget>,
+}
+
+/// 编译好的文件名规则：JLC层名称以及该层的正则表达式
+type CompiledRules = Vec<(&'static str, Vec<(String, Regex)>)>;
+
+/// 编译文件名规则，匹配多个文件时只编译一次
+fn compile_rules(rules: &[(&'static str, Vec<String>)]) -> Result<CompiledRules, std::io::Error> {
+    rules
+        .iter()
+        .map(|(key, patterns)| {
+            let compiled = patterns
+                .iter()
+                .map(|pattern| {
+                    Regex::new(pattern).map(|re| (pattern.clone(), re)).map_err(|e| {
+                        std::io::Error::new(
+                            std::io::ErrorKind::InvalidInput,
+                            format!("Invalid rule '{}' for {}: {}", pattern, key, e),
+                        )
+                    })
+                })
+                .collect::<Result<Vec<_>, std::io::Error>>()?;
+            Ok((*key, compiled))
+        })
+        .collect()
+}
+
+/// 按规则匹配文件名；同一层有多条正则时只取第一条匹配的
+fn match_rules(rules: &CompiledRules, file_name: &str) -> Result<Vec<RuleMatch>, std::io::Error> {
+    let mut matches = vec![];
+    for (key, patterns) in rules {
+        let Some((pattern, re)) = patterns.iter().find(|(_, re)| re.is_match(file_name)) else {
+            continue;
+        };
+        let target = match *key {
+            // 内层编号取自规则的捕获组，这里是源文件中的编号，转换时再统一重新编号
+            "InnerLayer" | "InnerPlane" => match stack::inner_number(re, file_name) {
+                Some(num) => Some(MapTarget::InnerLayer(num)),
+                None => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        format!("No inner layer number found in '{}'", file_name),
+                    ))
+                }
+            },
+            _ => JLC_STYLE.get(key).map(|_| MapTarget::Layer(key)),
+        };
+        matches.push(RuleMatch {
+            key,
+            pattern: pattern.clone(),
+            target,
+        });
+    }
+    Ok(matches)
+}
+
+/// 所有内置EDA风格的名称
+pub fn style_names() -> Vec<&'static str> {
+    ALL_STYLE.iter().map(|style| style.EDA_Name).collect()
+}
+
+/// 按名称查找内置EDA风格，不区分大小写
+fn find_style(name: &str) -> Option<&'static FileName::FileName> {
+    ALL_STYLE
+        .iter()
+        .find(|style| style.EDA_Name.eq_ignore_ascii_case(name))
+        .copied()
+}
+
+/// 记录对某个文件执行的一项处理
+fn transform_event(file_name: &str, transform: &str, description: &str) {
+    LOG.event(
+        Level::Trace,
+        event::TRANSFORM,
+        &format!("  {}: {}", file_name, description),
+        &[
+            ("file", file_name.to_string()),
+            ("transform", transform.to_string()),
+        ],
+    );
+}
+
+impl JLC {
+    /// 需要处理的输入文件
+    pub fn input_files(&self) -> Result<Vec<PathBuf>, std::io::Error> {
+        match &self.source_files {
+            Some(files) => Ok(files.clone()),
+            None => std::fs::read_dir(&self.path)?
+                .map(|res| res.map(|e| e.path()))
+                .collect(),
+        }
+    }
+
+    /// 选择输入文件使用的EDA风格
+    fn detect_style(&self, files: &[PathBuf]) -> Option<&'static FileName::FileName> {
+        match &self.eda {
             EDA::Auto => {
                 // 自动识别
                 ALL_STYLE
@@ -114,12 +735,24 @@ impl JlcTrait for JLC {
                         let re = Regex::new(rule.Board_Outline).unwrap();
                         files.iter().any(|file| re.is_match(file.to_str().unwrap()))
                     })
+                    .or_else(|| {
+                        // 导入或生成板框时文件中可能本来就没有边框层，改用顶层和底层铜层判断
+                        if self.generate_outline.is_none() && self.import_outline.is_none() {
+                            return None;
+                        }
+                        ALL_STYLE.iter().find(|rule| {
+                            [rule.Top_Layer, rule.Bottom_Layer].iter().any(|pattern| {
+                                let re = Regex::new(pattern).unwrap();
+                                files.iter().any(|file| re.is_match(file.to_str().unwrap()))
+                            })
+                        })
+                    })
                     .copied()
             }
 
             EDA::Custom(name) => {
                 // 自定义
-                ALL_STYLE.iter().find(|rule| rule.EDA_Name == name).copied()
+                find_style(name)
             }
 
             EDA::Kicad => {
@@ -127,221 +760,819 @@ impl JlcTrait for JLC {
                 Some(&KICAD_STYLE).map(|v| &**v)
             }
 
+            EDA::Protel => {
+                // 使用Protel/Altium风格
+                Some(&PROTEL_STYLE).map(|v| &**v)
+            }
+
             // EDA::AltiumDesigner => {
             //     // 使用Altium Designer风格
             //     Some(ALTUIM_DESIGNER_STYLE)
             // },
-            _ => {
-                // 直接使用指定的风格
-                None
-            }
-        };
+        }
+    }
 
-        if style.is_none() {
-            return Err(std::io::Error::new(
+    /// 指定名称的内置风格的规则，包括配置文件中追加的规则
+    pub fn style_rules(&self, name: &str) -> Result<Vec<(&'static str, Vec<String>)>, std::io::Error> {
+        let style = find_style(name).ok_or_else(|| {
+            std::io::Error::new(
                 std::io::ErrorKind::NotFound,
-                "No matching EDA style found",
+                format!("Unknown style '{}', available styles: {}", name, style_names().join(", ")),
+            )
+        })?;
+        Ok(self.rules(Some(style)))
+    }
+
+    /// 用指定风格的规则匹配文件名，用于调试规则
+    pub fn test_style(&self, name: &str, file_name: &str) -> Result<Vec<RuleMatch>, std::io::Error> {
+        match_rules(&compile_rules(&self.style_rules(name)?)?, file_name)
+    }
+
+    /// 输入文件使用的EDA风格名称，无法识别时为 `None`
+    pub fn style_name(&self) -> Result<Option<&'static str>, std::io::Error> {
+        Ok(self.detect_style(&self.input_files()?).map(|style| style.EDA_Name))
+    }
+
+    /// 文件名规则：风格自带的规则之后追加配置文件中的规则，没有风格时只使用配置文件中的规则
+    fn rules(&self, style: Option<&FileName::FileName>) -> Vec<(&'static str, Vec<String>)> {
+        let base: Vec<(&'static str, Vec<&'static str>)> = match style {
+            Some(style) => style.clone().into_iter().collect(),
+            None => KEYS.iter().map(|key| (*key, vec![])).collect(),
+        };
+        base.into_iter()
+            .map(|(key, values)| {
+                // 空字符串和 "null" 表示该风格没有这一层的规则
+                let mut values: Vec<String> = values
+                    .into_iter()
+                    .filter(|value| !value.is_empty() && *value != "null")
+                    .map(String::from)
+                    .collect();
+                if let Some(extra) = self.extra_rules.get(key) {
+                    values.extend(extra.iter().cloned());
+                }
+                (key, values)
+            })
+            .filter(|(_, values)| !values.is_empty())
+            .collect()
+    }
+
+    /// 渲染处理之后的每一层以及指定的合成视图，返回SVG文件名和内容
+    ///
+    /// 需要在 [`JlcTrait::copy_file`] 之后调用，每一层输出为 `<JLC文件名>.svg`，
+    /// 合成视图输出为 `top.svg` 和 `bottom.svg`。
+    pub fn render_svgs(
+        &self,
+        sides: &[render::Side],
+        colors: &render::Colors,
+    ) -> Result<Vec<(String, String)>, std::io::Error> {
+        let mut svgs = vec![];
+        let mut layers = BTreeMap::new();
+        for parsed in self.parsed_layers() {
+            let layer = parsed.gerber()?;
+            let key = parsed.target.key().unwrap();
+            svgs.push((format!("{}.svg", parsed.name), render::layer_svg(layer, colors.for_key(key))));
+            if let MapTarget::Layer(key) = parsed.target {
+                layers.insert(key.to_string(), layer.clone());
+            }
+        }
+
+        for side in sides {
+            svgs.push((
+                format!("{}.svg", side.name()),
+                render::board_svg(&layers, *side, colors),
             ));
         }
+        Ok(svgs)
+    }
 
-        for file in files {
-            if let Some(file_style) = style {
-                if file.is_file() {
-                    // 匹配文件名
-                    let file_name = file.file_name().unwrap().to_str().unwrap();
-                    // 遍历style的所有字段
-                    for (key, value) in file_style.clone() {
-                        if key == "null" {
-                            continue;
-                        }
+    /// 把 `copy_file` 处理后的文件渲染为每一面的PNG图像，返回文件名和内容
+    ///
+    /// `dpi` 为每英寸的像素数，例如 `top.png`、`bottom.png`。
+    pub fn render_pngs(
+        &self,
+        sides: &[render::Side],
+        colors: &render::Colors,
+        dpi: f64,
+    ) -> Result<Vec<(String, Vec<u8>)>, std::io::Error> {
+        let mut layers = BTreeMap::new();
+        for parsed in self.parsed_layers() {
+            if let MapTarget::Layer(key) = parsed.target {
+                layers.insert(key.to_string(), parsed.gerber()?.clone());
+            }
+        }
 
-                        let mut file_paths: Vec<PathBuf> = vec![];
-                        for value in value {
-                            if Regex::new(value).unwrap().is_match(file_name) {
-                                let file_path = match key {
-                                    "InnerLayer" => {
-                                        let mut num = 0;
-                                        let re = Regex::new(r"\d+").unwrap();
-                                        if let Some(caps) = re.captures(file_name) {
-                                            // 获取第一个捕获组（即第一个数字）
-                                            if let Some(matched) = caps.get(0) {
-                                                num = matched.as_str().parse::<i32>().unwrap();
-                                            }
-                                        } else {
-                                            return Err(std::io::Error::new(
-                                                std::io::ErrorKind::NotFound,
-                                                "No number found",
-                                            ));
-                                        }
-
-                                        let new_file_name = JLC_STYLE
-                                            .InnerLayer_Templete
-                                            .replace("{0}", num.to_string().as_str())
-                                            .replace("{1}", num.to_string().as_str());
-
-                                        let file_path = self.get_working_dir().join(new_file_name);
-                                        file_path
-                                    }
-
-                                    _ => {
-                                        let file_path = self
-                                            .get_working_dir()
-                                            .join(JLC_STYLE.get(key).unwrap());
-                                        file_path
-                                    }
-                                };
-                                file_paths.push(file_path);
-                            }
-
-                            for file_path in &file_paths {
-                                // 确保目录存在
-                                if let Some(parent) = file_path.parent() {
-                                    std::fs::create_dir_all(parent)?;
-                                }
-                                self.process_path.insert(file_path.clone());
-                                std::fs::copy(file.clone(), file_path.clone())?;
-                            }
-
-                            // 钻孔层只复制不修改
-                            const SKIP_KEYS: [&str; 3] =
-                                ["NPTH_Through", "PTH_Through", "PTH_Through_Via"];
-                            if SKIP_KEYS.contains(&key) {
-                                continue;
-                            }
-
-                            // 获取运行时间
-                            let now = chrono::Local::now();
-
-                            for file_path in &file_paths {
-                                // 在复制之后的文件的头部插入一些信息
-                                let mut temp =
-                                    std::fs::read_to_string(&file_path)?.replace("\r\n", "\n");
-                                temp = format!(
-                                    "G04 EasyEDA Pro v2.2.42.2, {}*\nG04 Gerber Generator version 0.3*\n{}",
-                                    now.format("%Y-%m-%d %H:%M:%S"),
-                                    temp
-                                );
-
-                                // 对KiCad风格的文件进行Dx*到G54Dx*的转换
-                                let is_kicad = matches!(self.eda, EDA::Kicad)
-                                    || file_style.EDA_Name == "KiCAD";
-                                if is_kicad {
-                                    temp = self.convert_kicad_aperture_format(temp);
-                                }
-
-                                // 对Gerber文件添加哈希孔径（跳过钻孔文件）
-                                if !SKIP_KEYS.contains(&key) {
-                                    temp = self.add_hash_aperture_to_gerber(temp)?;
-                                }
-
-                                std::fs::write(&file_path, temp)?;
-
-                                // 将处理之后的文件路径保存到process_path
-                                // self.process_path.insert(file_path.clone());
-                            }
-                        }
-                    }
+        let mut pngs = vec![];
+        for side in sides {
+            let svg = render::board_svg(&layers, *side, colors);
+            pngs.push((format!("{}.png", side.name()), render::svg_to_png(&svg, dpi)?));
+        }
+        Ok(pngs)
+    }
+
+    /// 生成转换结果的审阅报告（HTML）
+    ///
+    /// 报告包含每一层的渲染结果和显示开关、顶面和底面的合成视图、原始文件与JLC文件的对应关系、
+    /// 板子尺寸、钻孔统计以及转换过程中的警告，不依赖任何外部文件。
+    ///
+    /// 无法解析的层不影响其他内容，只在报告的警告中列出。
+    pub fn report_html(&self) -> Result<String, std::io::Error> {
+        let mut warnings = LOG.warnings_since(self.warning_start);
+        let mut loaded = vec![];
+        for parsed in self.parsed_layers() {
+            match &parsed.layer {
+                Ok(layer) => {
+                    warnings.extend(layer.warnings.iter().map(|warning| format!("{}: {}", parsed.name, warning)));
+                    loaded.push((parsed.name.clone(), parsed.target.clone(), layer));
                 }
+                Err(e) => warnings.push(format!("{}: cannot be rendered: {}", parsed.name, e)),
             }
         }
 
-        // 将PCB下单必读文件复制到输出路径
-        self.add_pcb_must_read()?;
+        // 从底面到顶面叠放，锡膏层默认隐藏
+        const STACK_ORDER: [&str; 14] = [
+            "Bottom_PasteMask", "Bottom_Silkscreen", "Bottom_Soldermask", "Bottom_Layer", "InnerLayer",
+            "Top_Layer", "Top_Soldermask", "Top_PasteMask", "Top_Silkscreen", "Board_Outline",
+            "PTH_Through_Via", "PTH_Through", "NPTH_Through", "Other",
+        ];
+        let mut stacked: Vec<&(String, MapTarget, &gerber::Layer)> = loaded.iter().collect();
+        stacked.sort_by_key(|(name, target, _)| {
+            let key = target.key().unwrap_or("Other");
+            (STACK_ORDER.iter().position(|k| *k == key).unwrap_or(STACK_ORDER.len()), name.clone())
+        });
+        let area = render::common_area(stacked.iter().map(|(_, _, layer)| *layer));
+        let layers = stacked
+            .iter()
+            .map(|(name, target, layer)| {
+                let key = target.key().unwrap();
+                report::ReportLayer {
+                    name: name.clone(),
+                    svg: render::layer_svg_in(layer, self.colors.for_key(key), area),
+                    visible: !key.ends_with("PasteMask"),
+                }
+            })
+            .collect();
+
+        let by_key: BTreeMap<String, gerber::Layer> = loaded
+            .iter()
+            .filter_map(|(_, target, layer)| match target {
+                MapTarget::Layer(key) => Some((key.to_string(), gerber::Layer::clone(layer))),
+                _ => None,
+            })
+            .collect();
+        let sides = [render::Side::Top, render::Side::Bottom]
+            .iter()
+            .map(|side| (side.name().to_string(), render::board_svg(&by_key, *side, &self.colors)))
+            .collect();
+        let summary = match self.summary.map(Ok).unwrap_or_else(|| self.board_summary()) {
+            Ok(summary) => summary,
+            Err(e) => {
+                warnings.push(format!("Cannot compute the board size: {}", e));
+                BoardSummary { outline: None, copper_layers: self.copper_layer_count() }
+            }
+        };
+
+        // 无法解析的钻孔文件已经在上面列出
+        let drills = self
+            .parsed_layers()
+            .iter()
+            .filter_map(|parsed| {
+                let drill = parsed.drill.as_ref()?;
+                Some(report::DrillStats { file: parsed.name.clone(), tools: drill.tool_counts(), slots: drill.slots.len() })
+            })
+            .collect();
+
+        let mut converted = self.converted.clone();
+        converted.sort();
+        let report = report::Report {
+            title: self.project_name(),
+            generated: format!(
+                "Generated by TransJLC {} on {}",
+                env!("CARGO_PKG_VERSION"),
+                self.zip_options
+                    .timestamp
+                    .unwrap_or_else(|| chrono::Local::now().naive_local())
+                    .format("%Y-%m-%d %H:%M:%S")
+            ),
+            size: summary.size(),
+            copper_layers: summary.copper_layers,
+            layers,
+            sides,
+            files: converted,
+            drills,
+            warnings,
+        };
+        Ok(report.to_html())
+    }
 
+    /// 需要时把审阅报告写到待发布的目录中
+    fn stage_report(&self, staging: &Path) -> Result<(), std::io::Error> {
+        let Some(name) = &self.report_name else {
+            return Ok(());
+        };
+        std::fs::write(staging.join(name), self.report_html()?)?;
+        LOG.info_no_color(&format!("Report written to {}", Path::new(&self.output_path).join(name).display()));
         Ok(())
     }
 
-    fn zip_file(&mut self, name: &str) -> Result<(), std::io::Error> {
-        // 确保输出目录存在
-        std::fs::create_dir_all(&self.output_path)?;
+    /// 处理后的各层，跳过下单必读等非Gerber文件
+    ///
+    /// 每一层只在第一次使用时解析一次，渲染、报告、板子尺寸、板框检查和工艺检查共用解析结果。
+    fn parsed_layers(&self) -> &[ParsedLayer] {
+        self.parsed.get_or_init(|| {
+            let mut files: Vec<&PathBuf> = self.process_path.iter().collect();
+            files.sort();
+
+            let mut layers = vec![];
+            for file in files {
+                let name = file.file_name().unwrap().to_string_lossy().to_string();
+                let Some(target) = MapTarget::from_file_name(&name) else {
+                    continue;
+                };
+                let content = std::fs::read_to_string(file);
+                let (layer, drill) = if render::is_drill(target.key().unwrap()) {
+                    match content.and_then(|content| excellon::parse(&content)) {
+                        Ok(drill) => (Ok(drill.to_layer()), Some(drill)),
+                        Err(e) => (Err(e), None),
+                    }
+                } else {
+                    (content.and_then(|content| gerber::parse(&content)), None)
+                };
+                for warning in layer.iter().flat_map(|layer| &layer.warnings) {
+                    LOG.trace(&format!("  {}: {}", name, warning));
+                }
+                layers.push(ParsedLayer { name, target, layer, drill });
+            }
+            layers
+        })
+    }
 
-        let zip_file = std::path::Path::new(&self.output_path).join(name.to_owned() + ".zip");
-        let mut zip = zip::ZipWriter::new(std::fs::File::create(zip_file)?);
+    /// 列出输入文件以及猜测的JLC层，供交互式映射向导使用
+    ///
+    /// 依次使用手动映射、EDA风格的规则、文件内容和文件名进行猜测，忽略的文件不会列出。
+    pub fn wizard_entries(&self) -> Result<Vec<wizard::WizardEntry>, std::io::Error> {
+        let mut files = self.input_files()?;
+        files.retain(|file| file.is_file());
+        files.sort();
+        let style = self.detect_style(&files);
+        let rules = compile_rules(&self.rules(style))?;
+        let ignore_res = self
+            .ignore_patterns
+            .iter()
+            .map(|pattern| config::glob_regex(pattern))
+            .collect::<Result<Vec<_>, std::io::Error>>()?;
+        let mut entries = vec![];
+        for file in files {
+            let file_name = file.file_name().unwrap().to_string_lossy().to_string();
+            if ignore_res.iter().any(|re| re.is_match(&file_name)) {
+                continue;
+            }
 
-        let options = SimpleFileOptions::default()
-            .compression_method(zip::CompressionMethod::Stored)
-            .unix_permissions(0o755);
+            let by_rule = match_rules(&rules, &file_name)
+                .unwrap_or_default()
+                .into_iter()
+                .find_map(|rule| rule.target);
+            let (guess, reason) = if let Some(mapping) =
+                self.file_mappings.iter().find(|m| m.matches(&file_name))
+            {
+                (Some(mapping.target.clone()), format!("mapping '{}'", mapping.pattern))
+            } else if let Some(target) = by_rule {
+                let style_name = style.map(|style| style.EDA_Name).unwrap_or("config");
+                (Some(target), format!("{} style", style_name))
+            } else if let Some(target) = guess::guess_from_content(&file) {
+                (Some(target), "file content".to_string())
+            } else {
+                (guess::guess_from_name(&file_name), "file name".to_string())
+            };
+            entries.push(wizard::WizardEntry { file, guess, reason });
+        }
+        Ok(entries)
+    }
 
-        for file in &self.process_path {
-            let file_name = file.file_name().unwrap().to_str().unwrap();
-            zip.start_file(file_name, options)?;
-            let content = std::fs::read(file)?;
-            zip.write(&content)?;
+    /// 将匹配到的文件复制到工作目录中的 `file_path`，并按层的类型进行处理
+    fn convert_file(
+        &mut self,
+        file: &Path,
+        key: &str,
+        file_path: &Path,
+        is_kicad: bool,
+        outline: Option<gerber::Bounds>,
+    ) -> Result<(), std::io::Error> {
+        let file_name = file.file_name().unwrap().to_string_lossy();
+        let file_name = file_name.as_ref();
+
+        // 确保目录存在
+        if let Some(parent) = file_path.parent() {
+            std::fs::create_dir_all(parent)?;
         }
+        self.process_path.insert(file_path.to_path_buf());
+        self.parsed.take();
+        std::fs::copy(file, file_path)?;
+        let target = file_path.file_name().unwrap().to_string_lossy();
+        self.converted.push((file_name.to_string(), target.to_string()));
+        LOG.event(
+            Level::Info,
+            event::FILE_COPIED,
+            &t!("copy_file_log", path = file_name, output_path = target),
+            &[
+                ("file", file_name.to_string()),
+                ("target", target.to_string()),
+            ],
+        );
 
-        zip.finish()?;
+        // 钻孔层只复制不修改
+        const SKIP_KEYS: [&str; 3] = ["NPTH_Through", "PTH_Through", "PTH_Through_Via"];
+        if SKIP_KEYS.contains(&key) {
+            transform_event(file_name, "drill_copy", "drill file copied without modification");
+            return Ok(());
+        }
+
+        // 获取运行时间，指定了固定时间戳时使用固定时间戳
+        let now = self
+            .zip_options
+            .timestamp
+            .unwrap_or_else(|| chrono::Local::now().naive_local());
+
+        // 在复制之后的文件的头部插入一些信息
+        let mut temp = std::fs::read_to_string(file_path)?.replace("\r\n", "\n");
+        if key == "InnerLayer" || key == "InnerPlane" {
+            temp = self.convert_plane_polarity(file_name, temp, outline);
+        }
+        temp = format!(
+            "G04 EasyEDA Pro v2.2.42.2, {}*\nG04 Gerber Generator version 0.3*\n{}",
+            now.format("%Y-%m-%d %H:%M:%S"),
+            temp
+        );
+        transform_event(file_name, "header", "inserted EasyEDA header");
+
+        if is_kicad {
+            temp = self.convert_kicad_aperture_format(temp);
+            transform_event(file_name, "kicad_aperture", "converted Dx* apertures to G54Dx*");
+        }
+
+        // 对Gerber文件添加哈希孔径
+        let (content, aperture) = self.insert_hash_aperture(temp)?;
+        temp = content;
+        match aperture {
+            Some(aperture) => LOG.event(
+                Level::Trace,
+                event::APERTURE_INSERTED,
+                &format!("  {}: added hash aperture {}", file_name, aperture),
+                &[
+                    ("file", file_name.to_string()),
+                    ("aperture", aperture),
+                ],
+            ),
+            None => transform_event(file_name, "hash_skipped", "hash aperture skipped"),
+        }
+
+        std::fs::write(file_path, temp)
+    }
+
+    /// 把计划中的多个板框文件合并为一个，合并后的文件以第一个文件的名称写到工作目录的 `merged` 文件夹中
+    fn merge_outlines(&self, plan: &mut Vec<Planned>) -> Result<(), std::io::Error> {
+        let outlines: Vec<usize> = (0..plan.len())
+            .filter(|index| plan[*index].target == MapTarget::Layer("Board_Outline"))
+            .collect();
+        if outlines.len() < 2 {
+            return Ok(());
+        }
+
+        let files = outlines
+            .iter()
+            .map(|index| Ok((plan[*index].file_name.clone(), std::fs::read_to_string(&plan[*index].file)?)))
+            .collect::<Result<Vec<_>, std::io::Error>>()?;
+        let merged = outline::merge(&files)?;
+        let dir = self.get_working_dir().join("merged");
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(&files[0].0);
+        std::fs::write(&path, merged)?;
+
+        let names: Vec<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+        for name in &names {
+            transform_event(name, "outline_merge", &format!("merged into the board outline with {} other file(s)", names.len() - 1));
+        }
+        let first = outlines[0];
+        plan[first] = Planned {
+            file: path,
+            file_name: names.join(" + "),
+            key: "Board_Outline",
+            target: MapTarget::Layer("Board_Outline"),
+            pattern: outlines.iter().map(|index| plan[*index].pattern.as_str()).collect::<Vec<_>>().join(", "),
+            manual: outlines.iter().all(|index| plan[*index].manual),
+        };
+        for index in outlines[1..].iter().rev() {
+            plan.remove(*index);
+        }
         Ok(())
     }
 
-    fn finalize_output(&mut self, create_zip: bool, zip_name: &str) -> Result<(), std::io::Error> {
-        if create_zip {
-            // 如果需要ZIP，只创建ZIP文件
-            self.zip_file(zip_name)?;
+    /// 把 DXF 或 SVG 板框转换为Gerber并加入计划，替换或者合并EDA导出的板框
+    fn plan_imported_outline(
+        &self,
+        plan: &mut Vec<Planned>,
+        import: &import::OutlineImport,
+    ) -> Result<(), std::io::Error> {
+        let drawing = import::Drawing::load(&import.path)?;
+        let name = import.path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        for warning in &drawing.warnings {
+            LOG.warn(&format!("{}: {}", name, warning));
+        }
+        if drawing.edges.is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("No outline found in '{}'", import.path.display()),
+            ));
+        }
+
+        let dir = self.get_working_dir().join("imported");
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(&name);
+        std::fs::write(&path, drawing.to_gerber())?;
+        transform_event(&name, "outline_import", &format!("imported {} outline edges", drawing.edges.len()));
+
+        if import.replace {
+            plan.retain(|item| {
+                let replaced = item.target == MapTarget::Layer("Board_Outline");
+                if replaced {
+                    LOG.trace(&format!("'{}' is replaced by the imported outline '{}'", item.file_name, name));
+                }
+                !replaced
+            });
+        }
+        plan.push(Planned {
+            file: path,
+            file_name: name,
+            key: "Board_Outline",
+            target: MapTarget::Layer("Board_Outline"),
+            pattern: "imported".to_string(),
+            manual: true,
+        });
+        self.merge_outlines(plan)
+    }
+
+    /// 计划中没有板框文件时，按铜层图形的范围生成板框并加入计划
+    fn plan_generated_outline(
+        &self,
+        plan: &mut Vec<Planned>,
+        generator: outline::OutlineGenerator,
+    ) -> Result<(), std::io::Error> {
+        if plan.iter().any(|item| item.target == MapTarget::Layer("Board_Outline")) {
+            LOG.trace("Board outline file found, no outline is generated");
+            return Ok(());
+        }
+
+        // 无法读取或解析的铜层不参与计算，逐个给出警告
+        let mut copper: Option<gerber::Bounds> = None;
+        for item in plan
+            .iter()
+            .filter(|item| matches!(item.key, "Top_Layer" | "Bottom_Layer" | "InnerLayer" | "InnerPlane"))
+        {
+            match std::fs::read_to_string(&item.file).and_then(|content| gerber::parse(&content)) {
+                Ok(layer) => match layer.bounds() {
+                    Some(bounds) => copper = Some(copper.map_or(bounds, |copper| copper.union(&bounds))),
+                    None => LOG.trace(&format!("'{}' is empty and not used for the generated outline", item.file_name)),
+                },
+                Err(e) => LOG.warn(&format!(
+                    "'{}' is not used for the generated outline: {}",
+                    item.file_name, e
+                )),
+            }
+        }
+        let bounds = generator.bounds(copper)?;
+        let dir = self.get_working_dir().join("generated");
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(GENERATED_OUTLINE_NAME);
+        std::fs::write(&path, generator.gerber(bounds))?;
+        LOG.warn(&format!(
+            "No board outline found, generated a {:.2} x {:.2} mm outline",
+            bounds.width(),
+            bounds.height()
+        ));
+
+        plan.push(Planned {
+            file: path,
+            file_name: GENERATED_OUTLINE_NAME.to_string(),
+            key: "Board_Outline",
+            target: MapTarget::Layer("Board_Outline"),
+            pattern: "generated".to_string(),
+            manual: true,
+        });
+        Ok(())
+    }
+
+    /// 检查板框的缺口、重复的线段和自相交，发现问题时给出警告
+    ///
+    /// 检查的是处理之后的板框层，警告中使用原始文件名 `file_name`。
+    fn check_outline(&self, file_name: &str) {
+        let Some(parsed) = self.parsed_layers().iter().find(|parsed| parsed.target == MapTarget::Layer("Board_Outline")) else {
+            return;
+        };
+        let layer = match &parsed.layer {
+            Ok(layer) => layer,
+            Err(e) => {
+                LOG.warn(&format!("Cannot check board outline '{}': {}", file_name, e));
+                return;
+            }
+        };
+        for issue in outline::validate(layer, self.outline_tolerance) {
+            LOG.event(
+                Level::Warn,
+                event::OUTLINE_ISSUE,
+                &format!("{}: {}", file_name, issue),
+                &[
+                    ("file", file_name.to_string()),
+                    ("issue", issue.kind().to_string()),
+                ],
+            );
+        }
+    }
+
+    /// 检查内层的极性，按设置把负片转换为正片
+    fn convert_plane_polarity(&self, file_name: &str, content: String, outline: Option<gerber::Bounds>) -> String {
+        let plane::Polarity::Negative { inferred } = plane::detect_polarity(&content) else {
+            return content;
+        };
+        if inferred {
+            LOG.warn(&format!(
+                "'{}' starts with clear polarity (%LPC*%), treated as a negative plane layer",
+                file_name
+            ));
+        }
+        if !self.positive_planes {
+            transform_event(file_name, "plane_negative", "negative plane layer kept as is");
+            return content;
+        }
+        match plane::to_positive(&content, outline) {
+            Some(positive) => {
+                let area = if outline.is_some() { "board outline" } else { "layer extents" };
+                transform_event(
+                    file_name,
+                    "plane_positive",
+                    &format!("negative plane layer converted to positive polarity (filled to {})", area),
+                );
+                positive
+            }
+            None => {
+                LOG.warn(&format!(
+                    "'{}' has no coordinate format, negative plane layer kept as is",
+                    file_name
+                ));
+                content
+            }
+        }
+    }
+
+    /// 检查路径是否为压缩包（按文件头魔数识别），如果是则解压到临时目录
+    ///
+    /// 路径为 `-` 时从标准输入读取压缩包。
+    pub fn extract_archive_if_needed(&mut self) -> Result<(), std::io::Error> {
+        // 标准输入不可定位，先落盘到临时文件再按普通文件处理，大小同样受解压限制
+        let stdin_spool = if self.path == STDIO_PATH {
+            let mut spool = tempfile::NamedTempFile::new()?;
+            let limit = self.extract_limits.max_total_size;
+            let mut stdin = std::io::Read::take(std::io::stdin().lock(), limit.saturating_add(1));
+            if std::io::copy(&mut stdin, spool.as_file_mut())? > limit {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Standard input exceeds the size limit of {} bytes", limit),
+                ));
+            }
+            Some(spool)
         } else {
-            // 如果不需要ZIP，复制所有处理过的文件到最终输出目录
-            std::fs::create_dir_all(&self.output_path)?;
+            None
+        };
+
+        let path = match &stdin_spool {
+            Some(spool) => spool.path().to_path_buf(),
+            None => PathBuf::from(&self.path),
+        };
+        if !path.is_file() {
+            return Ok(());
+        }
+
+        match ArchiveKind::detect(&path)? {
+            Some(kind) => {
+                LOG.info_no_color(&format!("Detected {:?} archive, extracting to temporary directory...", kind));
 
-            for file in &self.process_path {
-                let file_name = file.file_name().unwrap();
-                let dest_path = std::path::Path::new(&self.output_path).join(file_name);
-                std::fs::copy(file, dest_path)?;
+                // 创建临时目录并安全解压
+                let temp_dir = tempfile::TempDir::new()?;
+                archive::extract(&path, kind, temp_dir.path(), &self.extract_limits)?;
+
+                // 更新路径为临时目录
+                self.path = temp_dir.path().to_string_lossy().to_string();
+                self.temp_dir = Some(temp_dir);
+
+                LOG.info_no_color(&format!("Archive extracted to: {}", self.path));
+            }
+            None if stdin_spool.is_some() => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "Standard input is not a supported archive",
+                ));
             }
+            None => {}
         }
+
         Ok(())
     }
-}
 
-impl JLC {
-    /// 检查路径是否为ZIP文件，如果是则解压到临时目录
+    /// 旧名称，只支持ZIP时使用
+    #[deprecated(note = "use `extract_archive_if_needed`, which handles every supported archive format")]
     pub fn extract_zip_if_needed(&mut self) -> Result<(), std::io::Error> {
-        let path = std::path::Path::new(&self.path);
-
-        // 检查是否为文件且具有.zip扩展名
-        if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("zip") {
-            println!("Detected ZIP file, extracting to temporary directory...");
+        self.extract_archive_if_needed()
+    }
 
-            // 创建临时目录
-            let temp_dir = tempfile::TempDir::new()?;
-            let temp_path = temp_dir.path();
+    /// 查找输入中的独立板子
+    ///
+    /// 只有压缩包输入才会递归查找子文件夹；普通目录输入始终视为一块板子，
+    /// 避免把输出目录等无关文件夹当作输入。
+    pub fn board_sets(&self, default_name: &str) -> Result<Vec<BoardSet>, std::io::Error> {
+        if self.temp_dir.is_none() {
+            return Ok(vec![]);
+        }
+        board_set::find_board_sets(std::path::Path::new(&self.path), default_name)
+    }
 
-            // 打开ZIP文件
-            let file = std::fs::File::open(&self.path)?;
-            let mut archive = zip::ZipArchive::new(file)
-                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    /// 为单块板子创建一个独立的转换任务，沿用当前任务的设置
+    pub fn for_board(&self, board: &BoardSet, output_path: String) -> Result<JLC, std::io::Error> {
+        let mut jlc = JLC::new(
+            board.root.to_string_lossy().to_string(),
+            output_path,
+            self.eda.clone(),
+        );
+        jlc.ignore_hash = self.ignore_hash;
+        jlc.is_imported_pcb_doc = self.is_imported_pcb_doc;
+        jlc.extract_limits = self.extract_limits;
+        jlc.source_files = Some(board.files.clone());
+        jlc.source_name = board.name.clone();
+        jlc.input_path = self.input_path.clone();
+        jlc.zip_options = self.zip_options.clone();
+        jlc.overwrite = self.overwrite;
+        jlc.extra_rules = self.extra_rules.clone();
+        jlc.ignore_patterns = self.ignore_patterns.clone();
+        jlc.file_mappings = self.file_mappings.clone();
+        jlc.positive_planes = self.positive_planes;
+        jlc.report_name = self.report_name.clone();
+        jlc.notes_summary = self.notes_summary;
+        jlc.outline_tolerance = self.outline_tolerance;
+        jlc.outline_merge = self.outline_merge.clone();
+        jlc.style_outline_merge = self.style_outline_merge.clone();
+        jlc.import_outline = self.import_outline.clone();
+        jlc.generate_outline = self.generate_outline;
+        jlc.preflight = self.preflight.clone();
+        jlc.colors = self.colors.clone();
+        Ok(jlc)
+    }
 
-            // 解压所有文件
-            for i in 0..archive.len() {
-                let mut file = archive
-                    .by_index(i)
-                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    /// 工程名称：输入文件夹或压缩包去掉扩展名后的名称
+    pub fn project_name(&self) -> String {
+        archive::strip_archive_extension(&self.source_name).to_string()
+    }
 
-                let outpath = temp_path.join(file.name());
+    /// 把处理之后的板框和钻孔文件导出为 DXF，金属化孔和非金属化孔在不同的图层
+    ///
+    /// 无法解析的文件会给出警告并跳过，其余的内容照常导出。
+    pub fn board_dxf(&self) -> String {
+        let mut outline = None;
+        let (mut plated, mut non_plated) = (vec![], vec![]);
+        for parsed in self.parsed_layers() {
+            let key = parsed.target.key();
+            if !matches!(key, Some("Board_Outline" | "PTH_Through" | "PTH_Through_Via" | "NPTH_Through")) {
+                continue;
+            }
+            if let Err(e) = &parsed.layer {
+                LOG.warn(&format!("'{}' is not exported to the DXF: {}", parsed.name, e));
+                continue;
+            }
+            match (key, &parsed.layer, &parsed.drill) {
+                (Some("Board_Outline"), Ok(layer), _) => outline = Some(layer),
+                (Some("NPTH_Through"), _, Some(drill)) => non_plated.push(drill),
+                (_, _, Some(drill)) => plated.push(drill),
+                _ => {}
+            }
+        }
+        if outline.is_none() {
+            LOG.warn("No board outline found, the DXF only contains the holes");
+        }
+        dxf::board_dxf(outline, &plated, &non_plated)
+    }
 
-                if file.name().ends_with('/') {
-                    // 创建目录
-                    std::fs::create_dir_all(&outpath)?;
-                } else {
-                    // 创建父目录（如果需要）
-                    if let Some(p) = outpath.parent() {
-                        if !p.exists() {
-                            std::fs::create_dir_all(p)?;
-                        }
+    /// 按工艺能力检查处理之后的铜层、丝印层、钻孔和板子尺寸
+    ///
+    /// 无法解析的层作为 [`preflight::PreflightIssue::Unchecked`] 列出，其他层照常检查。
+    pub fn preflight_issues(&self, profile: &preflight::Profile) -> Vec<preflight::PreflightIssue> {
+        let mut issues = vec![];
+        let (mut outer, mut plated) = (vec![], vec![]);
+        for parsed in self.parsed_layers() {
+            let Some(key) = parsed.target.key() else {
+                continue;
+            };
+            let name = parsed.name.as_str();
+            if !matches!(
+                key,
+                "Top_Layer" | "Bottom_Layer" | "InnerLayer" | "InnerPlane" | "Top_Silkscreen" | "Bottom_Silkscreen"
+                    | "PTH_Through" | "PTH_Through_Via" | "NPTH_Through"
+            ) {
+                continue;
+            }
+            let layer = match &parsed.layer {
+                Ok(layer) => layer,
+                Err(e) => {
+                    issues.push(preflight::PreflightIssue::Unchecked { file: name.to_string(), error: e.to_string() });
+                    continue;
+                }
+            };
+            match (key, &parsed.drill) {
+                ("Top_Layer" | "Bottom_Layer", _) => {
+                    issues.extend(preflight::check_copper(name, layer, profile.min_trace, profile.min_space));
+                    outer.push(layer);
+                }
+                ("InnerLayer" | "InnerPlane", _) => {
+                    issues.extend(preflight::check_copper(
+                        name,
+                        layer,
+                        profile.min_inner_trace,
+                        profile.min_inner_space,
+                    ));
+                }
+                ("Top_Silkscreen" | "Bottom_Silkscreen", _) => {
+                    issues.extend(preflight::check_silkscreen(name, layer, profile.min_silkscreen_width));
+                }
+                (_, Some(drill)) => {
+                    issues.extend(preflight::check_drill(name, drill, profile.min_drill));
+                    if key != "NPTH_Through" {
+                        plated.push((name, drill));
                     }
-
-                    // 解压文件
-                    let mut outfile = std::fs::File::create(&outpath)?;
-                    std::io::copy(&mut file, &mut outfile)?;
                 }
+                _ => {}
             }
+        }
+        for (name, drill) in &plated {
+            issues.extend(preflight::check_annular_ring(name, drill, &outer, profile.min_annular_ring));
+        }
+        if let Some((width, height)) = self.summary.as_ref().and_then(BoardSummary::size) {
+            issues.extend(preflight::check_board_size(width, height, profile));
+        }
+        issues
+    }
 
-            // 更新路径为临时目录
-            self.path = temp_path.to_string_lossy().to_string();
-            self.temp_dir = Some(temp_dir);
+    /// 根据处理之后的板框层计算板子尺寸（含圆弧），并统计铜层数量
+    pub fn board_summary(&self) -> Result<BoardSummary, std::io::Error> {
+        let outline = match self
+            .parsed_layers()
+            .iter()
+            .find(|parsed| parsed.target == MapTarget::Layer("Board_Outline"))
+        {
+            Some(parsed) => parsed.gerber()?.path_bounds(),
+            None => None,
+        };
+        Ok(BoardSummary { outline, copper_layers: self.copper_layer_count() })
+    }
+
+    /// 根据处理之后的文件统计铜层数量
+    pub fn copper_layer_count(&self) -> usize {
+        self.process_path
+            .iter()
+            .filter_map(|file| file.file_name().and_then(|name| name.to_str()))
+            .filter(|name| {
+                *name == JLC_STYLE.Top_Layer
+                    || *name == JLC_STYLE.Bottom_Layer
+                    || JLC_INNER_LAYER_RE.is_match(name)
+            })
+            .count()
+    }
 
-            println!("ZIP file extracted to: {}", self.path);
+    /// 收集输出名称模板所需的变量
+    pub fn template_vars(&self) -> TemplateVars {
+        // 压缩包输入时在压缩包所在目录执行 git describe
+        let input = std::path::Path::new(&self.input_path);
+        let repo_dir = if input.is_dir() {
+            input.to_path_buf()
+        } else {
+            input
+                .parent()
+                .filter(|p| !p.as_os_str().is_empty())
+                .map(|p| p.to_path_buf())
+                .unwrap_or_else(|| PathBuf::from("."))
+        };
+
+        TemplateVars {
+            project: self.project_name(),
+            rev: template::git_describe(&repo_dir).unwrap_or_else(|| "norev".to_string()),
+            layers: self.copper_layer_count(),
+            time: self
+                .zip_options
+                .timestamp
+                .unwrap_or_else(|| chrono::Local::now().naive_local()),
         }
+    }
 
-        Ok(())
+    /// 展开名称模板，不含模板变量时原样返回
+    pub fn render_name(&self, template: &str) -> Result<String, std::io::Error> {
+        if !is_template(template) {
+            return Ok(template.to_string());
+        }
+        template::render(template, &self.template_vars())
     }
 
     /// 为KiCad风格文件转换Dx*格式为G54Dx*格式
@@ -367,9 +1598,89 @@ impl JLC {
         result_lines.join("\n")
     }
 
-    /// 获取工作目录（临时目录优先，否则使用输出目录）
+    /// 将处理之后的文件写为ZIP数据
+    ///
+    /// 条目按文件名排序，配合固定时间戳可以生成可复现的压缩包。
+    pub fn write_zip<W: Write + std::io::Seek>(&self, writer: W) -> Result<(), std::io::Error> {
+        let mut zip = zip::ZipWriter::new(writer);
+
+        let options = SimpleFileOptions::default()
+            .compression_method(self.zip_options.compression.into())
+            .last_modified_time(self.zip_options.zip_datetime()?)
+            // 层文件不是可执行文件，只有文件夹需要执行权限
+            .unix_permissions(0o644);
+
+        if let Some(folder) = &self.zip_options.folder {
+            zip.add_directory(folder.trim_matches('/'), options.unix_permissions(0o755))?;
+        }
+
+        let mut files: Vec<&PathBuf> = self.process_path.iter().collect();
+        files.sort_by_key(|file| file.file_name().map(|name| name.to_owned()));
+
+        for file in files {
+            let file_name = file.file_name().unwrap().to_str().unwrap();
+            zip.start_file(self.zip_options.entry_name(file_name), options)?;
+            let content = std::fs::read(file)?;
+            zip.write_all(&content)?;
+        }
+
+        zip.set_comment(format!(
+            "TransJLC v{}, source: {}",
+            env!("CARGO_PKG_VERSION"),
+            self.source_name
+        ));
+        zip.finish()?;
+        Ok(())
+    }
+
+    /// 把导出的渲染图、DXF等文件按覆盖策略发布到输出目录，返回输出目录
+    pub fn publish_files(&mut self, files: &[(String, Vec<u8>)]) -> Result<PathBuf, std::io::Error> {
+        self.output_path = self.render_name(&self.output_path)?;
+        self.publish(false, |staging| {
+            for (name, data) in files {
+                std::fs::write(staging.join(name), data)?;
+            }
+            Ok(())
+        })?;
+        Ok(PathBuf::from(&self.output_path))
+    }
+
+    /// 按覆盖策略将暂存的输出发布到输出目录
+    fn publish(
+        &self,
+        replace_layers: bool,
+        stage: impl FnOnce(&std::path::Path) -> Result<(), std::io::Error>,
+    ) -> Result<(), std::io::Error> {
+        let output_dir = std::path::Path::new(&self.output_path);
+
+        if self.overwrite == OverwritePolicy::Clean {
+            // 清空输出目录前确认它不包含输入文件或当前目录
+            let output = std::fs::canonicalize(output_dir).ok();
+            let input = std::fs::canonicalize(&self.input_path).ok();
+            let cwd = std::env::current_dir().ok();
+            if let Some(output) = output {
+                let contains_input = input.is_some_and(|input| input.starts_with(&output));
+                let contains_cwd = cwd.is_some_and(|cwd| cwd.starts_with(&output));
+                if contains_input || contains_cwd {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        format!(
+                            "Refusing to clean output directory '{}' because it contains the input or the current directory",
+                            self.output_path
+                        ),
+                    ));
+                }
+            }
+        }
+
+        output::publish_staged(output_dir, self.overwrite, replace_layers, stage)
+    }
+
+    /// 获取工作目录（临时工作目录优先，其次是解压目录，否则使用输出目录）
     fn get_working_dir(&self) -> PathBuf {
-        if let Some(ref temp_dir) = self.temp_dir {
+        if let Some(ref work_dir) = self.work_dir {
+            work_dir.path().to_path_buf()
+        } else if let Some(ref temp_dir) = self.temp_dir {
             temp_dir.path().to_path_buf()
         } else {
             PathBuf::from(&self.output_path)
@@ -378,12 +1689,17 @@ impl JLC {
 
     /// 向Gerber文件添加哈希孔径，用作文件指纹
     pub fn add_hash_aperture_to_gerber(&self, content: String) -> Result<String, std::io::Error> {
+        self.insert_hash_aperture(content).map(|(content, _)| content)
+    }
+
+    /// 添加哈希孔径，同时返回插入的孔径定义（跳过时为 `None`）
+    fn insert_hash_aperture(&self, content: String) -> Result<(String, Option<String>), std::io::Error> {
         use md5::{Digest, Md5};
-        use rand::Rng;
+        use rand::{Rng, SeedableRng};
 
         // 如果设置了忽略哈希或文件过大（>30MB），直接返回原内容
         if self.ignore_hash || content.len() > 30_000_000 {
-            return Ok(content);
+            return Ok((content, None));
         }
 
         let lines: Vec<&str> = content.split('\n').collect();
@@ -416,8 +1732,15 @@ impl JLC {
             }
         }
 
-        // 选择插入位置
-        let mut rng = rand::thread_rng();
+        // 选择插入位置；指定了固定时间戳时随机数由内容和时间戳决定，保证输出可复现
+        let mut rng = match self.zip_options.timestamp {
+            Some(time) => {
+                let digest = Md5::digest(content.as_bytes());
+                let seed = u64::from_le_bytes(digest[..8].try_into().unwrap()) ^ time.and_utc().timestamp() as u64;
+                rand::rngs::StdRng::seed_from_u64(seed)
+            }
+            None => rand::rngs::StdRng::from_entropy(),
+        };
         let selection_index = std::cmp::min(
             5 + rng.gen_range(0..5),
             if aperture_numbers.len() > 1 {
@@ -544,9 +1867,135 @@ impl JLC {
             result_lines.join("\n")
         };
 
-        Ok(result)
+        Ok((result, Some(hash_aperture)))
     }
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tests_style_rules() {
+        let mut jlc = JLC::new(".".to_string(), "out".to_string(), EDA::Auto);
+        let matches = jlc.test_style("kicad", "board-F_Cu.gbr").unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].target, Some(MapTarget::Layer("Top_Layer")));
+
+        // Protel 风格中空的规则不匹配任何文件
+        let matches = jlc.test_style("Protel", "board.GTL").unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].key, "Top_Layer");
+
+        jlc.extra_rules
+            .insert("Board_Outline".to_string(), vec![r"(?i)^outline\.gbr$".to_string()]);
+        let matches = jlc.test_style("KiCAD", "Outline.gbr").unwrap();
+        assert_eq!(matches[0].target, Some(MapTarget::Layer("Board_Outline")));
+        assert!(jlc.test_style("unknown", "a.gbr").is_err());
+    }
+
+    #[test]
+    fn fixed_timestamp_gives_identical_zip() {
+        let input = tempfile::TempDir::new().unwrap();
+        let layer = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10C,0.100000*%\n%ADD11C,0.200000*%\nD10*\nX0Y0D02*\nX1000000Y0D01*\nM02*\n";
+        std::fs::write(input.path().join("board-F_Cu.gtl"), layer).unwrap();
+        std::fs::write(input.path().join("board-Edge_Cuts.gm1"), layer).unwrap();
+
+        let build = || {
+            let mut jlc = JLC::new(input.path().to_string_lossy().to_string(), "out".to_string(), EDA::Kicad);
+            jlc.zip_options.timestamp = Some(parse_timestamp("1700000000").unwrap());
+            jlc.copy_file().unwrap();
+            let mut zip = std::io::Cursor::new(Vec::new());
+            jlc.write_zip(&mut zip).unwrap();
+            zip.into_inner()
+        };
+        let first = build();
+        assert_eq!(first, build());
+    }
+
+    #[test]
+    fn generated_outline_skips_unparsable_copper() {
+        let input = tempfile::TempDir::new().unwrap();
+        let layer = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10C,0.100000*%\nD10*\nX0Y0D02*\nX1000000Y0D01*\nM02*\n";
+        std::fs::write(input.path().join("board-F_Cu.gtl"), layer).unwrap();
+        std::fs::write(input.path().join("board-B_Cu.gbl"), "%FSLAX46Y46*%\n%ADD10MISSING*%\nM02*\n").unwrap();
+
+        let mut jlc = JLC::new(input.path().to_string_lossy().to_string(), "out".to_string(), EDA::Kicad);
+        jlc.generate_outline = Some(outline::OutlineGenerator { shape: outline::OutlineShape::Copper, margin: 1.0, radius: 0.0 });
+        let start = LOG.warning_count();
+        jlc.copy_file().unwrap();
+        assert!(LOG
+            .warnings_since(start)
+            .iter()
+            .any(|warning| warning.starts_with("'board-B_Cu.gbl' is not used for the generated outline")));
+        let (width, height) = jlc.summary.unwrap().size().unwrap();
+        assert!((width - 3.1).abs() < 1e-6 && (height - 2.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn converted_layers_are_parsed_once() {
+        let input = tempfile::TempDir::new().unwrap();
+        let layer = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10C,0.100000*%\nD10*\nX0Y0D02*\nX1000000Y0D01*\nM02*\n";
+        std::fs::write(input.path().join("board-F_Cu.gtl"), layer).unwrap();
+        std::fs::write(input.path().join("board-Edge_Cuts.gm1"), layer).unwrap();
+
+        let mut jlc = JLC::new(input.path().to_string_lossy().to_string(), "out".to_string(), EDA::Kicad);
+        jlc.copy_file().unwrap();
+        // 转换时计算板子尺寸已经解析了各层，之后不再读取文件
+        for file in &jlc.process_path {
+            std::fs::remove_file(file).unwrap();
+        }
+        assert_eq!(jlc.render_svgs(&[render::Side::Top], &jlc.colors).unwrap().len(), 3);
+        assert!(jlc.board_summary().unwrap().size().is_some());
+        assert!(jlc.report_html().is_ok());
+    }
+
+    #[test]
+    fn preflight_skips_layers_that_cannot_be_parsed() {
+        let input = tempfile::TempDir::new().unwrap();
+        let layer = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10C,0.100000*%\nD10*\nX0Y0D02*\nX1000000Y0D01*\nM02*\n";
+        std::fs::write(input.path().join("board-F_Cu.gtl"), layer).unwrap();
+        std::fs::write(input.path().join("board-F_Silkscreen.gto"), "%FSLAX46Y46*%\n%ADD10MISSING*%\nM02*\n").unwrap();
+
+        let mut jlc = JLC::new(input.path().to_string_lossy().to_string(), "out".to_string(), EDA::Kicad);
+        jlc.copy_file().unwrap();
+        let rules: Vec<&str> = jlc
+            .preflight_issues(&preflight::Profile::builtin("jlc-standard").unwrap())
+            .iter()
+            .map(|issue| issue.rule())
+            .collect();
+        assert_eq!(rules, ["min_trace", "unchecked"]);
+    }
+
+    #[test]
+    fn dxf_skips_files_that_cannot_be_parsed() {
+        let input = tempfile::TempDir::new().unwrap();
+        std::fs::write(input.path().join("board-Edge_Cuts.gm1"), "%FSLAX46Y46*%\n%ADD10MISSING*%\nM02*\n").unwrap();
+        std::fs::write(input.path().join("board-PTH.drl"), "M48\nMETRIC\nT1C0.8\n%\nT1\nX5.0Y5.0\nM30\n").unwrap();
+
+        let mut jlc = JLC::new(input.path().to_string_lossy().to_string(), "out".to_string(), EDA::Kicad);
+        jlc.copy_file().unwrap();
+        let start = LOG.warning_count();
+        let dxf = jlc.board_dxf();
+        assert!(dxf.contains("CIRCLE\n8\nPLATED"));
+        assert!(!dxf.contains("OUTLINE\n10"));
+        assert!(LOG
+            .warnings_since(start)
+            .iter()
+            .any(|warning| warning.starts_with("'Gerber_BoardOutlineLayer.GKO' is not exported to the DXF")));
+    }
+
+    #[test]
+    fn report_lists_layers_that_cannot_be_parsed() {
+        let input = tempfile::TempDir::new().unwrap();
+        let layer = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10C,0.100000*%\nD10*\nX0Y0D02*\nX1000000Y0D01*\nM02*\n";
+        std::fs::write(input.path().join("board-F_Cu.gtl"), layer).unwrap();
+        std::fs::write(input.path().join("board-F_Silkscreen.gto"), "%FSLAX46Y46*%\n%ADD10MISSING*%\nM02*\n").unwrap();
+
+        let mut jlc = JLC::new(input.path().to_string_lossy().to_string(), "out".to_string(), EDA::Kicad);
+        jlc.copy_file().unwrap();
+        let html = jlc.report_html().unwrap();
+        assert!(html.contains("Gerber_TopSilkscreenLayer.GTO: cannot be rendered"));
+        assert!(html.contains("Gerber_TopLayer.GTL"));
+    }
+}
@@ -1,9 +1,15 @@
 // SPDX-FileCopyrightText: 2025 HalfSweet
 // SPDX-License-Identifier: Apache-2.0
 
+use std::fmt::Display;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::Mutex;
+
 use colored::{Color, Colorize};
 use lazy_static::lazy_static;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Level {
     Trace,
     Info,
@@ -11,21 +17,175 @@ pub enum Level {
     Error,
 }
 
+impl Level {
+    fn from_u8(value: u8) -> Level {
+        match value {
+            0 => Level::Trace,
+            1 => Level::Info,
+            2 => Level::Warn,
+            _ => Level::Error,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Level::Trace => "trace",
+            Level::Info => "info",
+            Level::Warn => "warn",
+            Level::Error => "error",
+        }
+    }
+
+    /// 当前日志级别下是否输出该级别的消息
+    fn enabled(&self, current: Level) -> bool {
+        (*self as u8) >= (current as u8)
+    }
+}
+
+/// 日志输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// 带颜色的文本，供人阅读
+    Text,
+
+    /// 每个事件一行JSON对象，供自动化工具解析
+    Json,
+}
+
+/// 事件代码，JSON格式输出中的 `event` 字段
+pub mod event {
+    pub const INFO: &str = "info";
+    pub const TRACE: &str = "trace";
+    pub const WARNING: &str = "warning";
+    pub const ERROR: &str = "error";
+    pub const FILE_MATCHED: &str = "file_matched";
+    pub const FILE_COPIED: &str = "file_copied";
+    pub const FILE_SKIPPED: &str = "file_skipped";
+    pub const TRANSFORM: &str = "transform";
+    pub const APERTURE_INSERTED: &str = "aperture_inserted";
+    pub const BOARD_SUMMARY: &str = "board_summary";
+    pub const OUTLINE_ISSUE: &str = "outline_issue";
+    pub const PREFLIGHT_ISSUE: &str = "preflight_issue";
+}
+
 pub struct Log  {
-    level: Level,
+    level: AtomicU8,
+
+    /// 是否输出JSON格式
+    json: AtomicBool,
+
+    /// 是否将所有输出写到标准错误（标准输出被用作数据流时）
+    to_stderr: AtomicBool,
+
+    /// 日志文件，记录所有级别的消息（不含颜色）
+    file: Mutex<Option<std::fs::File>>,
+
+    /// 输出过的所有警告，用于生成报告
+    warnings: Mutex<Vec<String>>,
 }
 
 impl Log {
     pub fn new(level: Level) -> Log {
         Log {
-            level,
+            level: AtomicU8::new(level as u8),
+            json: AtomicBool::new(false),
+            to_stderr: AtomicBool::new(false),
+            file: Mutex::new(None),
+            warnings: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn level(&self) -> Level {
+        Level::from_u8(self.level.load(Ordering::Relaxed))
+    }
+
+    fn format(&self) -> Format {
+        if self.json.load(Ordering::Relaxed) {
+            Format::Json
+        } else {
+            Format::Text
+        }
+    }
+
+    fn print(&self, line: impl Display) {
+        if self.to_stderr.load(Ordering::Relaxed) {
+            eprintln!("{}", line);
+        } else {
+            println!("{}", line);
         }
     }
 
+    /// 写入日志文件，写入失败时忽略，不影响转换本身
+    fn record(&self, line: &str) {
+        if let Ok(mut file) = self.file.lock() {
+            if let Some(file) = file.as_mut() {
+                let _ = writeln!(file, "{}", line);
+            }
+        }
+    }
+
+    /// 将事件序列化为一行JSON
+    fn json_line(level: Level, code: &str, msg: &str, fields: &[(&str, String)]) -> String {
+        let mut object = serde_json::Map::new();
+        object.insert("level".into(), level.name().into());
+        object.insert("timestamp".into(), chrono::Local::now().to_rfc3339().into());
+        object.insert("event".into(), code.into());
+        object.insert("message".into(), msg.into());
+        if !fields.is_empty() {
+            let fields: serde_json::Map<String, serde_json::Value> = fields
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone().into()))
+                .collect();
+            object.insert("fields".into(), fields.into());
+        }
+        serde_json::Value::Object(object).to_string()
+    }
+
+    /// 记录一个带事件代码和附加字段的事件
+    ///
+    /// 文本格式下与对应级别的普通消息输出一致，JSON格式下输出一行JSON对象。
+    pub fn event(&self, level: Level, code: &str, msg: &str, fields: &[(&str, String)]) {
+        if level == Level::Warn {
+            if let Ok(mut warnings) = self.warnings.lock() {
+                warnings.push(msg.to_string());
+            }
+        }
+        match self.format() {
+            Format::Json => {
+                let line = Self::json_line(level, code, msg, fields);
+                self.record(&line);
+                if level == Level::Error {
+                    eprintln!("{}", line);
+                } else if level.enabled(self.level()) {
+                    self.print(line);
+                }
+            }
+            Format::Text => match level {
+                Level::Trace => self.trace_text(msg),
+                Level::Info => self.info_text(msg, Color::White),
+                Level::Warn => self.warn_text(msg),
+                Level::Error => self.error_text(msg),
+            },
+        }
+    }
+
+    fn text_record(&self, tag: &str, msg: &str) {
+        let time = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
+        self.record(&format!("{} [{}] {}", tag, time, msg));
+    }
+
     pub fn info(&self, msg: &str, color: Color) {
-        match self.level {
+        match self.format() {
+            Format::Json => self.event(Level::Info, event::INFO, msg, &[]),
+            Format::Text => self.info_text(msg, color),
+        }
+    }
+
+    fn info_text(&self, msg: &str, color: Color) {
+        self.text_record("INFO:", msg);
+        match self.level() {
             Level::Info | Level::Trace => {
-                println!("{}", msg.color(color));
+                self.print(msg.color(color));
             },
             _ => {},
         }
@@ -36,56 +196,116 @@ impl Log {
     }
 
     pub fn error(&self, msg: &str) {
-        match self.level {
-            _ => {
-                let level = "ERROR: ".color(Color::Red);
-                let time = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string().color(Color::White);
-                eprintln!("{} [{}] {}",
-                          level,
-                          time,
-                          msg
-                );
-            },
-        }
+        self.event(Level::Error, event::ERROR, msg, &[]);
+    }
+
+    fn error_text(&self, msg: &str) {
+        self.text_record("ERROR:", msg);
+        let level = "ERROR: ".color(Color::Red);
+        let time = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string().color(Color::White);
+        eprintln!("{} [{}] {}",
+                  level,
+                  time,
+                  msg
+        );
     }
 
     pub fn warn(&self, msg: &str) {
-        match self.level {
+        self.event(Level::Warn, event::WARNING, msg, &[]);
+    }
+
+    fn warn_text(&self, msg: &str) {
+        self.text_record("Warn:", msg);
+        match self.level() {
             Level::Warn | Level::Info | Level::Trace => {
                 let level = "Warn: ".color(Color::Yellow);
                 let time = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string().color(Color::White);
-                println!("{} [{}] {}",
-                         level,
-                         time,
-                         msg
-                );
+                self.print(format!("{} [{}] {}",
+                                   level,
+                                   time,
+                                   msg
+                ));
             },
             _ => {},
         }
     }
 
     pub fn trace(&self, msg: &str) {
-        match self.level {
-            Level::Trace => {
-                let level = "Trace: ".color(Color::Cyan);
-                let time = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string().color(Color::White);
-                println!("{} [{}] {}",
-                         level,
-                         time,
-                         msg
-                );
-            },
-            _ => {},
+        self.event(Level::Trace, event::TRACE, msg, &[]);
+    }
+
+    fn trace_text(&self, msg: &str) {
+        self.text_record("Trace:", msg);
+        if let Level::Trace = self.level() {
+            let level = "Trace: ".color(Color::Cyan);
+            let time = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string().color(Color::White);
+            self.print(format!("{} [{}] {}",
+                               level,
+                               time,
+                               msg
+            ));
         }
     }
 
-    pub fn set_level(&mut self, level: Level) {
-        self.level = level;
+    pub fn set_level(&self, level: Level) {
+        self.level.store(level as u8, Ordering::Relaxed);
+    }
+
+    pub fn set_format(&self, format: Format) {
+        self.json.store(format == Format::Json, Ordering::Relaxed);
+    }
+
+    /// 将普通输出改为写到标准错误
+    pub fn set_stderr(&self, enabled: bool) {
+        self.to_stderr.store(enabled, Ordering::Relaxed);
+    }
+
+    /// 到目前为止输出过的警告数量
+    pub fn warning_count(&self) -> usize {
+        self.warnings.lock().map(|warnings| warnings.len()).unwrap_or(0)
+    }
+
+    /// 从第 `start` 条开始输出过的警告
+    pub fn warnings_since(&self, start: usize) -> Vec<String> {
+        self.warnings
+            .lock()
+            .map(|warnings| warnings.iter().skip(start).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// 额外将所有级别的日志追加写入到文件
+    pub fn set_log_file(&self, path: &str) -> Result<(), std::io::Error> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        if let Ok(mut current) = self.file.lock() {
+            *current = Some(file);
+        }
+        Ok(())
     }
 }
 
 lazy_static! {
-    pub static ref LOG: Log = Log {
-        level: Level::Info,
-    };
-}
\ No newline at end of file
+    pub static ref LOG: Log = Log::new(Level::Info);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_line_contains_event_fields() {
+        let line = Log::json_line(
+            Level::Info,
+            event::FILE_MATCHED,
+            "matched",
+            &[("file", "board-F_Cu.gbr".to_string())],
+        );
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["level"], "info");
+        assert_eq!(value["event"], "file_matched");
+        assert_eq!(value["fields"]["file"], "board-F_Cu.gbr");
+        assert!(value["timestamp"].is_string());
+    }
+}
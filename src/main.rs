@@ -2,10 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 #![allow(non_snake_case)]
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
 use rust_i18n::t;
 use whoami::Language;
 
-use TransJLC::{JLC, JlcTrait};
+use TransJLC::log::{Format, Level, LOG};
+use TransJLC::{JLC, JlcTrait, STDIO_PATH};
 
 rust_i18n::i18n!("i18n");
 
@@ -18,31 +22,309 @@ fn set_language(language: &str) -> Result<(), Box<dyn std::error::Error>> {
     let i18n_list = rust_i18n::available_locales!();
     // 不支持的语言默认使用英语
     if !i18n_list.contains(&language) {
-        println!("{}", "Language not supported");
+        // 写到标准错误，避免污染作为数据流的标准输出
+        eprintln!("Language not supported");
         rust_i18n::set_locale("en");
     } else {
-        rust_i18n::set_locale(&language);
+        rust_i18n::set_locale(language);
     }
     Ok(())
 }
 
 fn default_language() -> Result<(), Box<dyn std::error::Error>> {
-    let language: Vec<_> = whoami::langs()?
-        .map(|lang: Language| lang.to_string())
+    // 构建系统等环境中可能没有设置语言环境变量，此时保持默认语言
+    let Ok(langs) = whoami::langs() else {
+        return Ok(());
+    };
+    let language: Vec<_> = langs.map(|lang: Language| lang.to_string()).collect();
+    if let Some(language) = language.first() {
+        set_language(language)?;
+    }
+
+    Ok(())
+}
+
+/// 工程配置文件的保存位置：已有的工程配置，或者输入文件夹（压缩包所在文件夹）中的 `.transjlc.toml`
+fn project_config_path(input: &str, explicit: Option<&str>) -> PathBuf {
+    if let Some(path) = explicit {
+        return PathBuf::from(path);
+    }
+    if let Some(path) = TransJLC::find_project_config(input) {
+        return path;
+    }
+    let input = Path::new(input);
+    let dir = if input.is_dir() {
+        input
+    } else {
+        input.parent().unwrap_or(Path::new("."))
+    };
+    dir.join(TransJLC::PROJECT_CONFIG_NAME)
+}
+
+/// 运行交互式映射向导，把确认的结果作为优先级最高的手动映射
+fn run_wizard(jlc: &mut JLC, trans_jlc: &Cli::TransJLC) -> Result<(), Box<dyn std::error::Error>> {
+    use TransJLC::wizard::{common_prefix, style_rule, SaveChoice};
+
+    if trans_jlc.path == STDIO_PATH {
+        return Err(Box::new(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "Interactive mode cannot be used when reading from standard input",
+        )));
+    }
+
+    let entries = jlc.wizard_entries()?;
+    // 标准输出用于数据流时，提示写到标准错误
+    let mut output: Box<dyn Write> = if trans_jlc.output_path == STDIO_PATH {
+        Box::new(std::io::stderr())
+    } else {
+        Box::new(std::io::stdout())
+    };
+    let result = TransJLC::wizard::run(&entries, &mut std::io::stdin().lock(), &mut output)?;
+
+    let mut mappings = result
+        .mappings
+        .iter()
+        .map(|(name, target)| TransJLC::FileMapping::new(name, target.clone()))
+        .collect::<Result<Vec<_>, _>>()?;
+    mappings.append(&mut jlc.file_mappings);
+    jlc.file_mappings = mappings;
+
+    match result.save {
+        SaveChoice::None => {}
+        SaveChoice::Project => {
+            let path = project_config_path(&jlc.input_path, trans_jlc.config.as_deref());
+            TransJLC::save_file_mappings(&path, &result.mappings)?;
+            LOG.info_no_color(&format!("Mappings saved to {}", path.display()));
+        }
+        SaveChoice::Style => {
+            let path = TransJLC::user_config_path().ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::NotFound, "Cannot locate the user config directory")
+            })?;
+            let names: Vec<String> = result.mappings.iter().map(|(name, _)| name.clone()).collect();
+            let prefix = common_prefix(&names);
+            let rules: Vec<(&str, String)> = result
+                .mappings
+                .iter()
+                .filter_map(|(name, target)| Some((target.key()?, style_rule(name, &prefix))))
+                .collect();
+            TransJLC::save_rules(&path, &rules)?;
+            LOG.info_no_color(&format!("Rules saved to {}", path.display()));
+        }
+    }
+    Ok(())
+}
+
+/// `render` 子命令的选项
+struct RenderOptions {
+    sides: Vec<TransJLC::render::Side>,
+    colors: TransJLC::render::Colors,
+
+    /// 同时输出每一面的PNG图像时的分辨率
+    png_dpi: Option<u32>,
+}
+
+/// 子命令指定的输出，代替Gerber文件或ZIP
+enum Export {
+    Render(Box<RenderOptions>),
+
+    /// 板框和钻孔位置的 DXF
+    Dxf,
+}
+
+/// 把转换后的板框和钻孔导出为 DXF，发布到输出目录；`prefix` 加在文件名前
+fn write_dxf(jlc: &mut JLC, prefix: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let name = format!("{}{}", prefix, TransJLC::DXF_NAME);
+    let output = jlc.publish_files(&[(name.clone(), jlc.board_dxf().into_bytes())])?;
+    LOG.info_no_color(&format!("Exported {}", output.join(&name).display()));
+    Ok(())
+}
+
+/// 把转换后的各层渲染为SVG（以及PNG），发布到输出目录；`prefix` 加在文件名前
+fn write_renders(jlc: &mut JLC, options: &RenderOptions, prefix: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut files: Vec<(String, Vec<u8>)> = jlc
+        .render_svgs(&options.sides, &options.colors)?
+        .into_iter()
+        .map(|(name, svg)| (format!("{}{}", prefix, name), svg.into_bytes()))
         .collect();
-    let language = language[0].as_str().to_owned();
-    set_language(&language)?;
+    if let Some(dpi) = options.png_dpi {
+        for (name, png) in jlc.render_pngs(&options.sides, &options.colors, dpi as f64)? {
+            files.push((format!("{}{}", prefix, name), png));
+        }
+    }
+    let output = jlc.publish_files(&files)?;
+    for (name, _) in &files {
+        LOG.info_no_color(&format!("Rendered {}", output.join(name).display()));
+    }
+    Ok(())
+}
 
+/// 转换一块板子；需要时先运行交互式映射向导
+///
+/// `export_prefix` 加在导出的渲染图和 DXF 的文件名前，多块板子输出到同一个目录时使用。
+/// `published` 记录多块板子已经发布过的输出目录，同一目录只在第一次发布时按 `--clean` 清空。
+fn convert(
+    jlc: &mut JLC,
+    trans_jlc: &Cli::TransJLC,
+    zip_name: &str,
+    export: Option<&Export>,
+    export_prefix: &str,
+    published: Option<&mut std::collections::HashSet<String>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // 无法识别EDA风格又没有任何手动规则时，在终端中自动进入向导
+    let unrecognized = jlc.style_name()?.is_none()
+        && jlc.file_mappings.is_empty()
+        && jlc.extra_rules.is_empty();
+    if trans_jlc.interactive || (unrecognized && std::io::stdin().is_terminal()) {
+        run_wizard(jlc, trans_jlc)?;
+    }
+
+    jlc.copy_file()?;
+
+    // 多块板子输出到同一个目录时只在第一次发布时清空，不能删除之前的板子的输出。
+    // 输出目录在转换之后展开，`{layers}` 等变量取这块板子的值，与实际发布的目录一致
+    if let Some(published) = published {
+        jlc.output_path = jlc.render_name(&jlc.output_path)?;
+        if !published.insert(jlc.output_path.clone()) && jlc.overwrite == TransJLC::OverwritePolicy::Clean {
+            jlc.overwrite = TransJLC::OverwritePolicy::Force;
+        }
+    }
+    match export {
+        Some(Export::Render(options)) => write_renders(jlc, options, export_prefix)?,
+        Some(Export::Dxf) => write_dxf(jlc, export_prefix)?,
+        None => jlc.finalize_output(trans_jlc.zip, zip_name)?,
+    }
+    Ok(())
+}
+
+/// `styles` 子命令：列出、显示和测试EDA风格的规则
+fn styles_command(jlc: &JLC, matches: &clap::ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    match matches.subcommand() {
+        Some(("list", _)) => {
+            for name in TransJLC::style_names() {
+                println!("{}", name);
+            }
+        }
+        Some(("show", sub)) => {
+            let name = sub.get_one::<String>("name").unwrap();
+            for (key, patterns) in jlc.style_rules(name)? {
+                for pattern in patterns {
+                    let source = if jlc.extra_rules.get(key).is_some_and(|extra| extra.contains(&pattern)) {
+                        "  (config)"
+                    } else {
+                        ""
+                    };
+                    println!("{:<18} {}{}", key, pattern, source);
+                }
+            }
+        }
+        Some(("test", sub)) => {
+            let name = sub.get_one::<String>("name").unwrap();
+            for file in sub.get_many::<String>("files").unwrap() {
+                let matches = jlc.test_style(name, file)?;
+                if matches.is_empty() {
+                    println!("{} -> no match", file);
+                }
+                for rule in matches {
+                    let target = rule
+                        .target
+                        .as_ref()
+                        .and_then(|target| target.file_name())
+                        .unwrap_or_else(|| "(no JLC layer, skipped)".to_string());
+                    println!("{} -> {} (/{}/) -> {}", file, rule.key, rule.pattern, target);
+                }
+            }
+        }
+        _ => {}
+    }
     Ok(())
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+fn main() {
+    if let Err(e) = run() {
+        LOG.error(&e.to_string());
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<(), Box<dyn std::error::Error>> {
     default_language()?;
 
     let matches = Cli::cli_command().get_matches();
-    let trans_jlc = Cli::TransJLC::new(&matches);
+    let mut trans_jlc = Cli::TransJLC::new(&matches);
     set_language(trans_jlc.language.as_str())?;
 
+    // 标准输出用于数据流时，所有日志都写到标准错误
+    if trans_jlc.output_path == STDIO_PATH {
+        LOG.set_stderr(true);
+    }
+
+    // -v 输出每个文件的匹配和处理细节，-q 只输出警告，-qq 只输出错误
+    let level = match (trans_jlc.verbose, trans_jlc.quiet) {
+        (v, _) if v > 0 => Level::Trace,
+        (_, 0) => Level::Info,
+        (_, 1) => Level::Warn,
+        _ => Level::Error,
+    };
+    LOG.set_level(level);
+    if trans_jlc.log_format == "json" {
+        LOG.set_format(Format::Json);
+    }
+    if let Some(log_file) = &trans_jlc.log_file {
+        LOG.set_log_file(log_file)?;
+    }
+
+    // 配置文件：用户级配置 < 工程配置 < 命令行参数
+    let (config, config_files) = TransJLC::load_config(
+        &trans_jlc.path,
+        trans_jlc.config.as_deref().map(std::path::Path::new),
+    )?;
+    trans_jlc.apply_config(&matches, &config);
+
+    if let Some(("config", sub)) = matches.subcommand() {
+        if let Some(("show", _)) = sub.subcommand() {
+            for file in &config_files {
+                println!("# {}", file.display());
+            }
+            if config_files.is_empty() {
+                println!("# no config file found, showing defaults");
+            }
+            print!("{}", trans_jlc.effective_config(&config).to_toml());
+        }
+        return Ok(());
+    }
+
+    if let Some(("styles", sub)) = matches.subcommand() {
+        let mut jlc = JLC::new(trans_jlc.path.clone(), trans_jlc.output_path.clone(), TransJLC::EDA::Auto);
+        jlc.extra_rules = config.mappings.clone();
+        return styles_command(&jlc, sub);
+    }
+
+    // render 和 dxf 子命令只输出转换结果的预览或图纸，不输出Gerber文件
+    let export = match matches.subcommand() {
+        Some(("render" | "dxf", _)) if trans_jlc.output_path == STDIO_PATH => {
+            return Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Rendered and exported files cannot be written to standard output",
+            )));
+        }
+        Some(("render", sub)) => {
+            let mut colors = config.render_colors()?;
+            for color in sub.get_many::<String>("color").into_iter().flatten() {
+                colors.set(color)?;
+            }
+            use TransJLC::render::Side;
+            let sides = match sub.get_one::<String>("side").map(String::as_str) {
+                Some("top") => vec![Side::Top],
+                Some("bottom") => vec![Side::Bottom],
+                _ => vec![Side::Top, Side::Bottom],
+            };
+            let png_dpi = sub.get_flag("png").then(|| *sub.get_one::<u32>("dpi").unwrap());
+            Some(Export::Render(Box::new(RenderOptions { sides, colors, png_dpi })))
+        }
+        Some(("dxf", _)) => Some(Export::Dxf),
+        _ => None,
+    };
+
     let path = trans_jlc.path.clone();
     let output = trans_jlc.output_path.clone();
     let eda = match trans_jlc.EDA.to_lowercase().as_str() {
@@ -53,16 +335,125 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
 
     let mut jlc = JLC::new(path, output, eda);
-    
-    // 检查是否为ZIP文件，如果是则解压
-    jlc.extract_zip_if_needed()?;
-    
-    jlc.copy_file()?;
+    jlc.ignore_hash = !config.fingerprint.hash_aperture.unwrap_or(true);
+    jlc.extra_rules = config.mappings.clone();
+    jlc.ignore_patterns = config.ignore.clone();
+    // 命令行中的映射优先于配置文件中的映射
+    jlc.file_mappings = trans_jlc
+        .map
+        .iter()
+        .map(|mapping| mapping.parse::<TransJLC::FileMapping>())
+        .collect::<Result<Vec<_>, _>>()?;
+    jlc.file_mappings.extend(config.file_mappings()?);
+    jlc.positive_planes = trans_jlc.positive_planes;
+    jlc.notes_summary = trans_jlc.notes_summary;
+    jlc.outline_tolerance = trans_jlc.outline_tolerance;
+    jlc.outline_merge = trans_jlc.merge_outline.clone();
+    jlc.import_outline = trans_jlc.import_outline.as_ref().map(|file| TransJLC::import::OutlineImport {
+        path: std::path::PathBuf::from(file),
+        replace: trans_jlc.import_outline_mode == "replace",
+    });
+    jlc.generate_outline = match &trans_jlc.generate_outline {
+        Some(shape) => Some(TransJLC::outline::OutlineGenerator {
+            shape: shape.parse()?,
+            margin: trans_jlc.outline_margin,
+            radius: trans_jlc.outline_radius,
+        }),
+        None => None,
+    };
+    jlc.preflight = match &trans_jlc.preflight {
+        Some(profile) => Some(config.preflight_profile(profile)?),
+        None => None,
+    };
+    jlc.outline_merge.extend(config.outline.merge.iter().cloned());
+    jlc.style_outline_merge = config
+        .outline
+        .styles
+        .iter()
+        .map(|(style, settings)| (style.to_lowercase(), settings.merge.clone()))
+        .collect();
+    if trans_jlc.report {
+        jlc.report_name = Some(TransJLC::REPORT_NAME.to_string());
+        jlc.colors = config.render_colors()?;
+    }
+    jlc.extract_limits = TransJLC::ExtractLimits {
+        max_total_size: trans_jlc.max_extract_size.saturating_mul(1024 * 1024),
+        max_entries: trans_jlc.max_extract_files,
+    };
+
+    // 未指定时间戳时遵循 SOURCE_DATE_EPOCH 约定，便于生成可复现的压缩包
+    let zip_timestamp = trans_jlc
+        .zip_timestamp
+        .clone()
+        .or_else(|| std::env::var("SOURCE_DATE_EPOCH").ok());
+    jlc.zip_options = TransJLC::ZipOptions {
+        compression: trans_jlc.zip_compression.parse()?,
+        timestamp: zip_timestamp
+            .as_deref()
+            .map(TransJLC::parse_timestamp)
+            .transpose()?,
+        folder: trans_jlc.zip_folder.clone(),
+    };
+    jlc.overwrite = if trans_jlc.clean {
+        TransJLC::OverwritePolicy::Clean
+    } else if trans_jlc.no_overwrite {
+        TransJLC::OverwritePolicy::NoOverwrite
+    } else if trans_jlc.force {
+        TransJLC::OverwritePolicy::Force
+    } else {
+        TransJLC::OverwritePolicy::Safe
+    };
+
+    // 检查是否为压缩包，如果是则解压
+    jlc.extract_archive_if_needed()?;
+
+    let boards = jlc.board_sets(jlc.project_name().as_str())?;
+    if boards.len() > 1 {
+        if trans_jlc.output_path == STDIO_PATH {
+            return Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Cannot write multiple boards to standard output",
+            )));
+        }
+
+        // 一个压缩包中包含多块板子时，分别输出到以文件夹命名的ZIP或目录
+        let mut published = std::collections::HashSet::new();
+        for board in &boards {
+            LOG.info_no_color(&format!("Converting board: {}", board.name));
+            let shared_output = trans_jlc.zip || TransJLC::is_template(&trans_jlc.output_path);
+            let output_path = if shared_output {
+                trans_jlc.output_path.clone()
+            } else {
+                std::path::Path::new(&trans_jlc.output_path)
+                    .join(&board.name)
+                    .to_string_lossy()
+                    .to_string()
+            };
+            // 名称模板中的 {project} 会展开为板子的文件夹名称；没有模板时直接使用文件夹名称
+            let zip_name = if TransJLC::is_template(&trans_jlc.zip_name) {
+                trans_jlc.zip_name.as_str()
+            } else {
+                board.name.as_str()
+            };
+            let mut board_jlc = jlc.for_board(board, output_path)?;
+            // 多块板子的ZIP输出到同一个目录，报告按板子命名
+            if trans_jlc.zip && board_jlc.report_name.is_some() {
+                board_jlc.report_name = Some(format!("{}-{}", board.name, TransJLC::REPORT_NAME));
+            }
+            // 输出目录共用时，导出的文件也按板子命名
+            let export_prefix = if shared_output { format!("{}-", board.name) } else { String::new() };
+            convert(&mut board_jlc, &trans_jlc, zip_name, export.as_ref(), &export_prefix, Some(&mut published))?;
+        }
+    } else {
+        // 压缩包中的文件可能分布在子文件夹中（例如单独的 drill 目录）
+        if let Some(board) = boards.first() {
+            jlc.source_files = Some(board.files.clone());
+        }
+
+        convert(&mut jlc, &trans_jlc, trans_jlc.zip_name.as_str(), export.as_ref(), "", None)?;
+    }
 
-    // 使用新的finalize_output方法处理最终输出
-    jlc.finalize_output(trans_jlc.zip, trans_jlc.zip_name.as_str())?;
-    
-    println!("{}", t!("success_log"));
+    LOG.info_no_color(&t!("success_log"));
 
     Ok(())
 }
@@ -0,0 +1,173 @@
+// SPDX-FileCopyrightText: 2025 HalfSweet
+// SPDX-License-Identifier: Apache-2.0
+
+use regex::Regex;
+
+use crate::config::glob_regex;
+use crate::FileName::{JLC_INNER_LAYER_RE, JLC_STYLE, KEYS};
+
+/// 手动映射的目标
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapTarget {
+    /// JLC层名称，例如 `Board_Outline`
+    Layer(&'static str),
+
+    /// 指定编号的内层，例如 `InnerLayer2`
+    InnerLayer(u32),
+
+    /// 忽略该文件
+    Ignore,
+}
+
+impl MapTarget {
+    /// 映射后在JLC风格中使用的层名称，忽略时为 `None`
+    pub fn key(&self) -> Option<&'static str> {
+        match self {
+            MapTarget::Layer(key) => Some(key),
+            MapTarget::InnerLayer(_) => Some("InnerLayer"),
+            MapTarget::Ignore => None,
+        }
+    }
+
+    /// 映射后的JLC文件名，忽略时为 `None`
+    pub fn file_name(&self) -> Option<String> {
+        match self {
+            MapTarget::Layer(key) => JLC_STYLE.get(key).map(String::from),
+            MapTarget::InnerLayer(num) => Some(
+                JLC_STYLE
+                    .InnerLayer_Templete
+                    .replace("{0}", &num.to_string())
+                    .replace("{1}", &num.to_string()),
+            ),
+            MapTarget::Ignore => None,
+        }
+    }
+
+    /// 根据JLC文件名反查对应的层，例如 `Gerber_InnerLayer2.G2` 对应 `InnerLayer2`
+    pub fn from_file_name(file_name: &str) -> Option<MapTarget> {
+        if let Some(caps) = JLC_INNER_LAYER_RE.captures(file_name) {
+            return caps[1].parse().ok().map(MapTarget::InnerLayer);
+        }
+        KEYS.iter()
+            .find(|key| JLC_STYLE.get(key) == Some(file_name))
+            .map(|key| MapTarget::Layer(key))
+    }
+}
+
+impl std::str::FromStr for MapTarget {
+    type Err = std::io::Error;
+
+    /// 解析映射目标：JLC层名称（不区分大小写）、`InnerLayer<N>` 或 `ignore`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let target = s.trim();
+        if target.eq_ignore_ascii_case("ignore") {
+            return Ok(MapTarget::Ignore);
+        }
+
+        let lower = target.to_ascii_lowercase();
+        if let Some(num) = lower.strip_prefix("innerlayer") {
+            return match num.trim_start_matches(['_', ':']).parse::<u32>() {
+                Ok(num) if num > 0 => Ok(MapTarget::InnerLayer(num)),
+                _ => Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("Inner layer mapping '{}' needs a layer number, e.g. InnerLayer2", target),
+                )),
+            };
+        }
+
+        KEYS.iter()
+            .find(|key| !matches!(**key, "InnerLayer" | "InnerPlane") && key.eq_ignore_ascii_case(target))
+            .map(|key| MapTarget::Layer(key))
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "Unknown mapping target '{}', expected one of: {}, InnerLayer<N>, ignore",
+                        target,
+                        KEYS.iter()
+                            .filter(|key| !matches!(**key, "InnerLayer" | "InnerPlane"))
+                            .copied()
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                )
+            })
+    }
+}
+
+impl std::fmt::Display for MapTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapTarget::Layer(key) => write!(f, "{}", key),
+            MapTarget::InnerLayer(num) => write!(f, "InnerLayer{}", num),
+            MapTarget::Ignore => write!(f, "ignore"),
+        }
+    }
+}
+
+/// 一条手动映射：文件名通配符到JLC层，优先于EDA风格的规则
+#[derive(Debug, Clone)]
+pub struct FileMapping {
+    /// 文件名通配符，例如 `outline.gbr` 或 `*.gm1`
+    pub pattern: String,
+
+    pub target: MapTarget,
+
+    re: Regex,
+}
+
+impl FileMapping {
+    pub fn new(pattern: &str, target: MapTarget) -> Result<FileMapping, std::io::Error> {
+        Ok(FileMapping {
+            pattern: pattern.to_string(),
+            target,
+            re: glob_regex(pattern)?,
+        })
+    }
+
+    pub fn matches(&self, file_name: &str) -> bool {
+        self.re.is_match(file_name)
+    }
+}
+
+impl std::str::FromStr for FileMapping {
+    type Err = std::io::Error;
+
+    /// 解析命令行中的 `<通配符>=<JLC层>`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (pattern, target) = s.rsplit_once('=').ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Invalid mapping '{}', expected <file pattern>=<JLC layer>", s),
+            )
+        })?;
+        FileMapping::new(pattern.trim(), target.parse()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_mapping_targets() {
+        let mapping: FileMapping = "outline.gbr=board_outline".parse().unwrap();
+        assert_eq!(mapping.target, MapTarget::Layer("Board_Outline"));
+        assert!(mapping.matches("Outline.GBR"));
+        assert!(!mapping.matches("outline.gbr.bak"));
+
+        let mapping: FileMapping = "*.g2=InnerLayer2".parse().unwrap();
+        assert_eq!(mapping.target.file_name().unwrap(), "Gerber_InnerLayer2.G2");
+        assert_eq!("*.pos=ignore".parse::<FileMapping>().unwrap().target, MapTarget::Ignore);
+
+        assert_eq!(MapTarget::from_file_name("Gerber_InnerLayer2.G2"), Some(MapTarget::InnerLayer(2)));
+        assert_eq!(MapTarget::from_file_name("Gerber_TopLayer.GTL"), Some(MapTarget::Layer("Top_Layer")));
+    }
+
+    #[test]
+    fn rejects_invalid_targets() {
+        assert!("outline.gbr".parse::<FileMapping>().is_err());
+        assert!("a.gbr=InnerLayer".parse::<FileMapping>().is_err());
+        assert!("a.gbr=Outline".parse::<FileMapping>().is_err());
+    }
+}
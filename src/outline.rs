@@ -0,0 +1,632 @@
+// SPDX-FileCopyrightText: 2025 HalfSweet
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::{Display, Write};
+
+use regex::Regex;
+
+use crate::gerber::{self, arc_sweep, Bounds, CoordFormat, Layer, Object, Point, Segment, Shape};
+
+/// 默认的端点相连容差（毫米）
+pub const DEFAULT_TOLERANCE: f64 = 0.01;
+
+/// 圆弧展开为折线时每一段的最大角度（弧度）
+const ARC_STEP: f64 = 0.05;
+
+/// 板框的问题
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutlineIssue {
+    /// 板框中没有任何线段
+    Empty,
+
+    /// 轮廓没有闭合，`from` 和 `to` 是缺口两侧最近的两个端点
+    Gap { from: Point, to: Point },
+
+    /// 重复或部分重叠的线段，`from` 到 `to` 为重叠的部分
+    Duplicate { from: Point, to: Point },
+
+    /// 线段在端点以外的位置相交
+    SelfIntersection { at: Point },
+}
+
+impl Display for OutlineIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutlineIssue::Empty => write!(f, "Board outline has no segments"),
+            OutlineIssue::Gap { from, to } => write!(
+                f,
+                "Board outline is open: gap of {:.3} mm between {} and {}",
+                from.distance(*to),
+                coordinate(*from),
+                coordinate(*to)
+            ),
+            OutlineIssue::Duplicate { from, to } => write!(
+                f,
+                "Board outline has duplicated segments from {} to {}",
+                coordinate(*from),
+                coordinate(*to)
+            ),
+            OutlineIssue::SelfIntersection { at } => {
+                write!(f, "Board outline intersects itself at {}", coordinate(*at))
+            }
+        }
+    }
+}
+
+impl OutlineIssue {
+    /// 事件中使用的问题类型
+    pub fn kind(&self) -> &'static str {
+        match self {
+            OutlineIssue::Empty => "empty",
+            OutlineIssue::Gap { .. } => "gap",
+            OutlineIssue::Duplicate { .. } => "duplicate",
+            OutlineIssue::SelfIntersection { .. } => "self_intersection",
+        }
+    }
+}
+
+fn coordinate(point: Point) -> String {
+    format!("({:.3}, {:.3})", point.x, point.y)
+}
+
+/// 板框中的一条线段或圆弧
+struct Edge {
+    from: Point,
+    segment: Segment,
+
+    /// 展开后的折线，圆弧按 `ARC_STEP` 分段
+    points: Vec<Point>,
+    bounds: Bounds,
+}
+
+/// 检查板框：端点在 `tolerance` 以内视为相连，报告没有闭合的缺口、重复的线段和自相交
+pub fn validate(layer: &Layer, tolerance: f64) -> Vec<OutlineIssue> {
+    let mut edges: Vec<Edge> = vec![];
+    let mut issues = vec![];
+    for object in &layer.objects {
+        let Shape::Draw { from, segment, .. } = object.shape else {
+            continue;
+        };
+        if matches!(segment, Segment::Line(to) if to.distance(from) <= tolerance) {
+            continue;
+        }
+        let points = flatten(from, segment);
+        let mut bounds = Bounds::point(from.x, from.y);
+        for point in &points {
+            bounds.include(point.x, point.y);
+        }
+        let edge = Edge { from, segment, points, bounds: bounds.expand(tolerance) };
+
+        // 完全相同的线段只报告一次，并且不参与后面的检查
+        if edges.iter().any(|other| same_edge(other, &edge, tolerance)) {
+            issues.push(OutlineIssue::Duplicate { from, to: segment.end() });
+            continue;
+        }
+        edges.push(edge);
+    }
+    let has_region = layer.objects.iter().any(|object| matches!(object.shape, Shape::Region(_)));
+    if edges.is_empty() && !has_region {
+        return vec![OutlineIssue::Empty];
+    }
+
+    // 部分重叠的共线直线，较短的一条不参与轮廓的连接
+    let mut overlapping = vec![];
+    let mut stray = vec![];
+    for (i, a) in edges.iter().enumerate() {
+        for (j, b) in edges.iter().enumerate().skip(i + 1) {
+            if !a.bounds.overlaps(&b.bounds) {
+                continue;
+            }
+            if let (Segment::Line(a_to), Segment::Line(b_to)) = (a.segment, b.segment) {
+                if let Some((from, to)) = collinear_overlap(a.from, a_to, b.from, b_to, tolerance) {
+                    issues.push(OutlineIssue::Duplicate { from, to });
+                    overlapping.push((i, j));
+                    let longer = a.from.distance(a_to) >= b.from.distance(b_to);
+                    stray.push(if longer { j } else { i });
+                }
+            }
+        }
+    }
+
+    // 在端点以外相交的线段；两条线段在共同端点处相接不算相交
+    let mut crossings: Vec<Point> = vec![];
+    for (i, a) in edges.iter().enumerate() {
+        for (j, b) in edges.iter().enumerate().skip(i + 1) {
+            if !a.bounds.overlaps(&b.bounds) || overlapping.contains(&(i, j)) {
+                continue;
+            }
+            let ends = [a.from, a.segment.end(), b.from, b.segment.end()];
+            for at in polyline_intersections(&a.points, &b.points) {
+                let at_end = ends.iter().any(|end| end.distance(at) <= tolerance);
+                let known = crossings.iter().any(|point| point.distance(at) <= tolerance);
+                if !at_end && !known {
+                    crossings.push(at);
+                }
+            }
+        }
+    }
+    issues.extend(crossings.into_iter().map(|at| OutlineIssue::SelfIntersection { at }));
+
+    // 没有闭合的轮廓：把各条开放轮廓的端点按距离从近到远两两配对，每一对是一个缺口
+    let deduplicated = Layer {
+        objects: edges
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !stray.contains(index))
+            .map(|(_, edge)| Object {
+                shape: Shape::Draw { from: edge.from, segment: edge.segment, aperture: 0 },
+                dark: true,
+            })
+            .collect(),
+        ..Layer::default()
+    };
+    let (_, open) = gerber::chain_contours(&deduplicated, tolerance);
+    let ends: Vec<Point> = open
+        .iter()
+        .flat_map(|contour| [contour.start, contour.segments.last().unwrap().end()])
+        .collect();
+    let mut pairs: Vec<(usize, usize)> = (0..ends.len())
+        .flat_map(|i| (i + 1..ends.len()).map(move |j| (i, j)))
+        .collect();
+    pairs.sort_by(|a, b| ends[a.0].distance(ends[a.1]).total_cmp(&ends[b.0].distance(ends[b.1])));
+    let mut paired = vec![false; ends.len()];
+    for (i, j) in pairs {
+        if !paired[i] && !paired[j] {
+            paired[i] = true;
+            paired[j] = true;
+            issues.push(OutlineIssue::Gap { from: ends[i], to: ends[j] });
+        }
+    }
+    issues
+}
+
+/// 两条线段或圆弧是否相同（方向可以相反）
+fn same_edge(a: &Edge, b: &Edge, tolerance: f64) -> bool {
+    let (a_to, b_to) = (a.segment.end(), b.segment.end());
+    let forward = a.from.distance(b.from) <= tolerance && a_to.distance(b_to) <= tolerance;
+    let backward = a.from.distance(b_to) <= tolerance && a_to.distance(b.from) <= tolerance;
+    match (a.segment, b.segment) {
+        (Segment::Line(_), Segment::Line(_)) => forward || backward,
+        (
+            Segment::Arc { center: a_center, clockwise: a_cw, .. },
+            Segment::Arc { center: b_center, clockwise: b_cw, .. },
+        ) => {
+            a_center.distance(b_center) <= tolerance
+                && ((forward && a_cw == b_cw) || (backward && a_cw != b_cw))
+        }
+        _ => false,
+    }
+}
+
+/// 两条共线直线重叠的部分，重叠长度不超过容差时返回 `None`
+fn collinear_overlap(a0: Point, a1: Point, b0: Point, b1: Point, tolerance: f64) -> Option<(Point, Point)> {
+    let length = a0.distance(a1);
+    let (dx, dy) = ((a1.x - a0.x) / length, (a1.y - a0.y) / length);
+    let offset = |p: Point| ((p.x - a0.x) * dy - (p.y - a0.y) * dx).abs();
+    if offset(b0) > tolerance || offset(b1) > tolerance {
+        return None;
+    }
+    let along = |p: Point| (p.x - a0.x) * dx + (p.y - a0.y) * dy;
+    let (b_start, b_end) = (along(b0).min(along(b1)), along(b0).max(along(b1)));
+    let (start, end) = (b_start.max(0.0), b_end.min(length));
+    if end - start <= tolerance {
+        return None;
+    }
+    let at = |t: f64| Point::new(a0.x + dx * t, a0.y + dy * t);
+    Some((at(start), at(end)))
+}
+
+/// 圆弧展开为折线，直线只有两个端点
+fn flatten(from: Point, segment: Segment) -> Vec<Point> {
+    match segment {
+        Segment::Line(to) => vec![from, to],
+        Segment::Arc { to, center, clockwise } => {
+            let radius = center.distance(from);
+            let (start, sweep) = arc_sweep(from, to, center, clockwise);
+            let steps = ((sweep.abs() / ARC_STEP).ceil() as usize).max(1);
+            let mut points: Vec<Point> = (0..steps)
+                .map(|step| {
+                    let angle = start + sweep * step as f64 / steps as f64;
+                    Point::new(center.x + radius * angle.cos(), center.y + radius * angle.sin())
+                })
+                .collect();
+            points.push(to);
+            points
+        }
+    }
+}
+
+/// 两条折线的交点（不含共线重叠）
+fn polyline_intersections(a: &[Point], b: &[Point]) -> Vec<Point> {
+    let mut points = vec![];
+    for pa in a.windows(2) {
+        for pb in b.windows(2) {
+            if let Some(point) = line_intersection(pa[0], pa[1], pb[0], pb[1]) {
+                points.push(point);
+            }
+        }
+    }
+    points
+}
+
+fn line_intersection(p0: Point, p1: Point, q0: Point, q1: Point) -> Option<Point> {
+    let (rx, ry) = (p1.x - p0.x, p1.y - p0.y);
+    let (sx, sy) = (q1.x - q0.x, q1.y - q0.y);
+    let denominator = rx * sy - ry * sx;
+    if denominator.abs() < 1e-12 {
+        return None;
+    }
+    let (qx, qy) = (q0.x - p0.x, q0.y - p0.y);
+    let t = (qx * sy - qy * sx) / denominator;
+    let u = (qx * ry - qy * rx) / denominator;
+    if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+        Some(Point::new(p0.x + rx * t, p0.y + ry * t))
+    } else {
+        None
+    }
+}
+
+/// 把多个板框或机械层文件合并为一个Gerber文件
+///
+/// 所有文件统一为 `%FSLAX46Y46*%` 格式；单位使用毫米，只有所有文件都是英寸时才使用英寸。
+/// 孔径从 D10 开始重新编号，同名的孔径宏加上序号，`%TF` 文件属性只保留第一个文件的。
+pub fn merge(files: &[(String, String)]) -> Result<String, std::io::Error> {
+    let invalid = |name: &str, msg: &str| {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Cannot merge '{}': {}", name, msg))
+    };
+    let formats = files
+        .iter()
+        .map(|(name, content)| CoordFormat::parse(content).ok_or_else(|| invalid(name, "no coordinate format (%FS)")))
+        .collect::<Result<Vec<_>, _>>()?;
+    let inch = formats.iter().all(|format| format.inch);
+    let output = CoordFormat { x_integers: 4, y_integers: 4, inch, ..CoordFormat::default() };
+
+    let coord_re = Regex::new(r"([XYIJ])([+-]?\d+)").unwrap();
+    let select_re = Regex::new(r"^(?:G54)?D(\d+)$").unwrap();
+    let define_re = Regex::new(r"^ADD(\d+)([A-Za-z_.$][\w.$-]*)(?:,(.*))?$").unwrap();
+    let step_re = Regex::new(r"([IJ])([\d.]+)").unwrap();
+
+    let mut merged = format!("%FSLAX46Y46*%\n%MO{}*%\n", if inch { "IN" } else { "MM" });
+    let mut macros: HashSet<String> = HashSet::new();
+    let mut next_aperture = 10;
+    for (index, ((name, content), format)) in files.iter().zip(&formats).enumerate() {
+        // 文件与输出之间的单位换算，孔径尺寸和阵列间距按此缩放
+        let factor = match (format.inch, inch) {
+            (true, false) => 25.4,
+            _ => 1.0,
+        };
+        let mut apertures: BTreeMap<u32, u32> = BTreeMap::new();
+        let mut renamed: BTreeMap<String, String> = BTreeMap::new();
+        if index > 0 {
+            merged.push_str("%LPD*%\nG01*\n");
+        }
+        push_line(&mut merged, &format!("G04 Merged from {}*", name.replace('*', "")));
+
+        for block in blocks(content) {
+            if let Some(extended) = block.strip_prefix('%') {
+                let command = extended.trim_end_matches('%').trim_end_matches('*');
+                match command.get(..2).unwrap_or("") {
+                    "FS" | "MO" | "IN" | "LN" | "IP" => continue,
+                    "TF" if index > 0 => continue,
+                    "AM" => {
+                        if factor != 1.0 {
+                            return Err(invalid(name, "aperture macros cannot be converted from inches to millimeters"));
+                        }
+                        let (macro_name, body) = command[2..].split_once('*').unwrap_or((&command[2..], ""));
+                        let mut new_name = macro_name.to_string();
+                        let mut suffix = index;
+                        while macros.contains(&new_name) {
+                            suffix += 1;
+                            new_name = format!("{}_{}", macro_name, suffix);
+                        }
+                        macros.insert(new_name.clone());
+                        renamed.insert(macro_name.to_string(), new_name.clone());
+                        push_line(&mut merged, &format!("%AM{}*{}*%", new_name, body));
+                    }
+                    "AD" => {
+                        let caps = define_re
+                            .captures(command)
+                            .ok_or_else(|| invalid(name, &format!("invalid aperture definition '%{}%'", command)))?;
+                        let number: u32 = caps[1].parse().unwrap_or(0);
+                        apertures.insert(number, next_aperture);
+                        let template = caps[2].to_string();
+                        let mut params: Vec<String> =
+                            caps.get(3).map(|p| p.as_str().split('X').map(str::to_string).collect()).unwrap_or_default();
+                        if factor != 1.0 {
+                            // 标准孔径的尺寸参数，多边形的第 2、3 个参数是顶点数和旋转角度
+                            let sizes: &[usize] = match template.as_str() {
+                                "C" => &[0, 1],
+                                "R" | "O" => &[0, 1, 2],
+                                "P" => &[0, 3],
+                                _ => return Err(invalid(name, "aperture macros cannot be converted from inches to millimeters")),
+                            };
+                            for param in sizes {
+                                if let Some(value) = params.get_mut(*param) {
+                                    *value = scale_number(value, factor);
+                                }
+                            }
+                        }
+                        let template = renamed.get(&template).cloned().unwrap_or(template);
+                        let params = if params.is_empty() { String::new() } else { format!(",{}", params.join("X")) };
+                        push_line(&mut merged, &format!("%ADD{}{}{}*%", next_aperture, template, params));
+                        next_aperture += 1;
+                    }
+                    "SR" if factor != 1.0 => {
+                        let scaled = step_re.replace_all(command, |caps: &regex::Captures| {
+                            format!("{}{}", &caps[1], scale_number(&caps[2], factor))
+                        });
+                        push_line(&mut merged, &format!("%{}*%", scaled));
+                    }
+                    _ => {
+                        push_line(&mut merged, &block);
+                    }
+                }
+                continue;
+            }
+
+            let statement = block.trim_end_matches('*');
+            if matches!(statement, "M02" | "M00" | "M01" | "G70" | "G71" | "G90") || statement.is_empty() {
+                continue;
+            }
+            if statement.starts_with("G04") {
+                push_line(&mut merged, &block);
+                continue;
+            }
+            if let Some(caps) = select_re.captures(statement) {
+                let number: u32 = caps[1].parse().unwrap_or(0);
+                if number >= 10 {
+                    let new = apertures
+                        .get(&number)
+                        .ok_or_else(|| invalid(name, &format!("aperture D{} is not defined", number)))?;
+                    push_line(&mut merged, &format!("D{}*", new));
+                    continue;
+                }
+            }
+            let mut error = None;
+            let rewritten = coord_re.replace_all(statement, |caps: &regex::Captures| {
+                let mm = match &caps[1] {
+                    "X" | "I" => format.x_to_mm(&caps[2]),
+                    _ => format.y_to_mm(&caps[2]),
+                };
+                match mm {
+                    Some(mm) => format!("{}{}", &caps[1], output.format(mm, 6)),
+                    None => {
+                        error = Some(caps[0].to_string());
+                        caps[0].to_string()
+                    }
+                }
+            });
+            if let Some(coordinate) = error {
+                return Err(invalid(name, &format!("invalid coordinate '{}'", coordinate)));
+            }
+            push_line(&mut merged, &format!("{}*", rewritten));
+        }
+    }
+    merged.push_str("M02*\n");
+    Ok(merged)
+}
+
+fn push_line(output: &mut String, block: &str) {
+    output.push_str(block);
+    output.push('\n');
+}
+
+/// 缩放十进制数，保留 6 位小数并去掉末尾的零
+fn scale_number(value: &str, factor: f64) -> String {
+    match value.parse::<f64>() {
+        Ok(number) => {
+            let scaled = format!("{:.6}", number * factor);
+            scaled.trim_end_matches('0').trim_end_matches('.').to_string()
+        }
+        Err(_) => value.to_string(),
+    }
+}
+
+/// 生成的板框的大小
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutlineShape {
+    /// 铜层图形的范围加上边距
+    Copper,
+
+    /// 指定宽和高（毫米），中心与铜层图形的中心对齐，没有铜层时左下角位于原点
+    Size { width: f64, height: f64 },
+}
+
+impl std::str::FromStr for OutlineShape {
+    type Err = std::io::Error;
+
+    /// 解析 `copper` 或 `<宽>x<高>`，例如 `100x80`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let shape = s.trim().to_ascii_lowercase();
+        if shape == "copper" {
+            return Ok(OutlineShape::Copper);
+        }
+        let size = shape
+            .split_once('x')
+            .and_then(|(width, height)| Some((width.trim().parse::<f64>().ok()?, height.trim().parse::<f64>().ok()?)));
+        match size {
+            Some((width, height)) if width > 0.0 && height > 0.0 && width.is_finite() && height.is_finite() => {
+                Ok(OutlineShape::Size { width, height })
+            }
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Invalid outline '{}', expected 'copper' or <width>x<height> in mm", s),
+            )),
+        }
+    }
+}
+
+/// 没有板框文件时生成板框的设置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OutlineGenerator {
+    pub shape: OutlineShape,
+
+    /// 铜层范围之外的边距（毫米），只用于 [`OutlineShape::Copper`]
+    pub margin: f64,
+
+    /// 圆角半径（毫米），为 0 时生成直角矩形
+    pub radius: f64,
+}
+
+impl OutlineGenerator {
+    /// 根据铜层图形的范围计算板框的矩形
+    pub fn bounds(&self, copper: Option<Bounds>) -> Result<Bounds, std::io::Error> {
+        match (self.shape, copper) {
+            (OutlineShape::Copper, Some(copper)) => Ok(copper.expand(self.margin)),
+            (OutlineShape::Copper, None) => Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "Cannot generate a board outline from the copper extents: no copper layer found",
+            )),
+            (OutlineShape::Size { width, height }, Some(copper)) => {
+                let (x, y) = ((copper.min_x + copper.max_x) / 2.0, (copper.min_y + copper.max_y) / 2.0);
+                Ok(Bounds { min_x: x - width / 2.0, min_y: y - height / 2.0, max_x: x + width / 2.0, max_y: y + height / 2.0 })
+            }
+            (OutlineShape::Size { width, height }, None) => Ok(Bounds { min_x: 0.0, min_y: 0.0, max_x: width, max_y: height }),
+        }
+    }
+
+    /// 生成板框的Gerber文件内容，圆角半径不超过短边的一半
+    pub fn gerber(&self, bounds: Bounds) -> String {
+        let format = CoordFormat::default();
+        let radius = self.radius.min(bounds.width() / 2.0).min(bounds.height() / 2.0).max(0.0);
+        let xy = |x: f64, y: f64| format!("X{}Y{}", format.format(x, 6), format.format(y, 6));
+        let ij = |i: f64, j: f64| format!("I{}J{}", format.format(i, 6), format.format(j, 6));
+
+        let mut gerber = String::from(
+            "%FSLAX46Y46*%\n%MOMM*%\n%TF.FileFunction,Profile,NP*%\nG04 Board outline generated by TransJLC*\n%ADD10C,0.100000*%\nG75*\nG01*\nD10*\n",
+        );
+        let Bounds { min_x, min_y, max_x, max_y } = bounds;
+        // 从左下角圆角的终点开始，逆时针绕一周
+        let _ = writeln!(gerber, "{}D02*", xy(min_x + radius, min_y));
+        let corners = [
+            ((max_x - radius, min_y), (max_x, min_y + radius), (0.0, radius)),
+            ((max_x, max_y - radius), (max_x - radius, max_y), (-radius, 0.0)),
+            ((min_x + radius, max_y), (min_x, max_y - radius), (0.0, -radius)),
+            ((min_x, min_y + radius), (min_x + radius, min_y), (radius, 0.0)),
+        ];
+        for ((x, y), (arc_x, arc_y), (i, j)) in corners {
+            let _ = writeln!(gerber, "{}D01*", xy(x, y));
+            if radius > 0.0 {
+                let _ = writeln!(gerber, "G03*\n{}{}D01*\nG01*", xy(arc_x, arc_y), ij(i, j));
+            }
+        }
+        gerber.push_str("M02*\n");
+        gerber
+    }
+}
+
+/// 把Gerber内容拆分为数据块：扩展指令 `%...%` 整体作为一块，其余按 `*` 分隔
+fn blocks(content: &str) -> Vec<String> {
+    let mut blocks = vec![];
+    let mut rest = content;
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+        if let Some(extended) = rest.strip_prefix('%') {
+            let end = extended.find('%').map(|end| end + 2).unwrap_or(rest.len());
+            let block: String = rest[..end].split_whitespace().collect();
+            blocks.push(block);
+            rest = &rest[end..];
+        } else {
+            let end = rest.find(['*', '%']).map(|end| end + usize::from(rest[end..].starts_with('*'))).unwrap_or(rest.len());
+            let block = rest[..end].trim();
+            if !block.is_empty() {
+                blocks.push(block.to_string());
+            }
+            rest = &rest[end..];
+        }
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10C,0.1*%\nD10*\nG01*\n";
+
+    fn check(body: &str) -> Vec<OutlineIssue> {
+        validate(&gerber::parse(&format!("{}{}M02*\n", HEADER, body)).unwrap(), DEFAULT_TOLERANCE)
+    }
+
+    #[test]
+    fn accepts_closed_outline_with_arcs() {
+        // 左上角为圆角的矩形，加一个圆形开孔
+        let issues = check(
+            "X0Y0D02*\nX20000000Y0D01*\nX20000000Y10000000D01*\nX3000000Y10000000D01*\nG75*\nG03*\nX0Y7000000I0J-3000000D01*\nG01*\nX0Y0D01*\n\
+             X12000000Y5000000D02*\nG02*\nX12000000Y5000000I-2000000J0D01*\n",
+        );
+        assert_eq!(issues, []);
+        assert_eq!(check(""), [OutlineIssue::Empty]);
+    }
+
+    #[test]
+    fn reports_gaps_duplicates_and_intersections() {
+        let gap = check("X0Y0D02*\nX10000000Y0D01*\nX10000000Y10000000D01*\nX0Y10000000D01*\nX0Y500000D01*\n");
+        assert_eq!(gap, [OutlineIssue::Gap { from: Point::new(0.0, 0.5), to: Point::new(0.0, 0.0) }]);
+        assert!(gap[0].to_string().contains("gap of 0.500 mm between (0.000, 0.500) and (0.000, 0.000)"));
+
+        let duplicate = check(
+            "X0Y0D02*\nX10000000Y0D01*\nX10000000Y10000000D01*\nX0Y10000000D01*\nX0Y0D01*\nX10000000Y0D02*\nX0Y0D01*\nX2000000Y0D02*\nX5000000Y0D01*\n",
+        );
+        assert_eq!(
+            duplicate,
+            [
+                OutlineIssue::Duplicate { from: Point::new(10.0, 0.0), to: Point::new(0.0, 0.0) },
+                OutlineIssue::Duplicate { from: Point::new(2.0, 0.0), to: Point::new(5.0, 0.0) },
+            ]
+        );
+
+        // 8 字形的轮廓
+        let crossed = check("X0Y0D02*\nX10000000Y10000000D01*\nX10000000Y0D01*\nX0Y10000000D01*\nX0Y0D01*\n");
+        assert_eq!(crossed, [OutlineIssue::SelfIntersection { at: Point::new(5.0, 5.0) }]);
+    }
+
+    #[test]
+    fn merges_files_with_different_formats() {
+        let board = format!("%TF.FileFunction,Profile,NP*%\n{}X0Y0D02*\nX20000000Y0D01*\nX20000000Y10000000D01*\nX0Y10000000D01*\nX0Y0D01*\nM02*\n", HEADER);
+        // 英寸、2.4 格式的开槽，孔径 D10 与上一个文件冲突
+        let slot = "%FSLAX24Y24*%\n%MOIN*%\n%TF.FileFunction,Other*%\n%ADD10C,0.01*%\nD10*\nX4000Y2000D02*\nX6000Y2000D01*\nM02*\n";
+        let merged = merge(&[("board.gm1".to_string(), board), ("slot.gm2".to_string(), slot.to_string())]).unwrap();
+
+        assert!(merged.starts_with("%FSLAX46Y46*%\n%MOMM*%\n"));
+        assert_eq!(merged.matches("%TF").count(), 1);
+        assert!(merged.contains("%ADD11C,0.254*%\nD11*\n"));
+        assert!(merged.contains("X10160000Y5080000D02*"));
+        assert!(merged.ends_with("D01*\nM02*\n"));
+
+        let layer = gerber::parse(&merged).unwrap();
+        assert_eq!(layer.apertures.len(), 2);
+        assert_eq!(layer.warnings, Vec::<String>::new());
+        assert_eq!(validate(&layer, DEFAULT_TOLERANCE), [OutlineIssue::Gap { from: Point::new(15.24, 5.08), to: Point::new(10.16, 5.08) }]);
+
+        let error = merge(&[("empty.gm1".to_string(), "G04 nothing*\n".to_string())]).unwrap_err();
+        assert!(error.to_string().contains("empty.gm1"));
+    }
+
+    #[test]
+    fn generates_closed_outline() {
+        assert_eq!("100x80".parse::<OutlineShape>().unwrap(), OutlineShape::Size { width: 100.0, height: 80.0 });
+        assert!("100x0".parse::<OutlineShape>().is_err());
+
+        let copper = Bounds { min_x: 10.0, min_y: 5.0, max_x: 30.0, max_y: 15.0 };
+        for radius in [0.0, 2.0, 100.0] {
+            let generator = OutlineGenerator { shape: OutlineShape::Copper, margin: 1.0, radius };
+            let bounds = generator.bounds(Some(copper)).unwrap();
+            assert_eq!(bounds, Bounds { min_x: 9.0, min_y: 4.0, max_x: 31.0, max_y: 16.0 });
+
+            let layer = gerber::parse(&generator.gerber(bounds)).unwrap();
+            assert_eq!(validate(&layer, DEFAULT_TOLERANCE), []);
+            let outline = layer.bounds().unwrap().expand(-0.05);
+            assert!((outline.min_x - 9.0).abs() < 1e-6 && (outline.max_y - 16.0).abs() < 1e-6);
+        }
+
+        let sized = OutlineGenerator { shape: OutlineShape::Size { width: 50.0, height: 40.0 }, margin: 1.0, radius: 0.0 };
+        assert_eq!(sized.bounds(Some(copper)).unwrap(), Bounds { min_x: -5.0, min_y: -10.0, max_x: 45.0, max_y: 30.0 });
+        assert_eq!(sized.bounds(None).unwrap(), Bounds { min_x: 0.0, min_y: 0.0, max_x: 50.0, max_y: 40.0 });
+        assert!(OutlineGenerator { shape: OutlineShape::Copper, margin: 1.0, radius: 0.0 }.bounds(None).is_err());
+    }
+}
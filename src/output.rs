@@ -0,0 +1,349 @@
+// SPDX-FileCopyrightText: 2025 HalfSweet
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::{Path, PathBuf};
+
+use chrono::{Datelike, NaiveDateTime, Timelike};
+
+use crate::FileName::{JLC_INNER_LAYER_RE, JLC_STYLE};
+
+/// 输出ZIP的压缩方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ZipCompression {
+    /// 不压缩，与嘉立创EDA导出的ZIP一致
+    #[default]
+    Stored,
+    Deflate,
+    Zstd,
+}
+
+impl std::str::FromStr for ZipCompression {
+    type Err = std::io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "stored" => Ok(ZipCompression::Stored),
+            "deflate" => Ok(ZipCompression::Deflate),
+            "zstd" => Ok(ZipCompression::Zstd),
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Unknown zip compression method: {}", s),
+            )),
+        }
+    }
+}
+
+impl From<ZipCompression> for zip::CompressionMethod {
+    fn from(value: ZipCompression) -> Self {
+        match value {
+            ZipCompression::Stored => zip::CompressionMethod::Stored,
+            ZipCompression::Deflate => zip::CompressionMethod::Deflated,
+            ZipCompression::Zstd => zip::CompressionMethod::Zstd,
+        }
+    }
+}
+
+/// 输出ZIP的格式选项
+#[derive(Debug, Clone, Default)]
+pub struct ZipOptions {
+    /// 压缩方式
+    pub compression: ZipCompression,
+
+    /// 固定的条目时间戳，为空时使用当前时间
+    pub timestamp: Option<NaiveDateTime>,
+
+    /// ZIP内的顶层文件夹名称，为空时文件直接位于根目录
+    pub folder: Option<String>,
+}
+
+impl ZipOptions {
+    /// 条目使用的ZIP时间戳
+    pub fn zip_datetime(&self) -> Result<zip::DateTime, std::io::Error> {
+        match self.timestamp {
+            Some(time) => zip::DateTime::from_date_and_time(
+                time.year().clamp(1980, 2107) as u16,
+                time.month() as u8,
+                time.day() as u8,
+                time.hour() as u8,
+                time.minute() as u8,
+                time.second() as u8,
+            )
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string())),
+            None => Ok(zip::DateTime::default_for_write()),
+        }
+    }
+
+    /// 条目在ZIP中的路径
+    pub fn entry_name(&self, file_name: &str) -> String {
+        match &self.folder {
+            Some(folder) => format!("{}/{}", folder.trim_matches('/'), file_name),
+            None => file_name.to_string(),
+        }
+    }
+}
+
+/// 解析固定时间戳，支持 Unix 秒数、RFC 3339 和 `YYYY-MM-DD HH:MM:SS` 格式
+pub fn parse_timestamp(value: &str) -> Result<NaiveDateTime, std::io::Error> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<i64>() {
+        if let Some(time) = chrono::DateTime::from_timestamp(seconds, 0) {
+            return Ok(time.naive_utc());
+        }
+    }
+    if let Ok(time) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(time.naive_utc());
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S"] {
+        if let Ok(time) = NaiveDateTime::parse_from_str(value, format) {
+            return Ok(time);
+        }
+    }
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap());
+    }
+
+    Err(std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        format!("Invalid timestamp: {}", value),
+    ))
+}
+
+/// 输出目录中已有文件的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverwritePolicy {
+    /// 覆盖同名文件；如果输出目录中残留了上一次转换的层文件则报错
+    #[default]
+    Safe,
+
+    /// 输出目录中已有同名文件时报错
+    NoOverwrite,
+
+    /// 覆盖同名文件，保留输出目录中的其他文件
+    Force,
+
+    /// 用本次的输出替换整个输出目录
+    Clean,
+}
+
+/// 判断文件名是否为转换生成的JLC风格文件
+fn is_jlc_output(name: &str) -> bool {
+    JLC_INNER_LAYER_RE.is_match(name)
+        || JLC_STYLE
+            .clone()
+            .into_iter()
+            .flat_map(|(_, values)| values)
+            .any(|value| value == name)
+}
+
+/// 先把输出写到与输出目录同级的暂存目录，成功后再按策略移动到输出目录
+///
+/// 暂存目录和输出目录位于同一文件系统。输出目录中本次不覆盖的文件先带到暂存目录，
+/// 再通过一次原子的重命名整体替换输出目录，转换或发布失败时输出目录保持原样。`replace_layers` 表示本次输出的是一整套层文件，
+/// 需要检查输出目录中是否残留了旧的层文件。
+pub fn publish_staged(
+    output_dir: &Path,
+    policy: OverwritePolicy,
+    replace_layers: bool,
+    stage: impl FnOnce(&Path) -> Result<(), std::io::Error>,
+) -> Result<(), std::io::Error> {
+    let parent = match output_dir.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    std::fs::create_dir_all(&parent)?;
+
+    let staging = tempfile::Builder::new()
+        .prefix(".transjlc-staging-")
+        .tempdir_in(&parent)?;
+    stage(staging.path())?;
+
+    let mut staged = std::fs::read_dir(staging.path())?
+        .map(|res| res.map(|e| e.file_name()))
+        .collect::<Result<Vec<_>, std::io::Error>>()?;
+    staged.sort();
+
+    // 输出目录不存在或需要清空时，直接整体替换
+    if policy == OverwritePolicy::Clean || !output_dir.exists() {
+        return replace_dir(staging, output_dir);
+    }
+
+    match policy {
+        OverwritePolicy::NoOverwrite => {
+            let existing: Vec<String> = staged
+                .iter()
+                .filter(|name| output_dir.join(name).exists())
+                .map(|name| name.to_string_lossy().to_string())
+                .collect();
+            if !existing.is_empty() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    format!("Output files already exist: {}", existing.join(", ")),
+                ));
+            }
+        }
+        OverwritePolicy::Safe if replace_layers => {
+            let mut stale = vec![];
+            for entry in std::fs::read_dir(output_dir)? {
+                let name = entry?.file_name();
+                if !staged.contains(&name) && is_jlc_output(&name.to_string_lossy()) {
+                    stale.push(name.to_string_lossy().to_string());
+                }
+            }
+            stale.sort();
+            if !stale.is_empty() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    format!(
+                        "Output directory contains stale files from a previous conversion: {}. Use --clean to remove them or --force to keep them",
+                        stale.join(", ")
+                    ),
+                ));
+            }
+        }
+        _ => {}
+    }
+
+    carry_over(output_dir, staging.path(), &staged)?;
+    replace_dir(staging, output_dir)
+}
+
+/// 把输出目录中本次不覆盖的文件带到暂存目录，普通文件优先使用硬链接，不支持时复制
+fn carry_over(from: &Path, to: &Path, skip: &[std::ffi::OsString]) -> Result<(), std::io::Error> {
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if skip.contains(&name) {
+            continue;
+        }
+        let (src, dst) = (entry.path(), to.join(&name));
+        let file_type = entry.file_type()?;
+
+        #[cfg(unix)]
+        if file_type.is_symlink() {
+            std::os::unix::fs::symlink(std::fs::read_link(&src)?, &dst)?;
+            continue;
+        }
+
+        if file_type.is_dir() {
+            std::fs::create_dir(&dst)?;
+            carry_over(&src, &dst, &[])?;
+            // 最后再设置权限，只读目录也能先写入其中的文件
+            std::fs::set_permissions(&dst, entry.metadata()?.permissions())?;
+        } else if std::fs::hard_link(&src, &dst).is_err() {
+            std::fs::copy(&src, &dst)?;
+        }
+    }
+    Ok(())
+}
+
+/// 用暂存目录替换输出目录，失败时恢复原来的输出目录
+fn replace_dir(staging: tempfile::TempDir, output_dir: &Path) -> Result<(), std::io::Error> {
+    let staging = staging.into_path();
+
+    // 临时目录默认只有所有者可访问，发布前沿用原输出目录的权限，没有时使用普通目录的权限
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let permissions = match std::fs::metadata(output_dir) {
+            Ok(metadata) => metadata.permissions(),
+            Err(_) => std::fs::Permissions::from_mode(0o755),
+        };
+        std::fs::set_permissions(&staging, permissions)?;
+    }
+
+    if !output_dir.exists() {
+        return std::fs::rename(&staging, output_dir).inspect_err(|_| {
+            let _ = std::fs::remove_dir_all(&staging);
+        });
+    }
+
+    let parent = staging.parent().unwrap_or(Path::new("."));
+    let backup = tempfile::Builder::new()
+        .prefix(".transjlc-old-")
+        .tempdir_in(parent)?
+        .into_path();
+    // rename 不能覆盖已存在的目录，先删掉刚创建的空目录只保留名称
+    std::fs::remove_dir(&backup)?;
+
+    std::fs::rename(output_dir, &backup)?;
+    if let Err(e) = std::fs::rename(&staging, output_dir) {
+        let _ = std::fs::rename(&backup, output_dir);
+        let _ = std::fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+    std::fs::remove_dir_all(&backup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_timestamps_and_entry_names() {
+        let expected = chrono::NaiveDate::from_ymd_opt(2023, 11, 14).unwrap().and_hms_opt(22, 13, 20).unwrap();
+        assert_eq!(parse_timestamp("1700000000").unwrap(), expected);
+        assert_eq!(parse_timestamp("2023-11-14T23:13:20+01:00").unwrap(), expected);
+        assert_eq!(parse_timestamp(" 2023-11-14 22:13:20 ").unwrap(), expected);
+        assert_eq!(parse_timestamp("2023-11-14").unwrap(), expected.date().and_hms_opt(0, 0, 0).unwrap());
+        assert_eq!(parse_timestamp("yesterday").unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+
+        // ZIP的时间戳从 1980 年开始
+        let options = ZipOptions { timestamp: Some(parse_timestamp("0").unwrap()), ..Default::default() };
+        let time = options.zip_datetime().unwrap();
+        assert_eq!((time.year(), time.month(), time.day()), (1980, 1, 1));
+
+        let options = ZipOptions { folder: Some("/gerber/".to_string()), ..Default::default() };
+        assert_eq!(options.entry_name("Gerber_TopLayer.GTL"), "gerber/Gerber_TopLayer.GTL");
+        assert_eq!(ZipOptions::default().entry_name("Gerber_TopLayer.GTL"), "Gerber_TopLayer.GTL");
+    }
+
+    fn stage_layer(staging: &Path) -> Result<(), std::io::Error> {
+        std::fs::write(staging.join(JLC_STYLE.Top_Layer), "G04*")
+    }
+
+    #[test]
+    fn safe_policy_rejects_stale_layers() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let output = dir.path().join("out");
+        std::fs::create_dir_all(&output).unwrap();
+        std::fs::write(output.join("Gerber_InnerLayer3.G3"), "old").unwrap();
+
+        let err = publish_staged(&output, OverwritePolicy::Safe, true, stage_layer).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+        assert!(!output.join(JLC_STYLE.Top_Layer).exists());
+
+        publish_staged(&output, OverwritePolicy::Clean, true, stage_layer).unwrap();
+        assert!(output.join(JLC_STYLE.Top_Layer).exists());
+        assert!(!output.join("Gerber_InnerLayer3.G3").exists());
+    }
+
+    #[test]
+    fn force_policy_keeps_other_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let output = dir.path().join("out");
+        std::fs::create_dir_all(output.join("docs")).unwrap();
+        std::fs::write(output.join("docs/readme.txt"), "notes").unwrap();
+        std::fs::write(output.join("bom.csv"), "bom").unwrap();
+        std::fs::write(output.join(JLC_STYLE.Top_Layer), "old").unwrap();
+
+        publish_staged(&output, OverwritePolicy::Force, true, stage_layer).unwrap();
+        assert_eq!(std::fs::read_to_string(output.join(JLC_STYLE.Top_Layer)).unwrap(), "G04*");
+        assert_eq!(std::fs::read_to_string(output.join("bom.csv")).unwrap(), "bom");
+        assert_eq!(std::fs::read_to_string(output.join("docs/readme.txt")).unwrap(), "notes");
+        // 暂存目录和备份目录都已清理
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn failed_stage_leaves_output_untouched() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let output = dir.path().join("out");
+        let result = publish_staged(&output, OverwritePolicy::Force, true, |staging| {
+            stage_layer(staging)?;
+            Err(std::io::Error::other("conversion failed"))
+        });
+        assert!(result.is_err());
+        assert!(!output.exists());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,130 @@
+// SPDX-FileCopyrightText: 2025 HalfSweet
+// SPDX-License-Identifier: Apache-2.0
+
+use regex::Regex;
+
+use crate::gerber::{self, Bounds, CoordFormat};
+
+/// Gerber 图像的极性
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Polarity {
+    Positive,
+
+    /// 负片：绘制的图形表示去掉铜的区域，例如 Altium 的内电层和 Eagle 的电源层
+    ///
+    /// `inferred` 表示文件没有 `%IPNEG*%`，只是根据极性指令推断出来的。
+    Negative { inferred: bool },
+}
+
+/// 判断Gerber文件是否为负片
+///
+/// 有 `%IPNEG*%` 时确定为负片；否则如果第一个图形之前已经切换到清除极性（`%LPC*%`），
+/// 在空白图像上清除没有意义，按负片处理。
+pub fn detect_polarity(content: &str) -> Polarity {
+    if content.contains("%IPNEG*%") {
+        return Polarity::Negative { inferred: false };
+    }
+
+    let op_re = Regex::new(r"D0?[123]\*|G36\*").unwrap();
+    let first_op = op_re.find(content).map(|m| m.start()).unwrap_or(content.len());
+    match content[..first_op].rfind("%LP") {
+        Some(index) if content[index..].starts_with("%LPC*%") => Polarity::Negative { inferred: true },
+        _ => Polarity::Positive,
+    }
+}
+
+/// 把负片转换为正片
+///
+/// 先在 `area`（通常是板框的范围，没有时使用文件自身的坐标范围）上铺满铜，
+/// 之后原文件中的图形全部作为清除图形绘制。`%IPNEG*%` 文件中的极性指令需要互换，
+/// 推断出的负片本身已经使用清除极性，保持不变。没有坐标格式时返回 `None`。
+pub fn to_positive(content: &str, area: Option<Bounds>) -> Option<String> {
+    let format = CoordFormat::parse(content)?;
+    let area = area.or_else(|| gerber::coordinate_bounds(content))?;
+
+    let mut content = content.to_string();
+    if content.contains("%IPNEG*%") {
+        content = content
+            .replace("%IPNEG*%", "")
+            .replace("%LPD*%", "%LPX*%")
+            .replace("%LPC*%", "%LPD*%")
+            .replace("%LPX*%", "%LPC*%");
+    }
+
+    // 铺铜区域插入在第一个图形或极性指令之前，此时坐标格式已经定义。
+    // 插入位置取该语句的起点而不是行首，整个文件只有一行时也不会插到格式定义之前
+    let op_re = Regex::new(r"D0?[123]\*|G36\*|%LP[CD]\*%").unwrap();
+    let index = match op_re.find(&content) {
+        Some(m) => {
+            let start = content[..m.start()].rfind(['*', '%']).map_or(0, |i| i + 1);
+            m.start() - content[start..m.start()].trim_start().len()
+        }
+        None => content.len(),
+    };
+
+    // 铺铜使用直线插补，之后恢复文件原来的圆弧插补模式
+    let mode_re = Regex::new(r"(?:^|[*%])\s*G0?([123])(?:[^0-9]|$)").unwrap();
+    let restore = match mode_re.captures_iter(&content[..index]).last() {
+        Some(caps) if &caps[1] != "1" => format!("G0{}*\n", &caps[1]),
+        _ => String::new(),
+    };
+
+    let x = |mm: f64| format.format(mm, format.x_decimals);
+    let y = |mm: f64| format.format(mm, format.y_decimals);
+    let fill = format!(
+        "%LPD*%\nG01*\nG36*\nX{0}Y{1}D02*\nX{2}Y{1}D01*\nX{2}Y{3}D01*\nX{0}Y{3}D01*\nX{0}Y{1}D01*\nG37*\n{4}%LPC*%\n",
+        x(area.min_x),
+        y(area.min_y),
+        x(area.max_x),
+        y(area.max_y),
+        restore
+    );
+    content.insert_str(index, &fill);
+
+    // 去掉 %IPNEG*% 后留下的空行
+    Some(content.lines().filter(|line| !line.trim().is_empty()).collect::<Vec<_>>().join("\n") + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLANE: &str = "%FSLAX46Y46*%\n%MOMM*%\n%IPNEG*%\n%ADD10C,0.500000*%\nD10*\nX1000000Y1000000D03*\n%LPC*%\nX2000000Y2000000D03*\nM02*\n";
+
+    #[test]
+    fn detects_negative_images() {
+        assert_eq!(detect_polarity(PLANE), Polarity::Negative { inferred: false });
+        let inferred = "%FSLAX46Y46*%\n%LPC*%\nD10*\nX0Y0D03*\n%LPD*%\nX1Y1D03*\n";
+        assert_eq!(detect_polarity(inferred), Polarity::Negative { inferred: true });
+        let positive = "%FSLAX46Y46*%\n%LPD*%\nD10*\nX0Y0D03*\n%LPC*%\nX1Y1D03*\n";
+        assert_eq!(detect_polarity(positive), Polarity::Positive);
+    }
+
+    #[test]
+    fn converts_negative_plane_to_positive() {
+        let area = Bounds { min_x: 0.0, min_y: 0.0, max_x: 10.0, max_y: 5.0 };
+        let positive = to_positive(PLANE, Some(area)).unwrap();
+        assert!(!positive.contains("%IPNEG*%"));
+        assert_eq!(detect_polarity(&positive), Polarity::Positive);
+
+        let fill = positive.find("G36*").unwrap();
+        let flash = positive.find("X1000000Y1000000D03*").unwrap();
+        assert!(fill > positive.find("%MOMM*%").unwrap() && fill < flash);
+        assert!(positive.contains("X10000000Y5000000D01*"));
+        // 原来的暗图形变为清除图形，原来的清除图形变为暗图形
+        assert_eq!(positive[..flash].rfind("%LP"), positive[..flash].rfind("%LPC*%"));
+        assert!(positive.contains("%LPD*%\nX2000000Y2000000D03*"));
+    }
+
+    #[test]
+    fn inserts_fill_after_header_on_single_line_files() {
+        let area = Bounds { min_x: 0.0, min_y: 0.0, max_x: 10.0, max_y: 5.0 };
+        let single = "%FSLAX46Y46*%%MOMM*%%IPNEG*%%ADD10C,0.500000*%G75*G03*D10*X1000000Y1000000D03*M02*";
+        let positive = to_positive(single, Some(area)).unwrap();
+        let fill = positive.find("G36*").unwrap();
+        assert!(fill > positive.find("%ADD10C").unwrap());
+        assert!(fill < positive.find("X1000000Y1000000D03*").unwrap());
+        // 铺铜之后恢复文件原来的插补模式
+        assert!(positive[fill..].starts_with("G36*") && positive.contains("G37*\nG03*\n%LPC*%"));
+    }
+}
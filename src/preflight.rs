@@ -0,0 +1,754 @@
+// SPDX-FileCopyrightText: 2025 HalfSweet
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeSet;
+use std::fmt::Display;
+
+use crate::excellon::Drill;
+use crate::gerber::{arc_sweep, Aperture, Bounds, Layer, Point, Primitive, Segment, Shape};
+
+/// 圆弧展开为折线时每一段的最大长度（毫米）
+const ARC_STEP: f64 = 0.05;
+
+/// 钻孔与焊盘中心视为重合的距离（毫米）
+const PAD_TOLERANCE: f64 = 0.001;
+
+/// 内置工艺能力的名称
+pub const BUILTIN_PROFILES: [&str; 2] = ["jlc-standard", "jlc-advanced"];
+
+/// 板厂的工艺能力，长度单位为毫米
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub name: String,
+
+    /// 外层的最小线宽和最小间距
+    pub min_trace: f64,
+    pub min_space: f64,
+
+    /// 内层的最小线宽和最小间距
+    pub min_inner_trace: f64,
+    pub min_inner_space: f64,
+
+    /// 最小钻孔直径
+    pub min_drill: f64,
+
+    /// 金属化孔的最小焊环宽度
+    pub min_annular_ring: f64,
+
+    /// 丝印的最小线宽
+    pub min_silkscreen_width: f64,
+
+    /// 最大板子尺寸，可以旋转 90° 放置
+    pub max_width: f64,
+    pub max_height: f64,
+}
+
+impl Profile {
+    /// 内置的工艺能力，参考JLC公布的常规工艺和高精度工艺
+    pub fn builtin(name: &str) -> Option<Profile> {
+        let profile = match name.to_ascii_lowercase().as_str() {
+            "jlc-standard" => Profile {
+                name: "jlc-standard".to_string(),
+                min_trace: 0.127,
+                min_space: 0.127,
+                min_inner_trace: 0.127,
+                min_inner_space: 0.127,
+                min_drill: 0.3,
+                min_annular_ring: 0.15,
+                min_silkscreen_width: 0.153,
+                max_width: 400.0,
+                max_height: 500.0,
+            },
+            "jlc-advanced" => Profile {
+                name: "jlc-advanced".to_string(),
+                min_trace: 0.09,
+                min_space: 0.09,
+                min_inner_trace: 0.09,
+                min_inner_space: 0.09,
+                min_drill: 0.2,
+                min_annular_ring: 0.1,
+                min_silkscreen_width: 0.153,
+                max_width: 500.0,
+                max_height: 600.0,
+            },
+            _ => return None,
+        };
+        Some(profile)
+    }
+}
+
+/// 检查的规则
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    Trace,
+    Space,
+    Drill,
+    AnnularRing,
+    SilkscreenWidth,
+}
+
+impl Rule {
+    /// 日志事件中使用的名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rule::Trace => "min_trace",
+            Rule::Space => "min_space",
+            Rule::Drill => "min_drill",
+            Rule::AnnularRing => "min_annular_ring",
+            Rule::SilkscreenWidth => "min_silkscreen_width",
+        }
+    }
+
+    fn noun(&self) -> (&'static str, &'static str) {
+        match self {
+            Rule::Trace => ("trace(s) narrower than", "narrowest"),
+            Rule::Space => ("gap(s) smaller than", "smallest"),
+            Rule::Drill => ("hole(s) smaller than", "smallest"),
+            Rule::AnnularRing => ("annular ring(s) narrower than", "narrowest"),
+            Rule::SilkscreenWidth => ("silkscreen line(s) narrower than", "narrowest"),
+        }
+    }
+}
+
+/// 超出工艺能力的地方
+#[derive(Debug, Clone, PartialEq)]
+pub enum PreflightIssue {
+    /// 某一层中有 `count` 处小于 `limit`，最小的是 `at` 处的 `worst`
+    Below { rule: Rule, file: String, count: usize, worst: f64, limit: f64, at: Point },
+
+    /// 板子超过最大尺寸
+    BoardSize { width: f64, height: f64, max_width: f64, max_height: f64 },
+
+    /// 某一层无法解析，没有检查
+    Unchecked { file: String, error: String },
+
+    /// 钻孔使用了没有定义直径的刀具，这些孔不检查
+    UndefinedTool { file: String, tools: Vec<u32>, count: usize },
+}
+
+impl PreflightIssue {
+    pub fn rule(&self) -> &'static str {
+        match self {
+            PreflightIssue::Below { rule, .. } => rule.name(),
+            PreflightIssue::BoardSize { .. } => "max_board_size",
+            PreflightIssue::Unchecked { .. } => "unchecked",
+            PreflightIssue::UndefinedTool { .. } => "undefined_tool",
+        }
+    }
+}
+
+impl Display for PreflightIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PreflightIssue::Below { rule, file, count, worst, limit, at } => {
+                let (what, superlative) = rule.noun();
+                write!(
+                    f,
+                    "{}: {} {} {:.3} mm, {} {:.3} mm at ({:.3}, {:.3})",
+                    file, count, what, limit, superlative, worst, at.x, at.y
+                )
+            }
+            PreflightIssue::BoardSize { width, height, max_width, max_height } => write!(
+                f,
+                "Board size {:.2} x {:.2} mm exceeds the maximum of {:.0} x {:.0} mm",
+                width, height, max_width, max_height
+            ),
+            PreflightIssue::Unchecked { file, error } => write!(f, "{}: not checked: {}", file, error),
+            PreflightIssue::UndefinedTool { file, tools, count } => {
+                let tools: Vec<String> = tools.iter().map(|tool| format!("T{}", tool)).collect();
+                write!(f, "{}: {} hole(s) use undefined tool(s) {}", file, count, tools.join(", "))
+            }
+        }
+    }
+}
+
+/// 收集某条规则在一层中的违规
+struct Finding {
+    rule: Rule,
+    limit: f64,
+    count: usize,
+    worst: Option<(f64, Point)>,
+}
+
+impl Finding {
+    fn new(rule: Rule, limit: f64) -> Finding {
+        Finding { rule, limit, count: 0, worst: None }
+    }
+
+    fn check(&mut self, value: f64, at: Point) {
+        if value >= self.limit - 1e-6 {
+            return;
+        }
+        self.count += 1;
+        if self.worst.is_none_or(|(worst, _)| value < worst) {
+            self.worst = Some((value, at));
+        }
+    }
+
+    fn issue(self, file: &str) -> Option<PreflightIssue> {
+        let (worst, at) = self.worst?;
+        Some(PreflightIssue::Below { rule: self.rule, file: file.to_string(), count: self.count, worst, limit: self.limit, at })
+    }
+}
+
+/// 检查一层铜的线宽和间距
+///
+/// 相互接触的图形连成一个导体，只检查不同导体之间的间距。之后用 `%LPC*%` 清除的部分不再算作铜，
+/// 例如铺铜中避让焊盘的区域。负片中画出的是没有铜的地方，不检查。
+pub fn check_copper(file: &str, layer: &Layer, min_trace: f64, min_space: f64) -> Vec<PreflightIssue> {
+    if layer.negative {
+        return vec![];
+    }
+    let mut trace = Finding::new(Rule::Trace, min_trace);
+    for object in layer.objects.iter().filter(|object| object.dark) {
+        if let Shape::Draw { from, aperture, .. } = object.shape {
+            if let Some(width) = layer.apertures.get(&aperture).and_then(Aperture::diameter).filter(|width| *width > 0.0) {
+                trace.check(width, from);
+            }
+        }
+    }
+
+    let shapes = |dark: bool| {
+        layer
+            .objects
+            .iter()
+            .enumerate()
+            .filter(move |(_, object)| object.dark == dark)
+            .filter_map(|(index, object)| Feature::new(index, &object.shape, layer))
+    };
+    let mut features: Vec<Feature> = shapes(true).collect();
+    let clears: Vec<Feature> = shapes(false).collect();
+    features.sort_by(|a, b| a.bounds.min_x.total_cmp(&b.bounds.min_x));
+    let near = near_clears(&features, &clears);
+
+    // 先把相互接触的图形合并为导体，所有图形都合并之后再检查不同导体之间的间隙
+    let mut conductors: Vec<usize> = (0..features.len()).collect();
+    let mut gaps = vec![];
+    for (i, a) in features.iter().enumerate() {
+        for (j, b) in features.iter().enumerate().skip(i + 1) {
+            if b.bounds.min_x > a.bounds.max_x + min_space {
+                break;
+            }
+            if !a.bounds.expand(min_space).overlaps(&b.bounds) {
+                continue;
+            }
+            // 在两个图形之间画出的清除图形只去掉先画的图形的铜
+            let (first, second) = if a.index < b.index { (i, j) } else { (j, i) };
+            let (first, second, candidates) = (
+                &features[first],
+                &features[second],
+                if near[first].len() <= near[second].len() { &near[first] } else { &near[second] },
+            );
+            let cleared: Vec<&Feature> = candidates
+                .iter()
+                .map(|index| &clears[*index])
+                .filter(|clear| first.index < clear.index && clear.index < second.index)
+                .filter(|clear| clear.bounds.overlaps(&first.bounds) && clear.bounds.overlaps(&second.bounds))
+                .collect();
+            match first.gap(second, min_space, &cleared) {
+                Some((gap, _)) if gap <= 1e-6 => {
+                    let (root_a, root_b) = (root(&mut conductors, i), root(&mut conductors, j));
+                    conductors[root_a] = root_b;
+                }
+                Some((gap, at)) => gaps.push((i, j, gap, at)),
+                None => {}
+            }
+        }
+    }
+
+    let mut space = Finding::new(Rule::Space, min_space);
+    for (i, j, gap, at) in gaps {
+        if root(&mut conductors, i) != root(&mut conductors, j) {
+            space.check(gap, at);
+        }
+    }
+    [trace.issue(file), space.issue(file)].into_iter().flatten().collect()
+}
+
+/// 并查集中图形所属导体的代表
+fn root(parents: &mut [usize], mut index: usize) -> usize {
+    while parents[index] != index {
+        parents[index] = parents[parents[index]];
+        index = parents[index];
+    }
+    index
+}
+
+/// 每个铜图形范围内的清除图形在 `clears` 中的下标
+fn near_clears(features: &[Feature], clears: &[Feature]) -> Vec<Vec<usize>> {
+    let mut near = vec![vec![]; features.len()];
+    if clears.is_empty() {
+        return near;
+    }
+    // 按左边界扫描，只比较横向范围重叠的图形
+    let mut items: Vec<(f64, bool, usize)> = features
+        .iter()
+        .enumerate()
+        .map(|(index, feature)| (feature.bounds.min_x, false, index))
+        .chain(clears.iter().enumerate().map(|(index, clear)| (clear.bounds.min_x, true, index)))
+        .collect();
+    items.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let (mut active_features, mut active_clears): (Vec<usize>, Vec<usize>) = (vec![], vec![]);
+    for (min_x, is_clear, index) in items {
+        active_features.retain(|feature| features[*feature].bounds.max_x >= min_x);
+        active_clears.retain(|clear| clears[*clear].bounds.max_x >= min_x);
+        if is_clear {
+            for feature in &active_features {
+                if features[*feature].bounds.overlaps(&clears[index].bounds) {
+                    near[*feature].push(index);
+                }
+            }
+            active_clears.push(index);
+        } else {
+            let bounds = features[index].bounds;
+            near[index] = active_clears.iter().copied().filter(|clear| clears[*clear].bounds.overlaps(&bounds)).collect();
+            active_features.push(index);
+        }
+    }
+    near
+}
+
+/// 检查丝印的线宽
+pub fn check_silkscreen(file: &str, layer: &Layer, min_width: f64) -> Option<PreflightIssue> {
+    let mut width = Finding::new(Rule::SilkscreenWidth, min_width);
+    for object in layer.objects.iter().filter(|object| object.dark) {
+        if let Shape::Draw { from, aperture, .. } = object.shape {
+            if let Some(diameter) = layer.apertures.get(&aperture).and_then(Aperture::diameter).filter(|width| *width > 0.0) {
+                width.check(diameter, from);
+            }
+        }
+    }
+    width.issue(file)
+}
+
+/// 检查钻孔和槽孔的直径
+///
+/// 使用未定义刀具的孔和槽不检查直径，单独列出。
+pub fn check_drill(file: &str, drill: &Drill, min_drill: f64) -> Vec<PreflightIssue> {
+    let mut finding = Finding::new(Rule::Drill, min_drill);
+    let mut undefined = BTreeSet::new();
+    let mut count = 0;
+    let holes = drill.holes.iter().map(|hole| (hole.tool, hole.at));
+    for (tool, at) in holes.chain(drill.slots.iter().map(|slot| (slot.tool, slot.from))) {
+        match drill.tools.get(&tool) {
+            Some(diameter) => finding.check(*diameter, at),
+            None => {
+                undefined.insert(tool);
+                count += 1;
+            }
+        }
+    }
+    let undefined = (count > 0).then(|| PreflightIssue::UndefinedTool {
+        file: file.to_string(),
+        tools: undefined.into_iter().collect(),
+        count,
+    });
+    finding.issue(file).into_iter().chain(undefined).collect()
+}
+
+/// 检查金属化孔在外层焊盘上的焊环宽度，取各层中最窄的焊环
+///
+/// 只检查中心有焊盘的孔，铺铜中的过孔等没有对应焊盘的孔不检查。
+pub fn check_annular_ring(file: &str, drill: &Drill, copper: &[&Layer], min_ring: f64) -> Option<PreflightIssue> {
+    let pads: Vec<(Point, f64)> = copper
+        .iter()
+        .filter(|layer| !layer.negative)
+        .flat_map(|layer| {
+            layer.objects.iter().filter(|object| object.dark).filter_map(|object| match object.shape {
+                Shape::Flash { at, aperture } => {
+                    let bounds = layer.apertures.get(&aperture)?.bounds()?;
+                    Some((at, bounds.width().min(bounds.height())))
+                }
+                _ => None,
+            })
+        })
+        .collect();
+
+    let mut finding = Finding::new(Rule::AnnularRing, min_ring);
+    for hole in &drill.holes {
+        // 未定义的刀具已经由 `check_drill` 列出
+        let Some(diameter) = drill.tools.get(&hole.tool).copied() else {
+            continue;
+        };
+        let size = pads
+            .iter()
+            .filter(|(at, _)| at.distance(hole.at) <= PAD_TOLERANCE)
+            .map(|(_, size)| *size)
+            .min_by(f64::total_cmp);
+        if let Some(size) = size {
+            finding.check((size - diameter) / 2.0, hole.at);
+        }
+    }
+    finding.issue(file)
+}
+
+/// 检查板子尺寸，允许旋转 90°
+pub fn check_board_size(width: f64, height: f64, profile: &Profile) -> Option<PreflightIssue> {
+    let fits = |max_w: f64, max_h: f64| width <= max_w + 1e-6 && height <= max_h + 1e-6;
+    if fits(profile.max_width, profile.max_height) || fits(profile.max_height, profile.max_width) {
+        return None;
+    }
+    Some(PreflightIssue::BoardSize { width, height, max_width: profile.max_width, max_height: profile.max_height })
+}
+
+/// 计算间距时使用的一个铜图形，由若干加粗的点、线段或多边形组成
+struct Feature {
+    /// 在层中的绘制顺序
+    index: usize,
+    bounds: Bounds,
+    elements: Vec<Element>,
+}
+
+/// 向外扩展 `radius` 的点（1 个点）、线段（2 个点）或多边形（3 个点以上）
+struct Element {
+    points: Vec<Point>,
+    radius: f64,
+    bounds: Bounds,
+    /// 各条边和边的范围
+    edges: Vec<(Point, Point, Bounds)>,
+}
+
+impl Element {
+    fn new(points: Vec<Point>, radius: f64) -> Element {
+        let mut bounds = Bounds::point(points[0].x, points[0].y);
+        for point in &points {
+            bounds.include(point.x, point.y);
+        }
+        let count = if points.len() < 3 { 1 } else { points.len() };
+        let edges = (0..count)
+            .map(|i| {
+                let (from, to) = (points[i], points[(i + 1) % points.len()]);
+                let mut edge = Bounds::point(from.x, from.y);
+                edge.include(to.x, to.y);
+                (from, to, edge)
+            })
+            .collect();
+        Element { points, radius, bounds: bounds.expand(radius), edges }
+    }
+
+    fn contains(&self, point: Point) -> bool {
+        self.points.len() >= 3 && polygon_contains(&self.points, point)
+    }
+
+    /// 点在加粗后的图形内的深度，即到边界的距离，在图形外时为负
+    fn depth(&self, point: Point) -> f64 {
+        let distance = self
+            .edges
+            .iter()
+            .map(|(from, to, _)| point.distance(closest_point(point, *from, *to)))
+            .fold(f64::INFINITY, f64::min);
+        if self.contains(point) {
+            self.radius + distance
+        } else {
+            self.radius - distance
+        }
+    }
+
+    /// 两个元素之间的间隙和最近处的位置，重叠时间隙不大于 0，不计算明显大于 `limit` 的间隙
+    ///
+    /// `cleared` 中的清除图形去掉了 `self` 的一部分，位于其中的点不算在 `self` 内部。
+    fn gap(&self, other: &Element, limit: f64, cleared: &[&Feature]) -> (f64, Point) {
+        let point = other.points[0];
+        if self.contains(point) && !cleared.iter().any(|clear| clear.covers(point)) {
+            return (-1.0, point);
+        }
+        if other.contains(self.points[0]) {
+            return (-1.0, self.points[0]);
+        }
+        // 跳过相距较远的边，铺铜等有很多条边的图形只计算附近的边
+        let margin = limit + self.radius + other.radius;
+        let mut best = (f64::INFINITY, Point::default());
+        for (a0, a1, a) in &self.edges {
+            let near = a.expand(margin);
+            if !near.overlaps(&other.bounds) {
+                continue;
+            }
+            for (b0, b1, b) in &other.edges {
+                if !near.overlaps(b) {
+                    continue;
+                }
+                let (distance, at) = segment_distance(*a0, *a1, *b0, *b1);
+                if distance < best.0 {
+                    best = (distance, at);
+                }
+            }
+        }
+        (best.0 - self.radius - other.radius, best.1)
+    }
+}
+
+impl Feature {
+    fn new(index: usize, shape: &Shape, layer: &Layer) -> Option<Feature> {
+        let mut elements = vec![];
+        match shape {
+            Shape::Draw { from, segment, aperture } => {
+                let aperture = layer.apertures.get(aperture)?;
+                let radius = match aperture.diameter() {
+                    Some(diameter) => diameter / 2.0,
+                    None => aperture.bounds().map(|bounds| bounds.width().min(bounds.height()) / 2.0)?,
+                };
+                if radius <= 0.0 {
+                    return None;
+                }
+                let points = flatten(*from, *segment);
+                for pair in points.windows(2) {
+                    elements.push(Element::new(pair.to_vec(), radius));
+                }
+            }
+            Shape::Flash { at, aperture } => {
+                for (_, primitive) in layer.apertures.get(aperture)?.shapes.iter().filter(|(exposure, _)| *exposure) {
+                    let offset = |point: Point| Point::new(point.x + at.x, point.y + at.y);
+                    elements.push(match primitive {
+                        Primitive::Circle { center, diameter } => Element::new(vec![offset(*center)], diameter / 2.0),
+                        Primitive::Rect { center, width, height, radius } => {
+                            // 圆角矩形是内缩的矩形向外扩展圆角半径
+                            let radius = radius.min(width / 2.0).min(height / 2.0);
+                            let (w, h) = (width / 2.0 - radius, height / 2.0 - radius);
+                            let c = offset(*center);
+                            let corners = vec![
+                                Point::new(c.x - w, c.y - h),
+                                Point::new(c.x + w, c.y - h),
+                                Point::new(c.x + w, c.y + h),
+                                Point::new(c.x - w, c.y + h),
+                            ];
+                            Element::new(corners, radius)
+                        }
+                        Primitive::Polygon(points) if !points.is_empty() => {
+                            Element::new(points.iter().map(|point| offset(*point)).collect(), 0.0)
+                        }
+                        Primitive::Polygon(_) => continue,
+                    });
+                }
+            }
+            Shape::Region(contours) => {
+                for contour in contours {
+                    let mut points = vec![contour.start];
+                    for segment in &contour.segments {
+                        let from = *points.last().unwrap();
+                        points.extend(flatten(from, *segment).into_iter().skip(1));
+                    }
+                    if points.len() > 1 && points[0].distance(*points.last().unwrap()) < 1e-9 {
+                        points.pop();
+                    }
+                    elements.push(Element::new(points, 0.0));
+                }
+            }
+        }
+
+        let bounds = elements.iter().map(|element| element.bounds).reduce(|a, b| a.union(&b))?;
+        Some(Feature { index, bounds, elements })
+    }
+
+    fn covers(&self, point: Point) -> bool {
+        self.elements.iter().any(|element| element.depth(point) > 0.0)
+    }
+
+    /// 与之后画出的图形 `other` 的间隙，明显大于 `limit` 时返回 `None`
+    ///
+    /// `cleared` 是在两者之间画出的清除图形，`other` 位于其中时，间隙是到清除区域边界的距离。
+    fn gap(&self, other: &Feature, limit: f64, cleared: &[&Feature]) -> Option<(f64, Point)> {
+        let mut best: Option<(f64, Point)> = None;
+        let mut candidates = vec![];
+        for a in &self.elements {
+            for b in &other.elements {
+                if a.bounds.expand(limit).overlaps(&b.bounds) {
+                    candidates.push(a.gap(b, limit, cleared));
+                }
+            }
+        }
+        for b in &other.elements {
+            // 各个顶点在清除区域中最深的深度，取最浅的顶点；顶点都不在清除区域中时与清除无关
+            let depths: Vec<(f64, Point)> = b
+                .points
+                .iter()
+                .map(|point| {
+                    let depth = cleared
+                        .iter()
+                        .flat_map(|clear| &clear.elements)
+                        .map(|clear| clear.depth(*point))
+                        .fold(f64::NEG_INFINITY, f64::max);
+                    (depth, *point)
+                })
+                .collect();
+            if depths.iter().any(|(depth, _)| *depth > 0.0) {
+                let (depth, at) = depths.into_iter().min_by(|a, b| a.0.total_cmp(&b.0)).unwrap();
+                candidates.push((depth - b.radius, at));
+            }
+        }
+        for (gap, at) in candidates {
+            if gap <= 0.0 {
+                return Some((gap, at));
+            }
+            if best.is_none_or(|(best, _)| gap < best) {
+                best = Some((gap, at));
+            }
+        }
+        best
+    }
+}
+
+/// 线段或圆弧展开为折线，包括起点
+fn flatten(from: Point, segment: Segment) -> Vec<Point> {
+    match segment {
+        Segment::Line(to) => vec![from, to],
+        Segment::Arc { to, center, clockwise } => {
+            let radius = center.distance(from);
+            let (start, sweep) = arc_sweep(from, to, center, clockwise);
+            let steps = ((radius * sweep.abs() / ARC_STEP).ceil() as usize).clamp(1, 1024);
+            (0..=steps)
+                .map(|step| {
+                    let angle = start + sweep * step as f64 / steps as f64;
+                    Point::new(center.x + radius * angle.cos(), center.y + radius * angle.sin())
+                })
+                .collect()
+        }
+    }
+}
+
+fn polygon_contains(polygon: &[Point], point: Point) -> bool {
+    let mut inside = false;
+    let mut j = polygon.len() - 1;
+    for i in 0..polygon.len() {
+        let (a, b) = (polygon[i], polygon[j]);
+        if (a.y > point.y) != (b.y > point.y) && point.x < (b.x - a.x) * (point.y - a.y) / (b.y - a.y) + a.x {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+/// 点到线段最近的点
+fn closest_point(point: Point, from: Point, to: Point) -> Point {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    let length = dx * dx + dy * dy;
+    if length < 1e-18 {
+        return from;
+    }
+    let t = (((point.x - from.x) * dx + (point.y - from.y) * dy) / length).clamp(0.0, 1.0);
+    Point::new(from.x + t * dx, from.y + t * dy)
+}
+
+/// 两条线段之间的距离和最近处的中点
+fn segment_distance(a0: Point, a1: Point, b0: Point, b1: Point) -> (f64, Point) {
+    let cross = |o: Point, p: Point, q: Point| (p.x - o.x) * (q.y - o.y) - (p.y - o.y) * (q.x - o.x);
+    let (d1, d2) = (cross(a0, a1, b0), cross(a0, a1, b1));
+    let (d3, d4) = (cross(b0, b1, a0), cross(b0, b1, a1));
+    if d1 * d2 < 0.0 && d3 * d4 < 0.0 {
+        let t = d1 / (d1 - d2);
+        return (0.0, Point::new(b0.x + t * (b1.x - b0.x), b0.y + t * (b1.y - b0.y)));
+    }
+    [(a0, b0, b1), (a1, b0, b1), (b0, a0, a1), (b1, a0, a1)]
+        .into_iter()
+        .map(|(point, from, to)| {
+            let closest = closest_point(point, from, to);
+            (point.distance(closest), Point::new((point.x + closest.x) / 2.0, (point.y + closest.y) / 2.0))
+        })
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{excellon, gerber};
+
+    const HEADER: &str = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10C,0.08*%\n%ADD11C,0.2*%\n%ADD12R,1.0X1.0*%\n%ADD13C,0.6*%\nG01*\n";
+
+    fn layer(body: &str) -> Layer {
+        gerber::parse(&format!("{}{}M02*\n", HEADER, body)).unwrap()
+    }
+
+    #[test]
+    fn checks_trace_and_space() {
+        // 0.08 mm 的走线，两条 0.2 mm 的走线相距 0.1 mm（间隙 0.1 - 0.2 = -0.1，重叠）和 0.3 mm（间隙 0.1 mm），
+        // 一个方形焊盘与最后一条走线相接
+        let copper = layer(
+            "D10*\nX0Y0D02*\nX5000000Y0D01*\n\
+             D11*\nX0Y2000000D02*\nX5000000Y2000000D01*\nX0Y2100000D02*\nX5000000Y2100000D01*\nX0Y2400000D02*\nX5000000Y2400000D01*\n\
+             D12*\nX5500000Y2900000D03*\n",
+        );
+        let issues = check_copper("Gerber_TopLayer.GTL", &copper, 0.127, 0.127);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(
+            issues[0],
+            PreflightIssue::Below {
+                rule: Rule::Trace,
+                file: "Gerber_TopLayer.GTL".to_string(),
+                count: 1,
+                worst: 0.08,
+                limit: 0.127,
+                at: Point::new(0.0, 0.0),
+            }
+        );
+        let PreflightIssue::Below { rule: Rule::Space, count, worst, at, .. } = issues[1] else {
+            panic!("expected a spacing issue, got {:?}", issues[1]);
+        };
+        assert_eq!(count, 1);
+        assert!((worst - 0.1).abs() < 1e-9);
+        assert!((at.y - 2.25).abs() < 1e-9);
+        assert_eq!(
+            issues[0].to_string(),
+            "Gerber_TopLayer.GTL: 1 trace(s) narrower than 0.127 mm, narrowest 0.080 mm at (0.000, 0.000)"
+        );
+        assert!(check_copper("Gerber_TopLayer.GTL", &copper, 0.08, 0.09).is_empty());
+    }
+
+    #[test]
+    fn ignores_gaps_within_one_conductor() {
+        // 0.2 mm 的走线在拐角处倒角，第一段和第三段相距 0.1 mm，但通过第二段相连
+        let copper = layer("D11*\nX0Y0D02*\nX1000000Y0D01*\nX1212132Y212132D01*\nY2000000D01*\n");
+        assert!(check_copper("Gerber_TopLayer.GTL", &copper, 0.127, 0.127).is_empty());
+    }
+
+    #[test]
+    fn cleared_copper_does_not_connect_pads() {
+        // 10 x 10 mm 的铺铜中清除出 1 mm 宽的通道，其中 0.4 mm 的焊盘与 0.2 mm 的走线相距 0.1 mm，
+        // 焊盘到铺铜 0.3 mm，走线到铺铜 0.4 mm
+        let copper = layer(
+            "%ADD14C,0.4*%\n%ADD15C,1.0*%\n\
+             G36*\nX0Y0D02*\nX10000000Y0D01*\nX10000000Y10000000D01*\nX0Y10000000D01*\nX0Y0D01*\nG37*\n\
+             %LPC*%\nD15*\nX2000000Y5000000D02*\nX8000000Y5000000D01*\n\
+             %LPD*%\nD14*\nX3000000Y5000000D03*\nD11*\nX3400000Y5000000D02*\nX8000000Y5000000D01*\n",
+        );
+        let issues = check_copper("Gerber_TopLayer.GTL", &copper, 0.127, 0.127);
+        let [PreflightIssue::Below { rule: Rule::Space, count: 1, worst, at, .. }] = issues[..] else {
+            panic!("expected one spacing issue, got {:?}", issues);
+        };
+        assert!((worst - 0.1).abs() < 1e-9);
+        assert!(at.distance(Point::new(3.2, 5.0)) < 1e-9);
+
+        let issues = check_copper("Gerber_TopLayer.GTL", &copper, 0.127, 0.35);
+        let [PreflightIssue::Below { rule: Rule::Space, count: 2, .. }] = issues[..] else {
+            panic!("expected two spacing issues, got {:?}", issues);
+        };
+    }
+
+    #[test]
+    fn checks_drills_rings_and_size() {
+        let drill = excellon::parse("M48\nMETRIC\nT1C0.2\nT2C0.4\n%\nT1\nX1.0Y1.0\nT2\nX3.0Y1.0\nX6.0Y1.0\nM30\n").unwrap();
+        let issues = check_drill("Drill_PTH_Through.DRL", &drill, 0.3);
+        assert!(matches!(issues[..], [PreflightIssue::Below { count: 1, worst: 0.2, .. }]));
+
+        // 没有定义直径的刀具不算作小孔
+        let undefined = excellon::parse("M48\nMETRIC\nT1C0.4\n%\nT1\nX1.0Y1.0\nT3\nX2.0Y1.0\nX3.0Y1.0\nM30\n").unwrap();
+        let issues = check_drill("Drill_PTH_Through.DRL", &undefined, 0.3);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].to_string(), "Drill_PTH_Through.DRL: 2 hole(s) use undefined tool(s) T3");
+        assert_eq!(check_annular_ring("Drill_PTH_Through.DRL", &undefined, &[&layer("D13*\nX2000000Y1000000D03*\n")], 0.15), None);
+
+        // 0.6 mm 的焊盘：0.2 mm 孔的焊环 0.2 mm，0.4 mm 孔的焊环 0.1 mm；(6, 1) 处没有焊盘
+        let copper = layer("D13*\nX1000000Y1000000D03*\nX3000000Y1000000D03*\n");
+        let issue = check_annular_ring("Drill_PTH_Through.DRL", &drill, &[&copper], 0.15).unwrap();
+        let PreflightIssue::Below { count, worst, at, .. } = issue else {
+            panic!("expected an annular ring issue");
+        };
+        assert_eq!((count, at), (1, Point::new(3.0, 1.0)));
+        assert!((worst - 0.1).abs() < 1e-9);
+
+        let standard = Profile::builtin("JLC-Standard").unwrap();
+        assert_eq!(check_board_size(480.0, 390.0, &standard), None);
+        assert!(check_board_size(480.0, 410.0, &standard).unwrap().to_string().contains("400 x 500 mm"));
+        assert!(Profile::builtin("unknown").is_none());
+    }
+}
@@ -0,0 +1,622 @@
+// SPDX-FileCopyrightText: 2025 HalfSweet
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::excellon;
+use crate::gerber::{self, arc_sweep, Aperture, Bounds, Contour, Layer, Point, Primitive, Segment, Shape};
+use crate::outline;
+
+/// 合成视图中板子四周留出的空白（毫米）
+const MARGIN: f64 = 1.0;
+
+/// PNG 图像每一边的最大像素数
+const MAX_PIXELS: u32 = 16384;
+
+/// 渲染时使用的颜色，可以是任意SVG颜色值
+#[derive(Debug, Clone, PartialEq)]
+pub struct Colors {
+    pub board: String,
+    pub copper: String,
+
+    /// 阻焊开窗处露出的铜（表面处理）
+    pub finish: String,
+    pub mask: String,
+    pub silk: String,
+    pub paste: String,
+    pub outline: String,
+    pub drill: String,
+}
+
+impl Default for Colors {
+    fn default() -> Self {
+        Colors {
+            board: "#6b5d2f".to_string(),
+            copper: "#b87333".to_string(),
+            finish: "#d4af37".to_string(),
+            mask: "#0b6623".to_string(),
+            silk: "#f5f5f5".to_string(),
+            paste: "#a0a0a0".to_string(),
+            outline: "#1a1a1a".to_string(),
+            drill: "#000000".to_string(),
+        }
+    }
+}
+
+impl Colors {
+    /// 名称列表，用于 `--color` 的帮助信息和错误提示
+    pub const NAMES: [&'static str; 8] = ["board", "copper", "finish", "mask", "silk", "paste", "outline", "drill"];
+
+    /// 设置一种颜色，例如 `mask=#1a3d8f`
+    pub fn set(&mut self, assignment: &str) -> Result<(), std::io::Error> {
+        let invalid = |message: String| std::io::Error::new(std::io::ErrorKind::InvalidInput, message);
+        let (name, value) = assignment
+            .split_once('=')
+            .ok_or_else(|| invalid(format!("Invalid color '{}', expected <name>=<color>", assignment)))?;
+        let value = value.trim();
+        if value.is_empty() || value.contains(['"', '<', '>', '&']) {
+            return Err(invalid(format!("Invalid color value '{}'", value)));
+        }
+        let field = match name.trim().to_lowercase().as_str() {
+            "board" => &mut self.board,
+            "copper" => &mut self.copper,
+            "finish" => &mut self.finish,
+            "mask" => &mut self.mask,
+            "silk" => &mut self.silk,
+            "paste" => &mut self.paste,
+            "outline" => &mut self.outline,
+            "drill" => &mut self.drill,
+            _ => {
+                return Err(invalid(format!(
+                    "Unknown color '{}', expected one of: {}",
+                    name,
+                    Colors::NAMES.join(", ")
+                )))
+            }
+        };
+        *field = value.to_string();
+        Ok(())
+    }
+
+    /// 单独渲染某一层时使用的颜色
+    pub fn for_key(&self, key: &str) -> &str {
+        match key {
+            "Top_Layer" | "Bottom_Layer" | "InnerLayer" => &self.copper,
+            "Top_Soldermask" | "Bottom_Soldermask" => &self.mask,
+            "Top_Silkscreen" | "Bottom_Silkscreen" => &self.silk,
+            "Top_PasteMask" | "Bottom_PasteMask" => &self.paste,
+            "Board_Outline" => &self.outline,
+            _ => &self.drill,
+        }
+    }
+}
+
+/// 合成视图的一面
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Top,
+    Bottom,
+}
+
+impl Side {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Side::Top => "top",
+            Side::Bottom => "bottom",
+        }
+    }
+}
+
+/// 钻孔层的键名
+pub fn is_drill(key: &str) -> bool {
+    matches!(key, "NPTH_Through" | "PTH_Through" | "PTH_Through_Via")
+}
+
+/// 读取一个JLC层文件，钻孔文件转换为Gerber层
+pub fn load_layer(path: &Path, key: &str) -> Result<Layer, std::io::Error> {
+    let content = std::fs::read_to_string(path)?;
+    if is_drill(key) {
+        Ok(excellon::parse(&content)?.to_layer())
+    } else {
+        gerber::parse(&content)
+    }
+}
+
+/// 把单独一层渲染为SVG
+pub fn layer_svg(layer: &Layer, color: &str) -> String {
+    layer_svg_in(layer, color, common_area([layer]))
+}
+
+/// 包含所有层的显示范围（含四周空白），用于渲染可以互相叠放的单层SVG
+pub fn common_area<'a>(layers: impl IntoIterator<Item = &'a Layer>) -> Bounds {
+    layers
+        .into_iter()
+        .filter_map(Layer::bounds)
+        .reduce(|a, b| a.union(&b))
+        .unwrap_or(Bounds::point(0.0, 0.0))
+        .expand(MARGIN)
+}
+
+/// 在指定的显示范围内渲染单独一层
+pub fn layer_svg_in(layer: &Layer, color: &str, area: Bounds) -> String {
+    let mut svg = Svg::new(area);
+    let body = svg.layer(layer, "l");
+    svg.finish(&format!("<g color=\"{}\">{}</g>", color, body))
+}
+
+/// 把一面的各层叠加渲染为SVG
+///
+/// `layers` 的键为JLC层名称（例如 `Top_Layer`、`PTH_Through`）。板子形状取自 `Board_Outline`
+/// 中闭合的轮廓，没有时使用所有层的范围。底面视图左右镜像，与从底面看到的一致。
+pub fn board_svg(layers: &BTreeMap<String, Layer>, side: Side, colors: &Colors) -> String {
+    let (copper, mask, silk) = match side {
+        Side::Top => ("Top_Layer", "Top_Soldermask", "Top_Silkscreen"),
+        Side::Bottom => ("Bottom_Layer", "Bottom_Soldermask", "Bottom_Silkscreen"),
+    };
+    let outline = layers.get("Board_Outline");
+
+    let bounds = outline
+        .and_then(Layer::path_bounds)
+        .or_else(|| layers.values().filter_map(Layer::bounds).reduce(|a, b| a.union(&b)))
+        .unwrap_or(Bounds::point(0.0, 0.0));
+    let area = bounds.expand(MARGIN);
+    let mut svg = Svg::new(area);
+
+    let contours = outline
+        .map(|layer| gerber::chain_contours(layer, outline::DEFAULT_TOLERANCE).0)
+        .unwrap_or_default();
+    let board_path = if contours.is_empty() {
+        rect_path(&bounds)
+    } else {
+        contours.iter().map(contour_path).collect::<String>()
+    };
+
+    // 钻孔从整块板子上挖掉
+    let mut holes = String::new();
+    for key in ["PTH_Through", "NPTH_Through", "PTH_Through_Via"] {
+        if let Some(layer) = layers.get(key) {
+            holes.push_str(&svg.layer(layer, key));
+        }
+    }
+    let board_mask = svg.id("board");
+    let mask_area = svg.mask_area();
+    let _ = write!(
+        svg.defs,
+        "<mask id=\"{}\" {}><path d=\"{}\" fill=\"#fff\" fill-rule=\"evenodd\"/><g color=\"#000\">{}</g></mask>",
+        board_mask,
+        mask_area,
+        board_path,
+        holes
+    );
+
+    let mut body = format!("<rect {} fill=\"{}\"/>", area_attributes(&area), colors.board);
+    if let Some(layer) = layers.get(copper) {
+        let copper_id = svg.id("copper");
+        let markup = svg.layer(layer, copper);
+        let _ = write!(svg.defs, "<g id=\"{}\">{}</g>", copper_id, markup);
+        let _ = write!(body, "<use href=\"#{}\" color=\"{}\"/>", copper_id, colors.copper);
+
+        // 阻焊层中绘制的是开窗，开窗处露出的铜使用表面处理的颜色
+        if let Some(layer) = layers.get(mask) {
+            let openings = svg.layer(layer, mask);
+            let (covered, exposed) = (svg.id("mask"), svg.id("open"));
+            let _ = write!(
+                svg.defs,
+                "<mask id=\"{0}\" {2}><rect {3} fill=\"#fff\"/><g color=\"#000\">{4}</g></mask><mask id=\"{1}\" {2}><rect {3} fill=\"#000\"/><g color=\"#fff\">{4}</g></mask>",
+                covered,
+                exposed,
+                mask_area,
+                area_attributes(&area),
+                openings
+            );
+            let _ = write!(
+                body,
+                "<rect {} fill=\"{}\" opacity=\"0.85\" mask=\"url(#{})\"/><g mask=\"url(#{})\"><use href=\"#{}\" color=\"{}\"/></g>",
+                area_attributes(&area),
+                colors.mask,
+                covered,
+                exposed,
+                copper_id,
+                colors.finish
+            );
+        }
+    } else if layers.contains_key(mask) {
+        let _ = write!(body, "<rect {} fill=\"{}\" opacity=\"0.85\"/>", area_attributes(&area), colors.mask);
+    }
+    if let Some(layer) = layers.get(silk) {
+        let markup = svg.layer(layer, silk);
+        let _ = write!(body, "<g color=\"{}\">{}</g>", colors.silk, markup);
+    }
+
+    let mut content = format!("<g mask=\"url(#{})\">{}</g>", board_mask, body);
+    if let Some(layer) = outline {
+        let markup = svg.layer(layer, "outline");
+        let _ = write!(content, "<g color=\"{}\">{}</g>", colors.outline, markup);
+    }
+    if side == Side::Bottom {
+        content = format!(
+            "<g transform=\"translate({} 0) scale(-1 1)\">{}</g>",
+            num(bounds.min_x + bounds.max_x),
+            content
+        );
+    }
+    svg.finish(&content)
+}
+
+/// 把渲染出的SVG光栅化为PNG
+///
+/// `dpi` 为每英寸的像素数，图像尺寸由SVG中以毫米为单位的宽高决定。
+pub fn svg_to_png(svg: &str, dpi: f64) -> Result<Vec<u8>, std::io::Error> {
+    let invalid = |msg: String| std::io::Error::new(std::io::ErrorKind::InvalidData, msg);
+    let tree = resvg::usvg::Tree::from_str(svg, &resvg::usvg::Options::default())
+        .map_err(|e| invalid(format!("Invalid SVG: {}", e)))?;
+
+    // usvg 按 96 DPI 把毫米换算为像素
+    let scale = (dpi / 96.0) as f32;
+    let size = tree.size();
+    let width = (size.width() * scale).ceil() as u32;
+    let height = (size.height() * scale).ceil() as u32;
+    if width > MAX_PIXELS || height > MAX_PIXELS {
+        return Err(invalid(format!(
+            "Image of {}x{} pixels is too large, use a lower DPI",
+            width, height
+        )));
+    }
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(width.max(1), height.max(1))
+        .ok_or_else(|| invalid(format!("Cannot create an image of {}x{} pixels", width, height)))?;
+    resvg::render(&tree, resvg::tiny_skia::Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+    pixmap.encode_png().map_err(|e| invalid(format!("Failed to encode PNG: {}", e)))
+}
+
+/// SVG 文档，坐标使用毫米，Y 轴向上
+struct Svg {
+    area: Bounds,
+    defs: String,
+    next_id: usize,
+}
+
+impl Svg {
+    fn new(area: Bounds) -> Svg {
+        Svg {
+            area,
+            defs: String::new(),
+            next_id: 0,
+        }
+    }
+
+    fn id(&mut self, kind: &str) -> String {
+        self.next_id += 1;
+        format!("{}{}", kind.to_lowercase().replace('_', "-"), self.next_id)
+    }
+
+    fn mask_area(&self) -> String {
+        format!("maskUnits=\"userSpaceOnUse\" {}", area_attributes(&self.area))
+    }
+
+    fn finish(self, content: &str) -> String {
+        let area = self.area;
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\" width=\"{}mm\" height=\"{}mm\">\n<defs>{}</defs>\n<g transform=\"scale(1 -1)\">{}</g>\n</svg>\n",
+            num(area.min_x),
+            num(-area.max_y),
+            num(area.width()),
+            num(area.height()),
+            num(area.width()),
+            num(area.height()),
+            self.defs,
+            content
+        )
+    }
+
+    /// 一层的图形，使用 `currentColor` 着色；清除极性的图形通过遮罩从之前的图形中挖掉
+    fn layer(&mut self, layer: &Layer, kind: &str) -> String {
+        let prefix = self.id(kind);
+        let mut apertures = BTreeMap::new();
+        for (number, aperture) in &layer.apertures {
+            let id = format!("{}-d{}", prefix, number);
+            let markup = self.aperture(aperture);
+            let _ = write!(self.defs, "<g id=\"{}\">{}</g>", id, markup);
+            apertures.insert(*number, id);
+        }
+
+        // 负片先铺满整个区域，之后所有图形的极性反转
+        let mut runs: Vec<(bool, String)> = vec![];
+        if layer.negative {
+            runs.push((true, format!("<rect {} fill=\"currentColor\"/>", area_attributes(&self.area))));
+        }
+        for object in &layer.objects {
+            let dark = object.dark != layer.negative;
+            let markup = shape_markup(&object.shape, layer, &apertures);
+            match runs.last_mut() {
+                Some((run_dark, run)) if *run_dark == dark => run.push_str(&markup),
+                _ => runs.push((dark, markup)),
+            }
+        }
+        self.compose(runs)
+    }
+
+    fn aperture(&mut self, aperture: &Aperture) -> String {
+        let mut runs: Vec<(bool, String)> = vec![];
+        for (exposure, primitive) in &aperture.shapes {
+            let markup = primitive_markup(primitive);
+            match runs.last_mut() {
+                Some((dark, run)) if dark == exposure => run.push_str(&markup),
+                _ => runs.push((*exposure, markup)),
+            }
+        }
+        self.compose(runs)
+    }
+
+    /// 依次叠加添加和清除的图形
+    fn compose(&mut self, runs: Vec<(bool, String)>) -> String {
+        let mut body = String::new();
+        for (dark, markup) in runs {
+            if dark {
+                body.push_str(&markup);
+            } else if !body.is_empty() {
+                let id = self.id("clear");
+                let mask_area = self.mask_area();
+                let _ = write!(
+                    self.defs,
+                    "<mask id=\"{}\" {}><rect {} fill=\"#fff\"/><g color=\"#000\">{}</g></mask>",
+                    id,
+                    mask_area,
+                    area_attributes(&self.area),
+                    markup
+                );
+                body = format!("<g mask=\"url(#{})\">{}</g>", id, body);
+            }
+        }
+        body
+    }
+}
+
+fn shape_markup(shape: &Shape, layer: &Layer, apertures: &BTreeMap<u32, String>) -> String {
+    match shape {
+        Shape::Flash { at, aperture } => format!(
+            "<use href=\"#{}\" x=\"{}\" y=\"{}\"/>",
+            apertures[aperture],
+            num(at.x),
+            num(at.y)
+        ),
+        Shape::Draw {
+            from,
+            segment,
+            aperture,
+        } => {
+            let aperture = &layer.apertures[aperture];
+            match (aperture.diameter(), segment) {
+                (Some(width), _) => stroke(*from, segment, width, "round"),
+                // 非圆形孔径画直线时，图形为孔径在两端之间扫过的凸包
+                (None, Segment::Line(to)) => {
+                    let bounds = aperture.bounds().unwrap_or(Bounds::point(0.0, 0.0));
+                    let mut points = vec![];
+                    for at in [*from, *to] {
+                        for (x, y) in [
+                            (bounds.min_x, bounds.min_y),
+                            (bounds.max_x, bounds.min_y),
+                            (bounds.max_x, bounds.max_y),
+                            (bounds.min_x, bounds.max_y),
+                        ] {
+                            points.push(Point::new(at.x + x, at.y + y));
+                        }
+                    }
+                    primitive_markup(&Primitive::Polygon(convex_hull(points)))
+                }
+                (None, _) => {
+                    let width = aperture.bounds().map(|b| b.width().min(b.height())).unwrap_or(0.0);
+                    stroke(*from, segment, width, "square")
+                }
+            }
+        }
+        Shape::Region(contours) => format!(
+            "<path d=\"{}\" fill=\"currentColor\"/>",
+            contours.iter().map(contour_path).collect::<String>()
+        ),
+    }
+}
+
+fn stroke(from: Point, segment: &Segment, width: f64, cap: &str) -> String {
+    format!(
+        "<path d=\"M{} {}{}\" fill=\"none\" stroke=\"currentColor\" stroke-width=\"{}\" stroke-linecap=\"{}\" stroke-linejoin=\"round\"/>",
+        num(from.x),
+        num(from.y),
+        segment_path(from, segment),
+        num(width),
+        cap
+    )
+}
+
+fn primitive_markup(primitive: &Primitive) -> String {
+    match primitive {
+        Primitive::Circle { center, diameter } => format!(
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"currentColor\"/>",
+            num(center.x),
+            num(center.y),
+            num(diameter / 2.0)
+        ),
+        Primitive::Rect {
+            center,
+            width,
+            height,
+            radius,
+        } => format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\" fill=\"currentColor\"/>",
+            num(center.x - width / 2.0),
+            num(center.y - height / 2.0),
+            num(*width),
+            num(*height),
+            num(*radius)
+        ),
+        Primitive::Polygon(points) => format!(
+            "<polygon points=\"{}\" fill=\"currentColor\"/>",
+            points
+                .iter()
+                .map(|point| format!("{},{}", num(point.x), num(point.y)))
+                .collect::<Vec<_>>()
+                .join(" ")
+        ),
+    }
+}
+
+fn contour_path(contour: &Contour) -> String {
+    let mut path = format!("M{} {}", num(contour.start.x), num(contour.start.y));
+    let mut from = contour.start;
+    for segment in &contour.segments {
+        path.push_str(&segment_path(from, segment));
+        from = segment.end();
+    }
+    path.push('Z');
+    path
+}
+
+/// 线段或圆弧的路径命令；整圆拆成两段，SVG 的圆弧命令无法表示起点与终点重合的圆弧
+fn segment_path(from: Point, segment: &Segment) -> String {
+    match *segment {
+        Segment::Line(to) => format!("L{} {}", num(to.x), num(to.y)),
+        Segment::Arc { to, center, clockwise } => {
+            let radius = center.distance(from);
+            let (start, sweep) = arc_sweep(from, to, center, clockwise);
+            let sweep_flag = if clockwise { 0 } else { 1 };
+            let mut path = String::new();
+            let steps = if sweep.abs() > std::f64::consts::PI * 1.5 { 2 } else { 1 };
+            for step in 1..=steps {
+                let angle = start + sweep * step as f64 / steps as f64;
+                let end = if step == steps {
+                    to
+                } else {
+                    Point::new(center.x + radius * angle.cos(), center.y + radius * angle.sin())
+                };
+                let large = if sweep.abs() / steps as f64 > std::f64::consts::PI { 1 } else { 0 };
+                let _ = write!(
+                    path,
+                    "A{} {} 0 {} {} {} {}",
+                    num(radius),
+                    num(radius),
+                    large,
+                    sweep_flag,
+                    num(end.x),
+                    num(end.y)
+                );
+            }
+            path
+        }
+    }
+}
+
+fn rect_path(bounds: &Bounds) -> String {
+    format!(
+        "M{0} {1}L{2} {1}L{2} {3}L{0} {3}Z",
+        num(bounds.min_x),
+        num(bounds.min_y),
+        num(bounds.max_x),
+        num(bounds.max_y)
+    )
+}
+
+fn area_attributes(area: &Bounds) -> String {
+    format!(
+        "x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"",
+        num(area.min_x),
+        num(area.min_y),
+        num(area.width()),
+        num(area.height())
+    )
+}
+
+/// 按逆时针顺序返回点集的凸包
+fn convex_hull(mut points: Vec<Point>) -> Vec<Point> {
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+    let cross = |o: Point, a: Point, b: Point| (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x);
+    let mut hull: Vec<Point> = vec![];
+    for pass in 0..2 {
+        let start = hull.len();
+        let iter: Box<dyn Iterator<Item = &Point>> = if pass == 0 {
+            Box::new(points.iter())
+        } else {
+            Box::new(points.iter().rev())
+        };
+        for point in iter {
+            while hull.len() >= start + 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], *point) <= 0.0 {
+                hull.pop();
+            }
+            hull.push(*point);
+        }
+        hull.pop();
+    }
+    hull
+}
+
+/// SVG 中的数字，最多保留 4 位小数
+fn num(value: f64) -> String {
+    let text = format!("{:.4}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COPPER: &str = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10C,0.250000*%\n%ADD11R,1.000000X2.000000*%\nD10*\nX0Y0D02*\nG01X10000000Y0D01*\nG75*\nG03X0Y0I-5000000J0D01*\nD11*\nX5000000Y5000000D03*\n%LPC*%\nG01*\nG36*\nX1000000Y1000000D02*\nX2000000Y1000000D01*\nX2000000Y2000000D01*\nX1000000Y1000000D01*\nG37*\nM02*\n";
+
+    #[test]
+    fn renders_layer_with_clear_polarity() {
+        let layer = gerber::parse(COPPER).unwrap();
+        let svg = layer_svg(&layer, "#b87333");
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.contains("stroke-width=\"0.25\""));
+        assert!(svg.contains("<rect x=\"-0.5\" y=\"-1\" width=\"1\" height=\"2\" rx=\"0\" fill=\"currentColor\"/>"));
+        // 清除极性的区域放在遮罩中
+        assert!(svg.contains("<mask id=\"clear"));
+        assert!(svg.contains("M1 1L2 1L2 2L1 1Z"));
+    }
+
+    #[test]
+    fn renders_board_side_with_outline_shape() {
+        let outline = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10C,0.1*%\nD10*\nX0Y0D02*\nX20000000Y0D01*\nX20000000Y10000000D01*\nX0Y10000000D01*\nX0Y0D01*\nM02*\n";
+        let drill = "M48\nMETRIC\nT1C0.8\n%\nT1\nX5.0Y5.0\nM30\n";
+        let mut layers = BTreeMap::new();
+        layers.insert("Board_Outline".to_string(), gerber::parse(outline).unwrap());
+        layers.insert("Top_Layer".to_string(), gerber::parse(COPPER).unwrap());
+        layers.insert("PTH_Through".to_string(), excellon::parse(drill).unwrap().to_layer());
+
+        let top = board_svg(&layers, Side::Top, &Colors::default());
+        assert!(top.contains("viewBox=\"-1 -11 22 12\""));
+        assert!(top.contains("M0 0L20 0L20 10L0 10L0 0Z"));
+        assert!(top.contains("<use href=\"#pth-through"));
+
+        let bottom = board_svg(&layers, Side::Bottom, &Colors::default());
+        assert!(bottom.contains("translate(20 0) scale(-1 1)"));
+
+        // 254 DPI 时每毫米 10 像素
+        let png = svg_to_png(&top, 254.0).unwrap();
+        let pixmap = resvg::tiny_skia::Pixmap::decode_png(&png).unwrap();
+        assert_eq!((pixmap.width(), pixmap.height()), (220, 120));
+        let alpha = |x, y| pixmap.pixel(x, y).unwrap().alpha();
+        assert_eq!(alpha(0, 0), 0);
+        assert_eq!(alpha(60, 60), 0);
+        assert_eq!(alpha(150, 60), 255);
+        assert!(svg_to_png(&top, 100_000.0).is_err());
+    }
+
+    #[test]
+    fn sets_colors_by_name() {
+        let mut colors = Colors::default();
+        colors.set("mask=#1a3d8f").unwrap();
+        assert_eq!(colors.mask, "#1a3d8f");
+        assert!(colors.set("mask").is_err());
+        assert!(colors.set("glass=#fff").is_err());
+        assert!(colors.set("silk=\"/><script>").is_err());
+    }
+}
@@ -0,0 +1,250 @@
+// SPDX-FileCopyrightText: 2025 HalfSweet
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fmt::Write;
+
+/// 报告的默认文件名
+pub const REPORT_NAME: &str = "report.html";
+
+/// 报告中可以切换显示的一层
+pub struct ReportLayer {
+    /// JLC文件名
+    pub name: String,
+    pub svg: String,
+
+    /// 打开报告时是否显示
+    pub visible: bool,
+}
+
+/// 一个钻孔文件的统计
+pub struct DrillStats {
+    pub file: String,
+
+    /// 每种孔径（毫米）的孔数
+    pub tools: Vec<(f64, usize)>,
+    pub slots: usize,
+}
+
+/// 转换结果的审阅报告，输出为不依赖外部资源的单个HTML文件
+pub struct Report {
+    pub title: String,
+    pub generated: String,
+
+    /// 板子的宽和高（毫米）
+    pub size: Option<(f64, f64)>,
+    pub copper_layers: usize,
+
+    /// 按从底面到顶面的顺序叠放的各层，所有SVG使用相同的显示范围
+    pub layers: Vec<ReportLayer>,
+
+    /// 合成视图，面的名称和SVG
+    pub sides: Vec<(String, String)>,
+
+    /// 原始文件名到JLC文件名
+    pub files: Vec<(String, String)>,
+    pub drills: Vec<DrillStats>,
+    pub warnings: Vec<String>,
+}
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;margin:2em auto;max-width:1200px;padding:0 1em;color:#222}\
+h1{margin-bottom:0}.meta{color:#666;margin-top:.3em}\
+table{border-collapse:collapse;margin:.5em 0}td,th{border:1px solid #ccc;padding:.3em .8em;text-align:left}\
+th{background:#f3f3f3}td.num{text-align:right}\
+.viewer{display:flex;gap:1em;align-items:flex-start}.panel{min-width:16em}.panel label{display:block;white-space:nowrap}\
+.stack{position:relative;flex:1;background:#202020;border-radius:4px}.stack img{position:absolute;left:0;top:0;width:100%;height:100%;opacity:.85}\
+.sides{display:flex;gap:1em;flex-wrap:wrap}.sides figure{flex:1;min-width:20em;margin:0}.sides img{width:100%;background:#fff}\
+.warnings li{color:#8a5a00}";
+
+const SCRIPT: &str = "document.querySelectorAll('.panel input').forEach(function(input){\
+input.addEventListener('change',function(){document.getElementById(input.dataset.layer).style.display=input.checked?'':'none';});});";
+
+impl Report {
+    /// 生成HTML，各层的SVG以 data URI 的形式嵌入
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n<style>{1}</style>\n</head>\n<body>\n<h1>{0}</h1>\n<p class=\"meta\">{2}</p>\n",
+            escape(&self.title),
+            STYLE,
+            escape(&self.generated)
+        );
+
+        html.push_str("<h2>Summary</h2>\n<table>\n");
+        let size = match self.size {
+            Some((width, height)) => format!("{:.2} × {:.2} mm", width, height),
+            None => "unknown (no board outline)".to_string(),
+        };
+        let _ = write!(
+            html,
+            "<tr><th>Board size</th><td>{}</td></tr>\n<tr><th>Copper layers</th><td>{}</td></tr>\n<tr><th>Files</th><td>{}</td></tr>\n<tr><th>Warnings</th><td>{}</td></tr>\n</table>\n",
+            size,
+            self.copper_layers,
+            self.files.len(),
+            self.warnings.len()
+        );
+
+        if !self.layers.is_empty() {
+            html.push_str("<h2>Layers</h2>\n<div class=\"viewer\">\n<div class=\"panel\">\n");
+            for (index, layer) in self.layers.iter().enumerate() {
+                let _ = writeln!(
+                    html,
+                    "<label><input type=\"checkbox\" data-layer=\"layer{}\"{}> {}</label>",
+                    index,
+                    if layer.visible { " checked" } else { "" },
+                    escape(&layer.name)
+                );
+            }
+            let _ = writeln!(html, "</div>\n<div class=\"stack\" style=\"aspect-ratio:{}\">", aspect_ratio(&self.layers[0].svg));
+            for (index, layer) in self.layers.iter().enumerate() {
+                let _ = writeln!(
+                    html,
+                    "<img id=\"layer{}\" alt=\"{}\" src=\"{}\"{}>",
+                    index,
+                    escape(&layer.name),
+                    svg_data_uri(&layer.svg),
+                    if layer.visible { "" } else { " style=\"display:none\"" }
+                );
+            }
+            html.push_str("</div>\n</div>\n");
+        }
+
+        if !self.sides.is_empty() {
+            html.push_str("<div class=\"sides\">\n");
+            for (name, svg) in &self.sides {
+                let _ = writeln!(
+                    html,
+                    "<figure><figcaption>{0}</figcaption><img alt=\"{0}\" src=\"{1}\"></figure>",
+                    escape(name),
+                    svg_data_uri(svg)
+                );
+            }
+            html.push_str("</div>\n");
+        }
+
+        html.push_str("<h2>Files</h2>\n<table>\n<tr><th>Original file</th><th>JLC file</th></tr>\n");
+        for (original, target) in &self.files {
+            let _ = writeln!(html, "<tr><td>{}</td><td>{}</td></tr>", escape(original), escape(target));
+        }
+        html.push_str("</table>\n");
+
+        if !self.drills.is_empty() {
+            html.push_str("<h2>Drills</h2>\n");
+            for drill in &self.drills {
+                let _ = writeln!(
+                    html,
+                    "<h3>{}</h3>\n<table>\n<tr><th>Diameter (mm)</th><th>Count</th></tr>",
+                    escape(&drill.file)
+                );
+                for (diameter, count) in &drill.tools {
+                    let _ = writeln!(html, "<tr><td class=\"num\">{:.3}</td><td class=\"num\">{}</td></tr>", diameter, count);
+                }
+                let total: usize = drill.tools.iter().map(|(_, count)| count).sum();
+                let _ = writeln!(
+                    html,
+                    "<tr><th>Total</th><th class=\"num\">{}</th></tr>\n</table>\n<p>{} tools, {} slots</p>",
+                    total,
+                    drill.tools.len(),
+                    drill.slots
+                );
+            }
+        }
+
+        html.push_str("<h2>Warnings</h2>\n");
+        if self.warnings.is_empty() {
+            html.push_str("<p>No warnings.</p>\n");
+        } else {
+            html.push_str("<ul class=\"warnings\">\n");
+            for warning in &self.warnings {
+                let _ = writeln!(html, "<li>{}</li>", escape(warning));
+            }
+            html.push_str("</ul>\n");
+        }
+
+        let _ = write!(html, "<script>{}</script>\n</body>\n</html>\n", SCRIPT);
+        html
+    }
+}
+
+/// 从SVG的 viewBox 中取出宽高比，用于叠放各层的容器
+fn aspect_ratio(svg: &str) -> String {
+    let view_box = svg
+        .split_once("viewBox=\"")
+        .and_then(|(_, rest)| rest.split_once('"'))
+        .map(|(view_box, _)| view_box)
+        .unwrap_or("0 0 1 1");
+    let values: Vec<&str> = view_box.split_whitespace().collect();
+    match values.as_slice() {
+        [_, _, width, height] => format!("{} / {}", width, height),
+        _ => "1 / 1".to_string(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn svg_data_uri(svg: &str) -> String {
+    format!("data:image/svg+xml;base64,{}", base64(svg.as_bytes()))
+}
+
+fn base64(data: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let value = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(TABLE[(value >> (18 - index * 6) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn writes_self_contained_report() {
+        let svg = "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"-1 -11 22 12\"></svg>";
+        let report = Report {
+            title: "board".to_string(),
+            generated: "TransJLC".to_string(),
+            size: Some((20.0, 10.0)),
+            copper_layers: 2,
+            layers: vec![
+                ReportLayer { name: "Gerber_TopLayer.GTL".to_string(), svg: svg.to_string(), visible: true },
+                ReportLayer { name: "Gerber_TopPasteMaskLayer.GTP".to_string(), svg: svg.to_string(), visible: false },
+            ],
+            sides: vec![("top".to_string(), svg.to_string())],
+            files: vec![("board-F_Cu.gtl".to_string(), "Gerber_TopLayer.GTL".to_string())],
+            drills: vec![DrillStats { file: "Drill_PTH_Through.DRL".to_string(), tools: vec![(0.3, 4), (0.8, 2)], slots: 1 }],
+            warnings: vec!["'<b>' ignored".to_string()],
+        };
+        let html = report.to_html();
+        assert!(html.contains("20.00 × 10.00 mm"));
+        assert!(html.contains("aspect-ratio:22 / 12"));
+        assert!(html.contains("data-layer=\"layer0\" checked"));
+        assert!(html.contains("<img id=\"layer1\" alt=\"Gerber_TopPasteMaskLayer.GTP\" src=\"data:image/svg+xml;base64,"));
+        assert!(html.contains("<td>board-F_Cu.gtl</td><td>Gerber_TopLayer.GTL</td>"));
+        assert!(html.contains("<tr><th>Total</th><th class=\"num\">6</th></tr>"));
+        assert!(html.contains("<li>'&lt;b&gt;' ignored</li>"));
+        assert!(!html.contains("<svg"));
+    }
+}
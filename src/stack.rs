@@ -0,0 +1,186 @@
+// SPDX-FileCopyrightText: 2025 HalfSweet
+// SPDX-License-Identifier: Apache-2.0
+
+use regex::Regex;
+
+/// 从匹配内层规则的文件名中取出内层编号
+///
+/// 规则中有捕获组时使用第一个捕获组，否则使用文件名中的最后一个数字，
+/// 避免工程名称中的数字（例如 `board2-In1_Cu.g2`）被当作层编号。
+pub fn inner_number(re: &Regex, file_name: &str) -> Option<u32> {
+    if let Some(group) = re.captures(file_name).and_then(|caps| caps.get(1)) {
+        return group.as_str().parse().ok();
+    }
+    let number_re = Regex::new(r"\d+").unwrap();
+    number_re.find_iter(file_name).last()?.as_str().parse().ok()
+}
+
+/// 规则匹配到的一个内层文件
+#[derive(Debug, Clone)]
+pub struct InnerSource {
+    pub file: String,
+
+    /// 文件名中的编号，信号层和内电层（例如 Altium 的 `.G1` 和 `.GP1`）分别编号
+    pub number: u32,
+
+    /// 是否为内电层
+    pub plane: bool,
+
+    /// Gerber X2 属性中的物理位置（第几个内层）
+    pub position: Option<u32>,
+}
+
+/// 将内层重新编号为连续的 1..N
+///
+/// 所有文件都有 X2 物理位置时按物理位置排序；否则信号层按编号排在前面，内电层排在后面，
+/// 此时内电层的位置是推断的，返回值的第二项为 `true`。
+/// 返回与 `sources` 顺序一致的新编号；两个文件使用同一个编号时报错。
+pub fn renumber(sources: &[InnerSource]) -> Result<(Vec<u32>, bool), std::io::Error> {
+    let by_position = sources.iter().all(|source| source.position.is_some());
+    let key = |source: &InnerSource| match source.position {
+        Some(position) if by_position => (false, position),
+        _ => (source.plane, source.number),
+    };
+
+    let mut order: Vec<usize> = (0..sources.len()).collect();
+    order.sort_by_key(|index| key(&sources[*index]));
+
+    for pair in order.windows(2) {
+        let (a, b) = (&sources[pair[0]], &sources[pair[1]]);
+        if key(a) == key(b) {
+            let kind = if a.plane && !by_position { "plane layer" } else { "inner layer" };
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Files '{}' and '{}' both map to {} {}", a.file, b.file, kind, key(a).1),
+            ));
+        }
+    }
+
+    let mut numbers = vec![0; sources.len()];
+    for (position, index) in order.into_iter().enumerate() {
+        numbers[index] = position as u32 + 1;
+    }
+    let inferred = !by_position
+        && sources.iter().any(|source| source.plane)
+        && sources.iter().any(|source| !source.plane);
+    Ok((numbers, inferred))
+}
+
+/// 检查铜层叠层：内层编号必须是不重复且连续的 1..N
+///
+/// 返回需要提示的警告，例如有内层却缺少顶层或底层。
+pub fn check_stack(
+    inner: &[(String, u32)],
+    has_top: bool,
+    has_bottom: bool,
+) -> Result<Vec<String>, std::io::Error> {
+    let mut numbers: Vec<u32> = inner.iter().map(|(_, num)| *num).collect();
+    numbers.sort_unstable();
+
+    for pair in numbers.windows(2) {
+        if pair[0] == pair[1] {
+            let files: Vec<&str> = inner
+                .iter()
+                .filter(|(_, num)| *num == pair[0])
+                .map(|(file, _)| file.as_str())
+                .collect();
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Inner layer {} is used by more than one file: {}", pair[0], files.join(", ")),
+            ));
+        }
+    }
+
+    let missing: Vec<String> = (1..=numbers.last().copied().unwrap_or(0))
+        .filter(|num| !numbers.contains(num))
+        .map(|num| num.to_string())
+        .collect();
+    if !missing.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Copper stack has gaps, missing inner layers: {}", missing.join(", ")),
+        ));
+    }
+
+    let mut warnings = vec![];
+    let missing_sides: Vec<&str> = [(has_top, "top"), (has_bottom, "bottom")]
+        .iter()
+        .filter(|(present, _)| !present)
+        .map(|(_, side)| *side)
+        .collect();
+    if !numbers.is_empty() && !missing_sides.is_empty() {
+        warnings.push(format!(
+            "{} inner layers found but the {} copper layer is missing",
+            numbers.len(),
+            missing_sides.join(" and ")
+        ));
+    }
+    let copper = numbers.len() + has_top as usize + has_bottom as usize;
+    if copper > 2 && copper % 2 == 1 {
+        warnings.push(format!("Odd number of copper layers ({}), JLCPCB only builds even layer counts", copper));
+    }
+    Ok(warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_inner_number_from_capture_group() {
+        let re = Regex::new(r"-In(\d+)_Cu").unwrap();
+        assert_eq!(inner_number(&re, "board2-In1_Cu.g2"), Some(1));
+        let re = Regex::new(r"(?i)inner").unwrap();
+        assert_eq!(inner_number(&re, "board2-inner3.gbr"), Some(3));
+    }
+
+    fn source(file: &str, number: u32, plane: bool, position: Option<u32>) -> InnerSource {
+        InnerSource {
+            file: file.to_string(),
+            number,
+            plane,
+            position,
+        }
+    }
+
+    #[test]
+    fn renumbers_to_contiguous_order() {
+        let sources = vec![
+            source("b-In30_Cu", 30, false, None),
+            source("b-In1_Cu", 1, false, None),
+            source("b-In5_Cu", 5, false, None),
+        ];
+        assert_eq!(renumber(&sources).unwrap(), (vec![3, 1, 2], false));
+
+        let duplicate = vec![source("a", 2, false, None), source("b", 2, false, None)];
+        assert!(renumber(&duplicate).is_err());
+    }
+
+    #[test]
+    fn places_plane_layers() {
+        let sources = vec![
+            source("b.GP1", 1, true, None),
+            source("b.G1", 1, false, None),
+            source("b.G2", 2, false, None),
+        ];
+        assert_eq!(renumber(&sources).unwrap(), (vec![3, 1, 2], true));
+
+        // X2 属性给出了物理位置时按位置排列
+        let sources = vec![
+            source("b.GP1", 1, true, Some(2)),
+            source("b.G1", 1, false, Some(1)),
+            source("b.G2", 2, false, Some(3)),
+        ];
+        assert_eq!(renumber(&sources).unwrap(), (vec![2, 1, 3], false));
+    }
+
+    #[test]
+    fn rejects_gaps_in_copper_stack() {
+        let inner = vec![("a".to_string(), 1), ("b".to_string(), 3)];
+        assert!(check_stack(&inner, true, true).is_err());
+
+        let inner = vec![("a".to_string(), 1), ("b".to_string(), 2)];
+        assert!(check_stack(&inner, true, true).unwrap().is_empty());
+        assert_eq!(check_stack(&inner, true, false).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,62 @@
+// SPDX-FileCopyrightText: 2025 HalfSweet
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fmt::Display;
+
+use crate::gerber::Bounds;
+
+/// 下单时需要填写的板子信息
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoardSummary {
+    /// 板框的外接矩形（含圆弧），没有板框时为 `None`
+    pub outline: Option<Bounds>,
+    pub copper_layers: usize,
+}
+
+impl BoardSummary {
+    /// 板子的宽和高（毫米）
+    pub fn size(&self) -> Option<(f64, f64)> {
+        self.outline.map(|bounds| (bounds.width(), bounds.height()))
+    }
+
+    /// 追加到下单必读文件中的内容
+    ///
+    /// 该文件使用 GBK 编码，这里只使用 ASCII 字符。
+    pub fn notes(&self) -> String {
+        let size = match self.size() {
+            Some((width, height)) => format!("{:.2} x {:.2} mm", width, height),
+            None => "unknown (no board outline)".to_string(),
+        };
+        format!("\n\nBoard size: {}\nCopper layers: {}\n", size, self.copper_layers)
+    }
+}
+
+impl Display for BoardSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.size() {
+            Some((width, height)) => write!(f, "Board size: {:.2} x {:.2} mm", width, height)?,
+            None => write!(f, "Board size: unknown (no board outline)")?,
+        }
+        write!(f, ", copper layers: {}", self.copper_layers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_summary() {
+        let summary = BoardSummary {
+            outline: Some(Bounds { min_x: -3.0, min_y: 0.0, max_x: 20.0, max_y: 10.5 }),
+            copper_layers: 4,
+        };
+        assert_eq!(summary.to_string(), "Board size: 23.00 x 10.50 mm, copper layers: 4");
+        assert!(summary.notes().is_ascii());
+        assert!(summary.notes().contains("Copper layers: 4"));
+
+        let unknown = BoardSummary { outline: None, copper_layers: 2 };
+        assert_eq!(unknown.size(), None);
+        assert!(unknown.to_string().contains("unknown"));
+    }
+}
@@ -0,0 +1,174 @@
+// SPDX-FileCopyrightText: 2025 HalfSweet
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::Path;
+
+use chrono::NaiveDateTime;
+
+/// 输出名称模板中可用的变量
+#[derive(Debug, Clone)]
+pub struct TemplateVars {
+    /// 工程名称，取自输入文件夹或压缩包名称
+    pub project: String,
+
+    /// 输入所在 git 仓库的 `git describe` 结果
+    pub rev: String,
+
+    /// 铜层数量
+    pub layers: usize,
+
+    /// 转换时间
+    pub time: NaiveDateTime,
+}
+
+const VARIABLES: [&str; 5] = ["project", "rev", "layers", "date", "time"];
+
+/// 判断字符串中是否包含模板变量
+pub fn is_template(template: &str) -> bool {
+    template.contains('{')
+}
+
+/// 展开模板，例如 `{project}_{rev}_{date}_{layers}L`
+///
+/// `{date}` 和 `{time}` 可以带 chrono 格式，例如 `{date:%Y%m%d}`；`{{` 和 `}}` 表示字面量花括号。
+/// 变量的值中不能出现在文件名中的字符会被替换为 `_`，模板本身的路径分隔符保持不变。
+pub fn render(template: &str, vars: &TemplateVars) -> Result<String, std::io::Error> {
+    let mut result = String::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                result.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                result.push('}');
+            }
+            '{' => {
+                let mut spec = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => spec.push(c),
+                        None => return Err(invalid_template(template, "unclosed '{'")),
+                    }
+                }
+                let (name, format) = match spec.split_once(':') {
+                    Some((name, format)) => (name, Some(format)),
+                    None => (spec.as_str(), None),
+                };
+                let value = match (name, format) {
+                    ("project", None) => vars.project.clone(),
+                    ("rev", None) => vars.rev.clone(),
+                    ("layers", None) => vars.layers.to_string(),
+                    ("date", format) => format_time(template, vars, format.unwrap_or("%Y-%m-%d"))?,
+                    ("time", format) => format_time(template, vars, format.unwrap_or("%H%M%S"))?,
+                    _ => {
+                        return Err(invalid_template(
+                            template,
+                            &format!(
+                                "unknown variable '{}', expected one of: {}",
+                                spec,
+                                VARIABLES.join(", ")
+                            ),
+                        ))
+                    }
+                };
+                result.push_str(&sanitize(&value));
+            }
+            c => result.push(c),
+        }
+    }
+
+    Ok(result)
+}
+
+/// 按 strftime 格式输出时间，无效的格式返回错误而不是 panic
+fn format_time(template: &str, vars: &TemplateVars, format: &str) -> Result<String, std::io::Error> {
+    use chrono::format::{Item, StrftimeItems};
+    use std::fmt::Write;
+
+    let items: Vec<Item> = StrftimeItems::new(format).collect();
+    let invalid = || invalid_template(template, &format!("invalid date format '{}'", format));
+    if items.contains(&Item::Error) {
+        return Err(invalid());
+    }
+    // 时区等本地时间没有的字段在输出时才会失败
+    let mut value = String::new();
+    write!(value, "{}", vars.time.format_with_items(items.into_iter())).map_err(|_| invalid())?;
+    Ok(value)
+}
+
+fn invalid_template(template: &str, reason: &str) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        format!("Invalid name template '{}': {}", template, reason),
+    )
+}
+
+/// 替换文件名中不允许出现的字符
+fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// 获取 `dir` 所在 git 仓库的版本描述，不在仓库中或没有安装 git 时返回 `None`
+pub fn git_describe(dir: &Path) -> Option<String> {
+    let output = std::process::Command::new("git")
+        .args(["describe", "--tags", "--always", "--dirty"])
+        .current_dir(dir)
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let rev = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if rev.is_empty() {
+        None
+    } else {
+        Some(rev)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> TemplateVars {
+        TemplateVars {
+            project: "board_v3".to_string(),
+            rev: "v1.2-3-gabc/dirty".to_string(),
+            layers: 4,
+            time: chrono::NaiveDate::from_ymd_opt(2025, 3, 9)
+                .unwrap()
+                .and_hms_opt(8, 30, 0)
+                .unwrap(),
+        }
+    }
+
+    #[test]
+    fn renders_variables() {
+        let name = render("{project}_{rev}_{date}_{layers}L", &vars()).unwrap();
+        assert_eq!(name, "board_v3_v1.2-3-gabc_dirty_2025-03-09_4L");
+        assert_eq!(render("out/{date:%Y%m%d}", &vars()).unwrap(), "out/20250309");
+        assert_eq!(render("{{literal}}", &vars()).unwrap(), "{literal}");
+    }
+
+    #[test]
+    fn rejects_unknown_variables() {
+        assert!(render("{board}", &vars()).is_err());
+        assert!(render("{project", &vars()).is_err());
+        let err = render("x-{date:%Q}", &vars()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(render("{time:%Z}", &vars()).is_err());
+    }
+}
@@ -0,0 +1,199 @@
+// SPDX-FileCopyrightText: 2025 HalfSweet
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+use crate::mapping::MapTarget;
+use crate::FileName::KEYS;
+
+/// 向导中的一个输入文件
+#[derive(Debug, Clone)]
+pub struct WizardEntry {
+    pub file: PathBuf,
+
+    /// 猜测的JLC层
+    pub guess: Option<MapTarget>,
+
+    /// 猜测的依据，例如 `file content`
+    pub reason: String,
+}
+
+/// 向导结束时选择的保存方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveChoice {
+    /// 只用于本次转换
+    None,
+
+    /// 保存为工程配置中的 `[map]` 表
+    Project,
+
+    /// 保存为用户配置中的 `[mappings]` 规则，可用于其他工程
+    Style,
+}
+
+/// 向导的结果：每个文件名对应的JLC层
+#[derive(Debug, Clone)]
+pub struct WizardResult {
+    pub mappings: Vec<(String, MapTarget)>,
+    pub save: SaveChoice,
+}
+
+/// 逐个确认或修改文件对应的JLC层
+///
+/// 直接回车接受猜测（没有猜测时忽略该文件），也可以输入层名称、`InnerLayer<N>` 或 `ignore`，
+/// 输入 `?` 列出所有层名称。输入在读到末尾时按回车处理。
+pub fn run(
+    entries: &[WizardEntry],
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> Result<WizardResult, std::io::Error> {
+    writeln!(output, "Found {} files:", entries.len())?;
+    for (index, entry) in entries.iter().enumerate() {
+        writeln!(output, "  {:>2}. {} -> {}", index + 1, file_name(entry), describe(entry))?;
+    }
+    writeln!(output)?;
+    writeln!(
+        output,
+        "Press Enter to accept the guess, or type a layer name, InnerLayer<N> or ignore (? lists layer names)."
+    )?;
+
+    let mut mappings = vec![];
+    for (index, entry) in entries.iter().enumerate() {
+        let target = loop {
+            write!(
+                output,
+                "[{}/{}] {} [{}]: ",
+                index + 1,
+                entries.len(),
+                file_name(entry),
+                entry.guess.clone().unwrap_or(MapTarget::Ignore)
+            )?;
+            output.flush()?;
+
+            let answer = read_answer(input)?;
+            if answer.is_empty() {
+                break entry.guess.clone().unwrap_or(MapTarget::Ignore);
+            }
+            if answer == "?" {
+                writeln!(output, "{}, InnerLayer<N>, ignore", layer_names().join(", "))?;
+                continue;
+            }
+            match answer.parse::<MapTarget>() {
+                Ok(target) => break target,
+                Err(e) => writeln!(output, "{}", e)?,
+            }
+        };
+        mappings.push((file_name(entry), target));
+    }
+
+    writeln!(output)?;
+    let save = loop {
+        write!(
+            output,
+            "Save these mappings? [n]o, [p]roject config (.transjlc.toml), [s]tyle rules (user config) [n]: "
+        )?;
+        output.flush()?;
+        match read_answer(input)?.to_lowercase().as_str() {
+            "" | "n" | "no" => break SaveChoice::None,
+            "p" | "project" => break SaveChoice::Project,
+            "s" | "style" => break SaveChoice::Style,
+            _ => continue,
+        }
+    };
+
+    Ok(WizardResult { mappings, save })
+}
+
+fn file_name(entry: &WizardEntry) -> String {
+    entry
+        .file
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn describe(entry: &WizardEntry) -> String {
+    match &entry.guess {
+        Some(target) => format!("{} ({})", target, entry.reason),
+        None => "? (unknown, ignored by default)".to_string(),
+    }
+}
+
+fn layer_names() -> Vec<&'static str> {
+    KEYS.iter().copied().filter(|key| !matches!(*key, "InnerLayer" | "InnerPlane")).collect()
+}
+
+fn read_answer(input: &mut impl BufRead) -> Result<String, std::io::Error> {
+    let mut line = String::new();
+    input.read_line(&mut line)?;
+    Ok(line.trim().to_string())
+}
+
+/// 根据文件名生成可用于其他工程的规则：去掉所有文件共同的前缀（通常是工程名称）
+pub fn style_rule(file_name: &str, prefix: &str) -> String {
+    let suffix = file_name.strip_prefix(prefix).unwrap_or(file_name);
+    let suffix = if suffix.is_empty() { file_name } else { suffix };
+    if suffix.len() == file_name.len() {
+        format!("(?i)^{}$", regex::escape(file_name))
+    } else {
+        format!("(?i){}$", regex::escape(suffix))
+    }
+}
+
+/// 所有文件名的最长公共前缀，截断到最后一个分隔符之前，分隔符留在规则中
+pub fn common_prefix(names: &[String]) -> String {
+    let Some(first) = names.first() else {
+        return String::new();
+    };
+    let mut prefix = first.as_str();
+    for name in &names[1..] {
+        while !name.starts_with(prefix) {
+            prefix = &prefix[..prefix.char_indices().last().map(|(i, _)| i).unwrap_or(0)];
+        }
+    }
+    match prefix.rfind(|c: char| !c.is_alphanumeric()) {
+        Some(index) => prefix[..index].to_string(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, guess: Option<MapTarget>) -> WizardEntry {
+        WizardEntry {
+            file: PathBuf::from(name),
+            guess,
+            reason: "file name".to_string(),
+        }
+    }
+
+    #[test]
+    fn accepts_and_reassigns_layers() {
+        let entries = [
+            entry("board-top.gbr", Some(MapTarget::Layer("Top_Layer"))),
+            entry("board-edge.gbr", None),
+            entry("board-mid.gbr", None),
+        ];
+        let mut input = "\nboard_outline\nfoo\nInnerLayer1\np\n".as_bytes();
+        let mut output = vec![];
+        let result = run(&entries, &mut input, &mut output).unwrap();
+
+        let targets: Vec<String> = result.mappings.iter().map(|(_, t)| t.to_string()).collect();
+        assert_eq!(targets, ["Top_Layer", "Board_Outline", "InnerLayer1"]);
+        assert_eq!(result.save, SaveChoice::Project);
+        assert!(String::from_utf8(output).unwrap().contains("Unknown mapping target 'foo'"));
+    }
+
+    #[test]
+    fn builds_style_rules_without_project_prefix() {
+        let names = vec!["board_v3-top.gbr".to_string(), "board_v3-bottom.gbr".to_string()];
+        let prefix = common_prefix(&names);
+        assert_eq!(prefix, "board_v3");
+        assert_eq!(style_rule(&names[0], &prefix), r"(?i)\-top\.gbr$");
+        assert_eq!(common_prefix(&["a-top.gbr".to_string(), "a-topmask.gbr".to_string()]), "a");
+        assert_eq!(style_rule("outline.gbr", ""), r"(?i)^outline\.gbr$");
+    }
+}